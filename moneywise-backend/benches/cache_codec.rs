@@ -0,0 +1,91 @@
+//! Size and latency of cache codecs on a large category list.
+//!
+//! Run with `cargo bench --bench cache_codec`. Encoded sizes are printed
+//! once before the timings.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use moneywise_backend::{
+    cache::core::codec::{decode, CacheCompression, CacheFormat, Codec},
+    models::CategoryBudgetApi,
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// Roughly a busy month: many categories, most of them grouped
+fn categories() -> Vec<CategoryBudgetApi> {
+    (0..200)
+        .map(|i| CategoryBudgetApi {
+            id: format!("8d0b9b6f-5cfa-43ef-9a48-{:012}", i),
+            category_name: format!("Category {}", i),
+            group_name: (i % 5 != 0).then(|| format!("Group {}", i % 7)),
+            category_color: "#4CAF50".to_string(),
+            group_color: (i % 5 != 0).then(|| "#2196F3".to_string()),
+            planned: Decimal::from_str("1200.50").unwrap(),
+            spent: Decimal::from_str("850.25").unwrap(),
+            remaining: Decimal::from_str("350.25").unwrap(),
+            percentage: Decimal::from_str("70.83").unwrap(),
+            currency: "EUR".to_string(),
+        })
+        .collect()
+}
+
+fn codecs() -> Vec<Codec> {
+    let mut codecs = Vec::new();
+    for format in [
+        CacheFormat::Json,
+        CacheFormat::MessagePack,
+        CacheFormat::Bincode,
+    ] {
+        for compression in [
+            CacheCompression::None,
+            CacheCompression::Zstd,
+            CacheCompression::Lz4,
+        ] {
+            codecs.push(Codec {
+                format,
+                compression,
+                compression_threshold: 0,
+            });
+        }
+    }
+    codecs
+}
+
+fn bench_codecs(c: &mut Criterion) {
+    let data = categories();
+
+    println!("encoded size of {} categories:", data.len());
+    for codec in codecs() {
+        let size = codec.encode(&data).unwrap().len();
+        println!(
+            "  {:>8}+{:<5} {:>7} bytes",
+            codec.format, codec.compression, size
+        );
+    }
+
+    let mut encode = c.benchmark_group("encode");
+    for codec in codecs() {
+        let name = format!("{}+{}", codec.format, codec.compression);
+        encode.bench_function(&name, |b| {
+            b.iter(|| codec.encode(black_box(&data)).unwrap())
+        });
+    }
+    encode.finish();
+
+    let mut decode_group = c.benchmark_group("decode");
+    for codec in codecs() {
+        let name = format!("{}+{}", codec.format, codec.compression);
+        let value = codec.encode(&data).unwrap();
+        decode_group.bench_function(&name, |b| {
+            b.iter(|| {
+                decode::<Vec<CategoryBudgetApi>>(black_box(&value))
+                    .unwrap()
+                    .unwrap()
+            })
+        });
+    }
+    decode_group.finish();
+}
+
+criterion_group!(benches, bench_codecs);
+criterion_main!(benches);
@@ -0,0 +1,6 @@
+// Rebuild when migrations change, since `sqlx::migrate!` embeds them, and
+// when the offline query metadata checked by `query!` changes
+fn main() {
+    println!("cargo:rerun-if-changed=database/migrations");
+    println!("cargo:rerun-if-changed=sqlx-data.json");
+}
@@ -2,7 +2,7 @@
 -- ⚠️  AUTO-GENERATED FILE - DO NOT EDIT DIRECTLY  ⚠️
 -- This file combines all schema components for one-time deployment to Supabase
 -- Generated from modular schema files in database/schema/
--- Generated on: mer. 27 août 2025 10:41:01 CEST
+-- Generated on: Sun Oct 18 12:15:40 UTC 2026
 --
 -- To modify the schema:
 -- 1. Edit files in ../schema/ directory
@@ -88,6 +88,21 @@ CREATE TABLE IF NOT EXISTS public.budgets (
     CONSTRAINT budgets_currency_check CHECK (length(currency) = 3)
 );
 
+-- Step 3b: Create api_keys table
+-- Hashed API keys used to give households their own rate-limit quotas
+CREATE TABLE IF NOT EXISTS public.api_keys (
+    id uuid NOT NULL DEFAULT uuid_generate_v4(),
+    name text NOT NULL,
+    key_hash text NOT NULL,
+    plan text NOT NULL DEFAULT 'free',
+    revoked_at timestamp with time zone,
+    created_at timestamp with time zone NOT NULL DEFAULT now(),
+    updated_at timestamp with time zone NOT NULL DEFAULT now(),
+    CONSTRAINT api_keys_pkey PRIMARY KEY (id),
+    CONSTRAINT api_keys_key_hash_uniq UNIQUE (key_hash),
+    CONSTRAINT api_keys_plan_check CHECK (plan = ANY (ARRAY['free'::text, 'family'::text]))
+);
+
 -- ==============================================================================
 -- INDEXES FOR PERFORMANCE
 -- ==============================================================================
@@ -139,6 +154,12 @@ CREATE OR REPLACE TRIGGER trg_budgets_updated
     FOR EACH ROW
     EXECUTE FUNCTION public.update_updated_at_column();
 
+-- API keys trigger
+CREATE OR REPLACE TRIGGER trg_api_keys_updated
+    BEFORE UPDATE ON public.api_keys
+    FOR EACH ROW
+    EXECUTE FUNCTION public.update_updated_at_column();
+
 -- ==============================================================================
 -- SAMPLE DATA INSERTION
 -- ==============================================================================
@@ -0,0 +1,30 @@
+-- MoneyWise API Keys Migration
+-- 📝  MANUALLY MAINTAINED FILE - CAN BE EDITED FOR DEVELOPMENT  📝
+-- Adds hashed API keys so the rate limiter can apply per-key plan quotas
+-- instead of sharing one bucket per IP address.
+--
+-- Keys are issued through the admin API; only the SHA-256 hash is stored.
+
+-- Step 1: Create api_keys table
+CREATE TABLE IF NOT EXISTS public.api_keys (
+    id uuid NOT NULL DEFAULT uuid_generate_v4(),
+    name text NOT NULL,
+    key_hash text NOT NULL,
+    plan text NOT NULL DEFAULT 'free',
+    revoked_at timestamp with time zone,
+    created_at timestamp with time zone NOT NULL DEFAULT now(),
+    updated_at timestamp with time zone NOT NULL DEFAULT now(),
+    CONSTRAINT api_keys_pkey PRIMARY KEY (id),
+    CONSTRAINT api_keys_key_hash_uniq UNIQUE (key_hash), -- also serves per-request lookups
+    CONSTRAINT api_keys_plan_check CHECK (plan = ANY (ARRAY['free'::text, 'family'::text]))
+);
+
+-- Step 2: Keep updated_at current
+CREATE OR REPLACE TRIGGER trg_api_keys_updated
+    BEFORE UPDATE ON public.api_keys
+    FOR EACH ROW
+    EXECUTE FUNCTION public.update_updated_at_column();
+
+COMMENT ON TABLE public.api_keys IS 'Hashed API keys with rate-limit plan tiers';
+COMMENT ON COLUMN public.api_keys.key_hash IS 'Hex-encoded SHA-256 of the issued key; the plaintext is never stored';
+COMMENT ON COLUMN public.api_keys.plan IS 'Rate-limit plan tier (free, family)';
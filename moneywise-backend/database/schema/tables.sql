@@ -67,3 +67,18 @@ CREATE TABLE IF NOT EXISTS public.budgets (
     CONSTRAINT budgets_year_check CHECK (year >= 2000),
     CONSTRAINT budgets_currency_check CHECK (length(currency) = 3)
 );
+
+-- Step 3b: Create api_keys table
+-- Hashed API keys used to give households their own rate-limit quotas
+CREATE TABLE IF NOT EXISTS public.api_keys (
+    id uuid NOT NULL DEFAULT uuid_generate_v4(),
+    name text NOT NULL,
+    key_hash text NOT NULL,
+    plan text NOT NULL DEFAULT 'free',
+    revoked_at timestamp with time zone,
+    created_at timestamp with time zone NOT NULL DEFAULT now(),
+    updated_at timestamp with time zone NOT NULL DEFAULT now(),
+    CONSTRAINT api_keys_pkey PRIMARY KEY (id),
+    CONSTRAINT api_keys_key_hash_uniq UNIQUE (key_hash),
+    CONSTRAINT api_keys_plan_check CHECK (plan = ANY (ARRAY['free'::text, 'family'::text]))
+);
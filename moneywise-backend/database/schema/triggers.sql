@@ -16,3 +16,9 @@ CREATE OR REPLACE TRIGGER trg_budgets_updated
     BEFORE UPDATE ON public.budgets
     FOR EACH ROW
     EXECUTE FUNCTION public.update_updated_at_column();
+
+-- API keys trigger
+CREATE OR REPLACE TRIGGER trg_api_keys_updated
+    BEFORE UPDATE ON public.api_keys
+    FOR EACH ROW
+    EXECUTE FUNCTION public.update_updated_at_column();
@@ -0,0 +1,483 @@
+//! Admin API for MoneyWise backend.
+//!
+//! Operator-only routes (API key issuance, rate limit inspection, and cache
+//! stats, inspection and flush). Every route requires the
+//! `x-admin-token` header to match the `ADMIN_API_TOKEN` environment
+//! variable; when the variable is unset the admin API is disabled.
+
+use axum::{
+    extract::{Query, State},
+    http::{Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    Extension,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::{
+    api::budget::AppState,
+    cache::{
+        core::{metrics::CacheStats, service::KEY_PREFIX},
+        domains::budget::keys,
+    },
+    error::{AppError, Result},
+    rate_limiter::{
+        api_keys::IssuedApiKey,
+        middleware::validate_device_id,
+        types::{RateLimitStatus, TransactionType},
+        ApiKeyStore, ClassifiedRouter, PlanTier, RateLimitService,
+    },
+    server::request_id::insert_request_id,
+};
+
+/// Header carrying the admin token
+pub const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+/// Maximum length of a human-readable API key name
+const MAX_API_KEY_NAME_LEN: usize = 100;
+
+/// Keys listed by `GET /admin/cache/keys` unless `limit` is given
+const DEFAULT_CACHE_KEYS_LIMIT: usize = 100;
+
+/// Upper bound on `limit` for `GET /admin/cache/keys`
+const MAX_CACHE_KEYS_LIMIT: usize = 1000;
+
+/// Payload for issuing a new API key
+#[derive(Debug, Deserialize)]
+pub struct IssueApiKeyRequest {
+    pub name: String,
+    pub plan: Option<PlanTier>, // Defaults to the free plan
+}
+
+/// Query identifying the client whose rate limit counters to inspect
+#[derive(Debug, Deserialize)]
+pub struct RateLimitQuery {
+    pub ip: String,
+    pub device: Option<String>, // Counters without a device use "unknown"
+}
+
+/// Current rate limit counters for a client
+#[derive(Debug, Serialize)]
+pub struct RateLimitsResponse {
+    pub ip: String,
+    pub device: Option<String>,
+    pub limits: Vec<RateLimitStatus>,
+}
+
+/// Result of resetting a client's counters
+#[derive(Debug, Serialize)]
+pub struct RateLimitResetResponse {
+    pub ip: String,
+    pub device: Option<String>,
+    pub reset: u32, // Number of counters that existed and were deleted
+}
+
+/// Result of flushing the budget cache namespace
+#[derive(Debug, Serialize)]
+pub struct CacheFlushResponse {
+    pub namespace: &'static str,
+    pub deleted: usize, // Keys deleted across all schema versions
+}
+
+/// Query selecting cache keys by prefix
+#[derive(Debug, Deserialize)]
+pub struct CacheKeysQuery {
+    pub prefix: String, // Must start with the cache key prefix `moneywise:`
+    pub limit: Option<usize>, // Listing only; defaults to 100, at most 1000
+}
+
+/// Cache keys under a prefix
+#[derive(Debug, Serialize)]
+pub struct CacheKeysResponse {
+    pub prefix: String,
+    pub total: usize,
+    pub keys: Vec<String>, // Sorted, at most `limit` keys
+    pub truncated: bool,
+}
+
+/// Result of purging a cache prefix
+#[derive(Debug, Serialize)]
+pub struct CachePurgeResponse {
+    pub prefix: String,
+    pub deleted: usize,
+}
+
+/// Creates the admin router; the token is read once from `ADMIN_API_TOKEN`
+pub fn admin_routes() -> ClassifiedRouter<AppState> {
+    let admin_token = Arc::new(
+        std::env::var("ADMIN_API_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty()),
+    );
+    if admin_token.is_none() {
+        tracing::warn!("ADMIN_API_TOKEN is not set; admin API is disabled");
+    }
+
+    ClassifiedRouter::new()
+        .post(
+            "/api-keys",
+            issue_api_key,
+            TransactionType::BudgetModification,
+        )
+        .get("/rate-limits", get_rate_limits, TransactionType::BudgetRead)
+        .delete(
+            "/rate-limits",
+            reset_rate_limits,
+            TransactionType::BudgetModification,
+        )
+        .post(
+            "/cache/flush",
+            flush_cache,
+            TransactionType::BudgetModification,
+        )
+        .get("/cache/stats", get_cache_stats, TransactionType::BudgetRead)
+        .get("/cache/keys", list_cache_keys, TransactionType::BudgetRead)
+        .delete(
+            "/cache/keys",
+            purge_cache_keys,
+            TransactionType::BudgetModification,
+        )
+        .map_router(|router| {
+            router.route_layer(middleware::from_fn_with_state(
+                admin_token,
+                require_admin_token,
+            ))
+        })
+}
+
+/// Issues a new API key for per-key rate limiting.
+///
+/// The plaintext key is returned once; only its hash is stored.
+///
+/// Requires the `ApiKeyStore` extension installed by `main`.
+///
+/// # Examples
+///
+/// Request:
+/// ```bash
+/// curl -s -X POST "http://localhost:3000/admin/api-keys" \
+///   -H "x-admin-token: $ADMIN_API_TOKEN" \
+///   -H 'Content-Type: application/json' \
+///   -d '{ "name": "Smith household", "plan": "family" }'
+/// ```
+///
+/// Response body (JSON, 201 Created):
+/// ```json
+/// {
+///   "id": "5b1f...",
+///   "name": "Smith household",
+///   "plan": "family",
+///   "key": "mw_3f9a...",
+///   "created_at": "2025-09-16T09:00:00Z"
+/// }
+/// ```
+async fn issue_api_key(
+    Extension(api_keys): Extension<ApiKeyStore>,
+    Json(payload): Json<IssueApiKeyRequest>,
+) -> Result<(StatusCode, Json<IssuedApiKey>)> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Name is required".to_string()));
+    }
+    if name.len() > MAX_API_KEY_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "Name must be at most {} characters",
+            MAX_API_KEY_NAME_LEN
+        )));
+    }
+
+    let plan = payload.plan.unwrap_or(PlanTier::Free);
+    let issued = api_keys.issue(name, plan).await?;
+
+    Ok((StatusCode::CREATED, Json(issued)))
+}
+
+/// Shows the current counters of a client for every transaction type.
+///
+/// Requires the `RateLimitService` extension installed by `main`.
+///
+/// # Examples
+///
+/// Request:
+/// ```bash
+/// curl -s "http://localhost:3000/admin/rate-limits?ip=203.0.113.7&device=ios-1234abcd" \
+///   -H "x-admin-token: $ADMIN_API_TOKEN"
+/// ```
+///
+/// Response body (JSON):
+/// ```json
+/// {
+///   "ip": "203.0.113.7",
+///   "device": "ios-1234abcd",
+///   "limits": [
+///     {
+///       "transaction_type": "BudgetModification",
+///       "key": "rate_limit:203.0.113.7:ios-1234abcd:budget_modification",
+///       "limit": 30,
+///       "count": 30,
+///       "remaining": 0,
+///       "reset_in_seconds": 87
+///     }
+///   ]
+/// }
+/// ```
+async fn get_rate_limits(
+    Extension(rate_limiter): Extension<Arc<RateLimitService>>,
+    Query(query): Query<RateLimitQuery>,
+) -> Result<Json<RateLimitsResponse>> {
+    validate_rate_limit_query(&query)?;
+
+    let limits = rate_limiter
+        .inspect(&query.ip, query.device.as_deref())
+        .await?;
+
+    Ok(Json(RateLimitsResponse {
+        ip: query.ip,
+        device: query.device,
+        limits,
+    }))
+}
+
+/// Resets every counter of a client so it is no longer throttled.
+///
+/// # Examples
+///
+/// Request:
+/// ```bash
+/// curl -s -X DELETE "http://localhost:3000/admin/rate-limits?ip=203.0.113.7&device=ios-1234abcd" \
+///   -H "x-admin-token: $ADMIN_API_TOKEN"
+/// ```
+///
+/// Response body (JSON):
+/// ```json
+/// { "ip": "203.0.113.7", "device": "ios-1234abcd", "reset": 2 }
+/// ```
+async fn reset_rate_limits(
+    Extension(rate_limiter): Extension<Arc<RateLimitService>>,
+    Query(query): Query<RateLimitQuery>,
+) -> Result<Json<RateLimitResetResponse>> {
+    validate_rate_limit_query(&query)?;
+
+    let reset = rate_limiter
+        .reset(&query.ip, query.device.as_deref())
+        .await?;
+
+    Ok(Json(RateLimitResetResponse {
+        ip: query.ip,
+        device: query.device,
+        reset,
+    }))
+}
+
+/// Deletes every key in the budget cache namespace, whatever its schema
+/// version. Useful after a deploy to drop entries of the previous models
+/// instead of waiting for their TTL.
+///
+/// # Examples
+///
+/// Request:
+/// ```bash
+/// curl -s -X POST "http://localhost:3000/admin/cache/flush" \
+///   -H "x-admin-token: $ADMIN_API_TOKEN"
+/// ```
+///
+/// Response body (JSON):
+/// ```json
+/// { "namespace": "moneywise:{budget:", "deleted": 42 }
+/// ```
+async fn flush_cache(
+    State((_repo, cache)): State<AppState>,
+) -> Result<Json<CacheFlushResponse>> {
+    let deleted = cache.flush().await?;
+
+    Ok(Json(CacheFlushResponse {
+        namespace: keys::NAMESPACE,
+        deleted,
+    }))
+}
+
+/// Shows cache hits, misses, purged corrupt entries, retries and mean
+/// latencies per domain since this instance started, and whether Redis is
+/// currently bypassed by the circuit breaker.
+///
+/// # Examples
+///
+/// Request:
+/// ```bash
+/// curl -s "http://localhost:3000/admin/cache/stats" \
+///   -H "x-admin-token: $ADMIN_API_TOKEN"
+/// ```
+///
+/// Response body (JSON):
+/// ```json
+/// {
+///   "backend": "redis",
+///   "hits": 940,
+///   "misses": 60,
+///   "corrupt": 0,
+///   "hit_ratio": 0.94,
+///   "retries": 2,
+///   "circuit_open": false,
+///   "domains": {
+///     "budget": {
+///       "hits": 940,
+///       "misses": 60,
+///       "corrupt": 0,
+///       "hit_ratio": 0.94,
+///       "latency": {
+///         "get": { "count": 1000, "mean_ms": 0.8 },
+///         "set": { "count": 60, "mean_ms": 1.1 }
+///       }
+///     }
+///   }
+/// }
+/// ```
+async fn get_cache_stats(
+    State((_repo, cache)): State<AppState>,
+) -> Json<CacheStats> {
+    Json(cache.service().stats())
+}
+
+/// Lists cache keys under a prefix, found with `SCAN`.
+///
+/// # Examples
+///
+/// Request:
+/// ```bash
+/// curl -s "http://localhost:3000/admin/cache/keys?prefix=moneywise:%7Bbudget:2025-08%7D:&limit=2" \
+///   -H "x-admin-token: $ADMIN_API_TOKEN"
+/// ```
+///
+/// Response body (JSON):
+/// ```json
+/// {
+///   "prefix": "moneywise:{budget:2025-08}:",
+///   "total": 14,
+///   "keys": [
+///     "moneywise:{budget:2025-08}:v1a2b3c4d:overview:8:2025",
+///     "moneywise:{budget:2025-08}:v1a2b3c4d:overview:8:2025:USD"
+///   ],
+///   "truncated": true
+/// }
+/// ```
+async fn list_cache_keys(
+    State((_repo, cache)): State<AppState>,
+    Query(query): Query<CacheKeysQuery>,
+) -> Result<Json<CacheKeysResponse>> {
+    validate_cache_prefix(&query.prefix)?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_CACHE_KEYS_LIMIT)
+        .min(MAX_CACHE_KEYS_LIMIT);
+
+    let mut keys = cache.service().keys_with_prefix(&query.prefix).await?;
+    keys.sort();
+    let total = keys.len();
+    keys.truncate(limit);
+
+    Ok(Json(CacheKeysResponse {
+        prefix: query.prefix,
+        total,
+        truncated: total > keys.len(),
+        keys,
+    }))
+}
+
+/// Deletes every cache key under a prefix (e.g. one month's overviews).
+///
+/// # Examples
+///
+/// Request:
+/// ```bash
+/// curl -s -X DELETE "http://localhost:3000/admin/cache/keys?prefix=moneywise:%7Bbudget:2025-08%7D:" \
+///   -H "x-admin-token: $ADMIN_API_TOKEN"
+/// ```
+///
+/// Response body (JSON):
+/// ```json
+/// { "prefix": "moneywise:{budget:2025-08}:", "deleted": 14 }
+/// ```
+async fn purge_cache_keys(
+    State((_repo, cache)): State<AppState>,
+    Query(query): Query<CacheKeysQuery>,
+) -> Result<Json<CachePurgeResponse>> {
+    validate_cache_prefix(&query.prefix)?;
+
+    let deleted = cache.service().flush_namespace(&query.prefix).await?;
+
+    Ok(Json(CachePurgeResponse {
+        prefix: query.prefix,
+        deleted,
+    }))
+}
+
+/// Reject queries that could never match a counter written by the middleware
+fn validate_rate_limit_query(query: &RateLimitQuery) -> Result<()> {
+    if query.ip.trim().is_empty() {
+        return Err(AppError::Validation("ip is required".to_string()));
+    }
+    if let Some(device) = &query.device {
+        if !validate_device_id(device) {
+            return Err(AppError::Validation(
+                "device must be 8-64 characters (letters, digits, '-', '_')"
+                    .to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Keep inspection and purges inside the cache's own keys, so rate limit
+/// counters sharing the same Redis can't be listed or deleted
+fn validate_cache_prefix(prefix: &str) -> Result<()> {
+    if !prefix.starts_with(KEY_PREFIX) {
+        return Err(AppError::Validation(format!(
+            "prefix must start with '{}'",
+            KEY_PREFIX
+        )));
+    }
+    Ok(())
+}
+
+/// Rejects requests whose `x-admin-token` does not match the configured token
+async fn require_admin_token<B>(
+    State(admin_token): State<Arc<Option<String>>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let Some(expected) = admin_token.as_deref() else {
+        return admin_error(StatusCode::FORBIDDEN, "Admin API is disabled");
+    };
+
+    let presented = req
+        .headers()
+        .get(ADMIN_TOKEN_HEADER)
+        .and_then(|h| h.to_str().ok());
+
+    match presented {
+        Some(token) if constant_time_eq(token, expected) => next.run(req).await,
+        _ => admin_error(StatusCode::UNAUTHORIZED, "Invalid admin token"),
+    }
+}
+
+/// Compare tokens without short-circuiting on the first differing byte
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+/// Build an error body matching `AppError::into_response`
+fn admin_error(status: StatusCode, message: &str) -> Response {
+    let mut body = json!({
+        "error": message,
+        "status": status.as_u16()
+    });
+    insert_request_id(&mut body);
+
+    (status, Json(body)).into_response()
+}
@@ -5,23 +5,22 @@
 use axum::{
     extract::{Path, Query, State},
     response::Json,
-    routing::{get, post, put},
-    Router,
 };
 use chrono::Datelike;
 use rust_decimal::Decimal;
 use serde::Deserialize;
-use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
 use crate::{
     cache::domains::budget::BudgetCache,
     error::{AppError, Result},
     models::*,
+    rate_limiter::{types::TransactionType, ClassifiedRouter},
+    repository::{NewBudget, SharedBudgetRepository},
 };
 
-/// Application state containing database pool and budget cache service
-pub type AppState = (PgPool, BudgetCache);
+/// Application state containing the budget repository and cache service
+pub type AppState = (SharedBudgetRepository, BudgetCache);
 
 /// Query parameters for budget filtering
 #[derive(Debug, Deserialize)]
@@ -31,14 +30,19 @@ pub struct BudgetQuery {
     pub currency: Option<String>,
 }
 
-/// Creates and configures the budget router with all budget-related endpoints
-pub fn budget_routes() -> Router<AppState> {
-    Router::new()
-        .route("/", get(get_budgets))
-        .route("/overview", get(get_budget_overview))
-        .route("/", post(create_budget))
-        .route("/:id", put(update_budget))
-        .route("/:id", get(get_budget_by_id))
+/// Creates and configures the budget router with all budget-related endpoints.
+/// Each route declares the rate-limit class it is counted against.
+pub fn budget_routes() -> ClassifiedRouter<AppState> {
+    ClassifiedRouter::new()
+        .get("/", get_budgets, TransactionType::BudgetRead)
+        .get(
+            "/overview",
+            get_budget_overview,
+            TransactionType::BudgetOverview,
+        )
+        .post("/", create_budget, TransactionType::BudgetModification)
+        .put("/:id", update_budget, TransactionType::BudgetModification)
+        .get("/:id", get_budget_by_id, TransactionType::BudgetRead)
 }
 
 // ================================================================
@@ -67,7 +71,7 @@ pub fn budget_routes() -> Router<AppState> {
 /// }
 /// ```
 async fn get_budget_overview(
-    State((pool, cache)): State<AppState>,
+    State((repo, cache)): State<AppState>,
     Query(query): Query<BudgetQuery>,
 ) -> Result<Json<BudgetOverviewApi>> {
     let month = query
@@ -79,29 +83,19 @@ async fn get_budget_overview(
     let month_str = month.to_string();
     let year_str = year.to_string();
 
-    let currency_filter = query.currency.as_deref();
-
-    // Try to get data from cache first
-    if let Some(cached_overview) = cache
-        .get_cached_budget_overview(&month_str, &year_str, currency_filter)
-        .await?
-    {
-        return Ok(Json(cached_overview));
-    }
-
-    // Cache miss - fetch from database and cache the result
-    let overview =
-        get_budget_overview_data(&pool, month, year, currency_filter).await?;
+    let currency_filter = query.currency.clone();
 
-    // Cache the result for future requests (don't block on cache write)
-    let _ = cache
-        .cache_budget_overview(
+    // Serve from cache; on a miss only one concurrent request hits the database
+    let overview = cache
+        .get_or_fill_budget_overview(
             &month_str,
             &year_str,
-            currency_filter,
-            &overview,
+            query.currency.as_deref(),
+            move || async move {
+                repo.overview(month, year, currency_filter.as_deref()).await
+            },
         )
-        .await;
+        .await?;
 
     Ok(Json(overview))
 }
@@ -147,7 +141,7 @@ async fn get_budget_overview(
 /// }
 /// ```
 async fn get_budgets(
-    State((pool, cache)): State<AppState>,
+    State((repo, cache)): State<AppState>,
     Query(query): Query<BudgetQuery>,
 ) -> Result<Json<BudgetResponse>> {
     // Default to current month/year if not provided
@@ -161,48 +155,38 @@ async fn get_budgets(
     let month_str = month.to_string();
     let year_str = year.to_string();
 
-    // Try to get cached data first
+    // Serve from cache; on a miss only one concurrent request per key hits
+    // the database
     let currency_filter = query.currency.as_deref();
-    let cached_overview = cache
-        .get_cached_budget_overview(&month_str, &year_str, currency_filter)
-        .await?;
-    let cached_categories = cache
-        .get_cached_category_budgets(&month_str, &year_str, currency_filter)
-        .await?;
-
-    let (overview, categories) = match (cached_overview, cached_categories) {
-        (Some(overview), Some(categories)) => {
-            // Cache hit for both overview and categories
-            (overview, categories)
-        }
-        _ => {
-            // Cache miss - fetch from database and cache the results
-            let (overview, categories) = tokio::try_join!(
-                get_budget_overview_data(&pool, month, year, currency_filter),
-                get_category_budgets(&pool, month, year, currency_filter),
-            )?;
-
-            // Cache the results for future requests (don't block on cache writes)
-            let _ = cache
-                .cache_budget_overview(
-                    &month_str,
-                    &year_str,
-                    currency_filter,
-                    &overview,
-                )
-                .await;
-            let _ = cache
-                .cache_category_budgets(
-                    &month_str,
-                    &year_str,
-                    currency_filter,
-                    &categories,
-                )
-                .await;
-
-            (overview, categories)
-        }
-    };
+    let (overview_repo, overview_currency) =
+        (repo.clone(), query.currency.clone());
+    let (categories_repo, categories_currency) = (repo, query.currency.clone());
+    let (overview, categories) = tokio::try_join!(
+        cache.get_or_fill_budget_overview(
+            &month_str,
+            &year_str,
+            currency_filter,
+            move || async move {
+                overview_repo
+                    .overview(month, year, overview_currency.as_deref())
+                    .await
+            },
+        ),
+        cache.get_or_fill_category_budgets(
+            &month_str,
+            &year_str,
+            currency_filter,
+            move || async move {
+                categories_repo
+                    .category_budgets(
+                        month,
+                        year,
+                        categories_currency.as_deref(),
+                    )
+                    .await
+            },
+        ),
+    )?;
 
     // Generate insights based on the retrieved data
     // This is done in-memory since it's lightweight and doesn't require DB access
@@ -220,7 +204,7 @@ async fn get_budgets(
 /// Security and validation considerations:
 /// - Uses UUID for ID generation (prevents enumeration attacks)
 /// - Validates input through serde deserialization
-/// - Uses parameterized queries to prevent SQL injection
+/// - Unknown categories and duplicate month/category pairs are 400s
 /// - Returns complete budget object for immediate use
 /// - Invalidates related cache entries to ensure data consistency
 ///
@@ -255,7 +239,7 @@ async fn get_budgets(
 /// }
 /// ```
 async fn create_budget(
-    State((pool, cache)): State<AppState>,
+    State((repo, cache)): State<AppState>,
     Json(payload): Json<CreateBudgetRequest>,
 ) -> Result<Json<BudgetApi>> {
     // Validate input data
@@ -277,7 +261,7 @@ async fn create_budget(
 
     // Validate optional month/year range to match database constraints
     if let Some(m) = payload.month {
-        if m < 1 || m > 12 {
+        if !(1..=12).contains(&m) {
             return Err(AppError::Validation(
                 "Month must be between 1 and 12".to_string(),
             ));
@@ -309,69 +293,23 @@ async fn create_budget(
         .unwrap_or_else(|| chrono::Utc::now().month() as i16);
     let year = payload.year.unwrap_or_else(|| chrono::Utc::now().year());
 
-    // Generate a secure UUID for the budget ID
-    // This prevents ID enumeration and provides global uniqueness
-    let id = Uuid::new_v4();
-
-    // Use parameterized query to prevent SQL injection
-    // The query_as! macro provides compile-time SQL validation
-    let budget = match sqlx::query_as::<_, Budget>(
-        r#"
-        INSERT INTO budgets (id, month, year, category_id, planned, currency)
-        VALUES ($1::uuid, $2, $3, $4::uuid, $5, $6)
-        RETURNING id, month, year, category_id, planned, spent, carryover, currency, created_at, updated_at
-        "#,
-    )
-    .bind(id)
-    .bind(month as i16)
-    .bind(year)
-    .bind(category_id)
-    .bind(payload.planned)
-    .bind(&payload.currency)
-    .fetch_one(&pool)
-    .await {
-        Ok(row) => row,
-        Err(e) => {
-            // Map unique constraint violation (year, month, category_id) to a friendly 400 error
-            if let sqlx::Error::Database(db_err) = &e {
-                if db_err.code().as_deref() == Some("23505") {
-                    return Err(AppError::Validation(
-                        "Budget already exists for this year, month, and category".to_string(),
-                    ));
-                }
-            }
-            return Err(AppError::Database(e));
-        }
-    };
-
-    // Convert database model to API model
-    // This separation ensures API stability even if database schema changes
-    let budget_api = BudgetApi {
-        id: budget.id.to_string(),
-        month: budget.month,
-        year: budget.year,
-        category_id: budget.category_id.to_string(),
-        planned: budget.planned,
-        spent: budget.spent,
-        carryover: budget.carryover,
-        currency: budget.currency,
-        created_at: budget.created_at,
-        updated_at: budget.updated_at,
-    };
-
-    // Invalidate cache for this month/year since we added a new budget
-    // This ensures cache consistency when new data is added
+    let budget = repo
+        .create(NewBudget {
+            month,
+            year,
+            category_id,
+            planned: payload.planned,
+            currency: payload.currency,
+        })
+        .await?;
+
+    // Invalidate everything cached for this month/year since we added a new
+    // budget; the period tag also covers the currency-less overview
     let month_str = budget.month.to_string();
     let year_str = budget.year.to_string();
-    let _ = cache
-        .invalidate_month_cache(
-            &month_str,
-            &year_str,
-            Some(payload.currency.as_str()),
-        )
-        .await;
+    let _ = cache.invalidate_period(&month_str, &year_str).await;
 
-    Ok(Json(budget_api))
+    Ok(Json(BudgetApi::from(budget)))
 }
 
 /// Updates an existing budget entry
@@ -408,7 +346,7 @@ async fn create_budget(
 /// }
 /// ```
 async fn update_budget(
-    State((pool, cache)): State<AppState>,
+    State((repo, cache)): State<AppState>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateBudgetRequest>,
 ) -> Result<Json<BudgetApi>> {
@@ -419,13 +357,10 @@ async fn update_budget(
 
     // Fetch current budget state to ensure it exists
     // This provides better error messages and maintains data consistency
-    let mut budget = sqlx::query_as::<_, Budget>(
-        "SELECT * FROM budgets WHERE id = $1::uuid",
-    )
-    .bind(budget_id)
-    .fetch_one(&pool)
-    .await
-    .map_err(|_| AppError::NotFound("Budget not found".to_string()))?;
+    let mut budget = repo
+        .find(budget_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Budget not found".to_string()))?;
 
     // Apply partial updates only for provided fields
     // This allows flexible updates without requiring all fields
@@ -446,50 +381,20 @@ async fn update_budget(
         budget.carryover = carryover;
     }
 
-    // Update the budget with new values
-    let updated_budget = sqlx::query_as::<_, Budget>(
-        r#"
-        UPDATE budgets
-        SET planned = $1, carryover = $2, updated_at = CURRENT_TIMESTAMP
-        WHERE id = $3::uuid
-        RETURNING id, month, year, category_id, planned, spent, carryover, currency, created_at, updated_at
-        "#,
-    )
-    .bind(budget.planned)
-    .bind(budget.carryover)
-    .bind(budget_id)
-    .fetch_one(&pool)
-    .await?;
-
-    // Convert to API model with proper timezone handling
-    let currency_owned = updated_budget.currency.clone();
-    let budget_api = BudgetApi {
-        id: updated_budget.id.to_string(),
-        month: updated_budget.month,
-        year: updated_budget.year,
-        category_id: updated_budget.category_id.to_string(),
-        planned: updated_budget.planned,
-        spent: updated_budget.spent,
-        carryover: updated_budget.carryover,
-        currency: currency_owned.clone(),
-        created_at: updated_budget.created_at,
-        updated_at: updated_budget.updated_at,
-    };
-
-    // Invalidate cache for this budget and the month/year
-    // This ensures cache consistency when data is updated
+    // Update the budget with new values; it may have been deleted since
+    let updated_budget = repo
+        .update(budget_id, budget.planned, budget.carryover)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Budget not found".to_string()))?;
+
+    // Invalidate cache for this budget and everything tagged with its
+    // month/year, whatever currency filter it was cached under
     let month_str = updated_budget.month.to_string();
     let year_str = updated_budget.year.to_string();
     let _ = cache.invalidate_budget_cache(&id).await;
-    let _ = cache
-        .invalidate_month_cache(
-            &month_str,
-            &year_str,
-            Some(currency_owned.as_str()),
-        )
-        .await;
+    let _ = cache.invalidate_period(&month_str, &year_str).await;
 
-    Ok(Json(budget_api))
+    Ok(Json(BudgetApi::from(updated_budget)))
 }
 
 /// Retrieves a specific budget by its ID
@@ -522,7 +427,7 @@ async fn update_budget(
 /// }
 /// ```
 async fn get_budget_by_id(
-    State((pool, cache)): State<AppState>,
+    State((repo, cache)): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<BudgetApi>> {
     // Try to get data from cache first
@@ -535,28 +440,12 @@ async fn get_budget_by_id(
         AppError::Validation("Invalid budget ID format".to_string())
     })?;
 
-    // Cache miss - fetch from database
-    let budget = sqlx::query_as::<_, Budget>(
-        "SELECT * FROM budgets WHERE id = $1::uuid",
-    )
-    .bind(budget_id)
-    .fetch_one(&pool)
-    .await
-    .map_err(|_| AppError::NotFound("Budget not found".to_string()))?;
-
-    // Convert to API model with proper timezone handling
-    let budget_api = BudgetApi {
-        id: budget.id.to_string(),
-        month: budget.month,
-        year: budget.year,
-        category_id: budget.category_id.to_string(),
-        planned: budget.planned,
-        spent: budget.spent,
-        carryover: budget.carryover,
-        currency: budget.currency,
-        created_at: budget.created_at,
-        updated_at: budget.updated_at,
-    };
+    // Cache miss - fetch from the repository
+    let budget_api = repo
+        .find(budget_id)
+        .await?
+        .map(BudgetApi::from)
+        .ok_or_else(|| AppError::NotFound("Budget not found".to_string()))?;
 
     // Cache the result for future requests (don't block on cache write)
     let _ = cache.cache_budget(&id, &budget_api).await;
@@ -565,148 +454,14 @@ async fn get_budget_by_id(
 }
 
 // ================================================================
-// 3) Internal data-access helpers (queries/aggregation)
-// ================================================================
-
-/// Calculates budget overview data for a given month/year.
-///
-/// Notes:
-/// - SUMs are done in SQL for efficiency and to reduce data transferred
-/// - `COALESCE` ensures NULL-safe totals
-/// - Grouped by currency to support multi-currency budgets; we pick the first (typical single currency per query)
-async fn get_budget_overview_data(
-    pool: &PgPool,
-    month: i16,
-    year: i32,
-    currency: Option<&str>,
-) -> Result<BudgetOverviewApi> {
-    let result = sqlx::query(
-        r#"
-        SELECT
-            COALESCE(SUM(planned), 0) as planned,
-            COALESCE(SUM(spent), 0) as spent,
-            COALESCE(SUM(carryover), 0) as carryover,
-            TRIM(currency) as currency
-        FROM budgets
-        WHERE month = $1::smallint AND year = $2
-        AND ($3::text IS NULL OR currency = $3)
-        GROUP BY currency
-        LIMIT 1
-        "#,
-    )
-    .bind(month as i16)
-    .bind(year)
-    .bind(currency)
-    .fetch_optional(pool)
-    .await?;
-
-    if let Some(result) = result {
-        let planned: Decimal = result.try_get("planned")?;
-        let spent: Decimal = result.try_get("spent")?;
-        let carryover: Decimal = result.try_get("carryover")?;
-        let remaining = &planned - &spent + &carryover;
-
-        Ok(BudgetOverviewApi {
-            planned,
-            spent,
-            remaining,
-            currency: result
-                .try_get::<String, _>("currency")?
-                .trim()
-                .to_string(),
-        })
-    } else {
-        // No data for this month/year: return zeros and default currency (EUR) if not provided
-        let currency_fallback = currency.unwrap_or("EUR").to_string();
-
-        Ok(BudgetOverviewApi {
-            planned: Decimal::from(0),
-            spent: Decimal::from(0),
-            remaining: Decimal::from(0),
-            currency: currency_fallback,
-        })
-    }
-}
-
-/// Retrieves category-specific budget rows and enriches them for API consumption.
-///
-/// Implementation details:
-/// - Single query joins categories and optional groups for efficiency
-/// - Sorting by group sort_order (NULLs last) then category name for stable UI rendering
-/// - Percentage computed in application code to keep SQL simple and precise with decimals
-async fn get_category_budgets(
-    pool: &PgPool,
-    month: i16,
-    year: i32,
-    currency: Option<&str>,
-) -> Result<Vec<CategoryBudgetApi>> {
-    let rows = sqlx::query(
-        r#"
-        SELECT
-            b.id,
-            c.name as category_name,
-            cg.name as group_name,
-            c.color as category_color,
-            cg.color as group_color,
-            b.planned,
-            b.spent,
-            b.carryover,
-            TRIM(b.currency) as currency
-        FROM budgets b
-        JOIN categories c ON b.category_id = c.id
-        LEFT JOIN category_groups cg ON c.group_id = cg.id
-        WHERE b.month = $1 AND b.year = $2
-        AND ($3::text IS NULL OR b.currency = $3)
-        ORDER BY COALESCE(cg.sort_order, 999), c.name
-        "#,
-    )
-    .bind(month as i16)
-    .bind(year)
-    .bind(currency)
-    .fetch_all(pool)
-    .await?;
-
-    let mut category_budgets = Vec::new();
-
-    for row in rows {
-        let planned: Decimal = row.try_get("planned")?;
-        let spent: Decimal = row.try_get("spent")?;
-        let carryover: Decimal = row.try_get("carryover")?;
-        let remaining = &planned - &spent + &carryover;
-
-        // Percentage of budget used; safe when planned is zero
-        let percentage = if &planned > &Decimal::from(0) {
-            ((&spent / &planned) * Decimal::from(100)).round_dp(2)
-        } else {
-            Decimal::from(0)
-        };
-
-        category_budgets.push(CategoryBudgetApi {
-            id: row.try_get::<Uuid, _>("id")?.to_string(),
-            category_name: row.try_get("category_name")?,
-            group_name: row.try_get("group_name").ok(),
-            category_color: row.try_get("category_color")?,
-            group_color: row.try_get("group_color").ok(),
-            planned,
-            spent,
-            remaining,
-            percentage,
-            currency: row.try_get::<String, _>("currency")?.trim().to_string(),
-        });
-    }
-
-    Ok(category_budgets)
-}
-
-// ================================================================
-// 4) Insights generator (pure, in-memory)
+// 3) Insights generator (pure, in-memory)
 // ================================================================
 
 /// Generates human-readable insights based on spending progress.
 ///
 /// Categories with spending > 100% trigger warnings; nearing 90% triggers suggestions.
 /// The overall remaining amount determines positive or warning messages.
-fn generate_budget_insights(
+pub fn generate_budget_insights(
     overview: &BudgetOverviewApi,
     categories: &[CategoryBudgetApi],
 ) -> Vec<BudgetInsight> {
@@ -721,8 +476,7 @@ fn generate_budget_insights(
                 type_: "warning".to_string(),
                 message: format!(
                     "You're {}% over budget on {}",
-                    over_percentage.to_string(),
-                    category.category_name
+                    over_percentage, category.category_name
                 ),
                 icon: "warning-outline".to_string(),
                 color: "#FF6B6B".to_string(),
@@ -736,7 +490,7 @@ fn generate_budget_insights(
             type_: "positive".to_string(),
             message: format!(
                 "You have ${} remaining for other expenses",
-                overview.remaining.to_string()
+                overview.remaining
             ),
             icon: "checkmark-circle-outline".to_string(),
             color: "#4ECDC4".to_string(),
@@ -746,7 +500,7 @@ fn generate_budget_insights(
             type_: "warning".to_string(),
             message: format!(
                 "You're ${} over your total budget",
-                overview.remaining.abs().to_string()
+                overview.remaining.abs()
             ),
             icon: "warning-outline".to_string(),
             color: "#FF6B6B".to_string(),
@@ -0,0 +1,263 @@
+//! Health API for MoneyWise backend.
+//!
+//! Probes for orchestrators, mounted under `/health` and exempt from rate
+//! limiting:
+//! - `/health/live`: the process is up and serving requests
+//! - `/health/ready`: the dependencies are reachable; 503 when a required
+//!   one is down so traffic is routed elsewhere
+//!
+//! PostgreSQL and its migration state are always required. The cache is
+//! never required, since reads fall back to the database while Redis is
+//! down. The rate limiter's Redis is required only when some transaction
+//! type fails closed without it.
+
+use axum::{http::StatusCode, response::Json, Extension};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{
+    api::budget::AppState,
+    cache::domains::budget::BudgetCache,
+    database::migrations::migration_status,
+    rate_limiter::{ClassifiedRouter, RateLimitClass, RateLimitService},
+};
+
+/// Upper bound on each dependency check
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Creates the health router; both probes skip rate limiting
+pub fn health_routes() -> ClassifiedRouter<AppState> {
+    ClassifiedRouter::new()
+        .get("/live", live, RateLimitClass::Exempt)
+        .get("/ready", ready, RateLimitClass::Exempt)
+}
+
+/// State of one dependency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyStatus {
+    Up,
+    Down,
+}
+
+/// Outcome of checking one dependency
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyCheck {
+    pub status: DependencyStatus,
+    /// Whether the server is not ready while this dependency is down
+    pub required: bool,
+    pub latency_ms: u64,
+    /// Backend or version information
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response body of `/health/ready`
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    /// `ready` or `not_ready`
+    pub status: &'static str,
+    pub checks: BTreeMap<&'static str, DependencyCheck>,
+}
+
+impl ReadinessReport {
+    /// `false` if any required dependency is down
+    pub fn is_ready(&self) -> bool {
+        self.checks
+            .values()
+            .all(|c| !c.required || c.status == DependencyStatus::Up)
+    }
+}
+
+/// Dependencies checked by `/health/ready`.
+///
+/// Installed by `main` as an `Arc<ReadinessProbe>` extension.
+#[derive(Clone)]
+pub struct ReadinessProbe {
+    pool: PgPool,
+    cache: BudgetCache,
+    rate_limiter: Arc<RateLimitService>,
+    timeout: Duration,
+}
+
+impl ReadinessProbe {
+    pub fn new(
+        pool: PgPool,
+        cache: BudgetCache,
+        rate_limiter: Arc<RateLimitService>,
+    ) -> Self {
+        Self {
+            pool,
+            cache,
+            rate_limiter,
+            timeout: DEFAULT_CHECK_TIMEOUT,
+        }
+    }
+
+    /// Bound each check by `timeout` instead of `DEFAULT_CHECK_TIMEOUT`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Check every dependency concurrently
+    pub async fn check(&self) -> ReadinessReport {
+        let cache_backend = self.cache.service().backend();
+        let (database, migrations, cache, rate_limiter) = tokio::join!(
+            self.run(true, None, async {
+                sqlx::query("SELECT 1")
+                    .execute(&self.pool)
+                    .await
+                    .map(|_| ())
+            }),
+            self.run(true, None, async {
+                let status = migration_status(&self.pool)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if status.pending.is_empty() {
+                    Ok(format!("{} applied", status.applied.len()))
+                } else {
+                    Err(format!("pending migrations {:?}", status.pending))
+                }
+            }),
+            self.run(false, Some(cache_backend.name()), cache_backend.ping()),
+            self.run(
+                self.rate_limiter.fails_closed(),
+                Some(self.rate_limiter.backend_name()),
+                self.rate_limiter.ping()
+            ),
+        );
+
+        let checks = BTreeMap::from([
+            ("database", database),
+            ("migrations", migrations),
+            ("cache", cache),
+            ("rate_limiter", rate_limiter),
+        ]);
+        let mut report = ReadinessReport {
+            status: "ready",
+            checks,
+        };
+        if !report.is_ready() {
+            report.status = "not_ready";
+        }
+        report
+    }
+
+    /// Time `check`, bounded by the probe timeout. A successful check may
+    /// return a detail string, replacing `detail`.
+    async fn run<T, E>(
+        &self,
+        required: bool,
+        detail: Option<&str>,
+        check: impl Future<Output = Result<T, E>>,
+    ) -> DependencyCheck
+    where
+        T: CheckDetail,
+        E: Display,
+    {
+        let started = Instant::now();
+        let outcome = tokio::time::timeout(self.timeout, check).await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let (status, detail, error) = match outcome {
+            Ok(Ok(value)) => (
+                DependencyStatus::Up,
+                value.detail().or(detail.map(str::to_string)),
+                None,
+            ),
+            Ok(Err(e)) => (
+                DependencyStatus::Down,
+                detail.map(str::to_string),
+                Some(e.to_string()),
+            ),
+            Err(_) => (
+                DependencyStatus::Down,
+                detail.map(str::to_string),
+                Some(format!("timed out after {}ms", self.timeout.as_millis())),
+            ),
+        };
+        DependencyCheck {
+            status,
+            required,
+            latency_ms,
+            detail,
+            error,
+        }
+    }
+}
+
+/// Value of a successful check, reported as its detail
+trait CheckDetail {
+    fn detail(self) -> Option<String>;
+}
+
+impl CheckDetail for () {
+    fn detail(self) -> Option<String> {
+        None
+    }
+}
+
+impl CheckDetail for String {
+    fn detail(self) -> Option<String> {
+        Some(self)
+    }
+}
+
+/// Reports that the process is up; never touches a dependency.
+///
+/// # Examples
+///
+/// ```bash
+/// curl -s "http://localhost:3000/health/live"
+/// ```
+///
+/// Response body (JSON):
+/// ```json
+/// { "status": "live" }
+/// ```
+async fn live() -> Json<Value> {
+    Json(json!({ "status": "live" }))
+}
+
+/// Reports each dependency; 503 when a required one is down.
+///
+/// Requires the `Arc<ReadinessProbe>` extension installed by `main`.
+///
+/// # Examples
+///
+/// ```bash
+/// curl -s "http://localhost:3000/health/ready"
+/// ```
+///
+/// Response body (JSON, 503 Service Unavailable):
+/// ```json
+/// {
+///   "status": "not_ready",
+///   "checks": {
+///     "cache": { "status": "up", "required": false, "latency_ms": 1, "detail": "redis" },
+///     "database": { "status": "down", "required": true, "latency_ms": 2000, "error": "timed out after 2000ms" },
+///     "migrations": { "status": "down", "required": true, "latency_ms": 2000, "error": "timed out after 2000ms" },
+///     "rate_limiter": { "status": "up", "required": false, "latency_ms": 1, "detail": "redis" }
+///   }
+/// }
+/// ```
+async fn ready(
+    Extension(probe): Extension<Arc<ReadinessProbe>>,
+) -> (StatusCode, Json<ReadinessReport>) {
+    let report = probe.check().await;
+    let status = if report.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
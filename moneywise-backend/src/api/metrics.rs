@@ -0,0 +1,42 @@
+//! Metrics API for MoneyWise backend.
+//!
+//! `GET /metrics` serves the process-wide registry in the Prometheus text
+//! exposition format for scrapers. It needs no token and is exempt from
+//! rate limiting.
+
+use axum::{http::header, response::IntoResponse};
+
+use crate::{
+    api::budget::AppState,
+    metrics,
+    rate_limiter::{ClassifiedRouter, RateLimitClass},
+};
+
+/// Creates the metrics router, mounted at the root
+pub fn metrics_routes() -> ClassifiedRouter<AppState> {
+    ClassifiedRouter::new().get("/metrics", get_metrics, RateLimitClass::Exempt)
+}
+
+/// Renders all registered metrics for Prometheus.
+///
+/// Covers HTTP requests per route and status, database pool usage, cache
+/// lookups, rate limit decisions and background job runs.
+///
+/// # Examples
+///
+/// ```bash
+/// curl -s "http://localhost:3000/metrics"
+/// ```
+///
+/// Response body (text):
+/// ```text
+/// # HELP moneywise_http_requests_total HTTP requests by method, route and status
+/// # TYPE moneywise_http_requests_total counter
+/// moneywise_http_requests_total{method="GET",route="/api/budgets",status="200"} 12
+/// ```
+async fn get_metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics::render(),
+    )
+}
@@ -3,17 +3,20 @@
 //! This module organizes all API routes and provides a centralized way to manage
 //! different API endpoints for the application.
 
-use crate::cache::domains::budget::BudgetCache;
-use axum::Router;
-use sqlx::PgPool;
+use crate::api::budget::AppState;
+use crate::rate_limiter::ClassifiedRouter;
 
 // Import route modules
+pub mod admin;
 pub mod budget;
+pub mod health;
+pub mod metrics;
+pub mod warmup;
 
 /// Create the main API router with all available routes
 /// This function combines all API routes into a single router
-/// and returns the router with state already configured
-pub fn create_api_router() -> Router<(PgPool, BudgetCache)> {
+/// together with the rate-limit class declared by each route
+pub fn create_api_router() -> ClassifiedRouter<AppState> {
     /*
      * Frontend linkage:
      * - The MoneyWise web app consumes these routes via the service client in
@@ -28,9 +31,27 @@ pub fn create_api_router() -> Router<(PgPool, BudgetCache)> {
      * - This module is typically mounted under the "/api" prefix in the main
      *   server/router configuration.
      */
-    Router::new().nest("/budgets", budget::budget_routes())
+    ClassifiedRouter::new().nest("/budgets", budget::budget_routes())
     // Future API routes can be added here by merging routers:
     // .merge(transactions::create_transaction_routes())
     // .merge(goals::create_goal_routes())
     // .merge(users::create_user_routes())
 }
+
+/// Create the operator-only router.
+/// Mounted under "/admin"; every route requires the `x-admin-token` header.
+pub fn create_admin_router() -> ClassifiedRouter<AppState> {
+    admin::admin_routes()
+}
+
+/// Create the health probe router.
+/// Mounted under "/health"; no route is rate limited.
+pub fn create_health_router() -> ClassifiedRouter<AppState> {
+    health::health_routes()
+}
+
+/// Create the Prometheus scrape router.
+/// Merged at the root, serving "/metrics" without rate limiting.
+pub fn create_metrics_router() -> ClassifiedRouter<AppState> {
+    metrics::metrics_routes()
+}
@@ -0,0 +1,151 @@
+//! Budget cache warm-up for MoneyWise backend.
+//!
+//! Precomputes the dashboard data (overview and category budgets) of the
+//! current and previous month, for every currency budgeted in them plus the
+//! currency-less view, and fills it through `BudgetCache`. Runs at startup
+//! and then every `CacheConfig::warmup_interval`, so the first dashboard
+//! loads after a deploy or a Redis restart are cache hits.
+//!
+//! Entries are filled like a request would (`get_or_fill_*`): cached entries
+//! are kept, and misses take the fill lock, so a warm-up never replaces an
+//! entry written after it read the database.
+
+use chrono::{Datelike, NaiveDate, Utc};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+use crate::{
+    cache::domains::budget::BudgetCache,
+    error::{AppError, Result},
+    metrics::jobs::{job, record_job, JobOutcome},
+    repository::SharedBudgetRepository,
+};
+
+/// The month of `today` and the one before it, as `(month, year)`
+pub fn warmup_periods(today: NaiveDate) -> [(i16, i32); 2] {
+    let (month, year) = (today.month() as i16, today.year());
+    let previous = if month == 1 {
+        (12, year - 1)
+    } else {
+        (month - 1, year)
+    };
+    [(month, year), previous]
+}
+
+/// Precompute and cache the current and previous month; returns the number
+/// of entries cached afterwards (filled now or already present).
+///
+/// An entry that fails is logged and skipped; the run then ends with an
+/// error once every other entry has been tried.
+pub async fn warm_budget_cache(
+    repo: &SharedBudgetRepository,
+    cache: &BudgetCache,
+) -> Result<usize> {
+    let periods = warmup_periods(Utc::now().date_naive());
+    let currencies = repo.currencies(&periods).await?;
+    let variants: Vec<Option<&str>> = std::iter::once(None)
+        .chain(currencies.iter().map(|c| Some(c.as_str())))
+        .collect();
+
+    let (mut warmed, mut failed) = (0, 0);
+    for (month, year) in periods {
+        let (month_str, year_str) = (month.to_string(), year.to_string());
+        for &currency in &variants {
+            let (overview_repo, overview_currency) =
+                (repo.clone(), currency.map(str::to_string));
+            let overview = cache
+                .get_or_fill_budget_overview(
+                    &month_str,
+                    &year_str,
+                    currency,
+                    move || async move {
+                        overview_repo
+                            .overview(month, year, overview_currency.as_deref())
+                            .await
+                    },
+                )
+                .await
+                .map(drop);
+
+            let (categories_repo, categories_currency) =
+                (repo.clone(), currency.map(str::to_string));
+            let categories = cache
+                .get_or_fill_category_budgets(
+                    &month_str,
+                    &year_str,
+                    currency,
+                    move || async move {
+                        categories_repo
+                            .category_budgets(
+                                month,
+                                year,
+                                categories_currency.as_deref(),
+                            )
+                            .await
+                    },
+                )
+                .await
+                .map(drop);
+
+            for (kind, result) in
+                [("overview", overview), ("categories", categories)]
+            {
+                match result {
+                    Ok(()) => warmed += 1,
+                    Err(e) => {
+                        failed += 1;
+                        tracing::warn!(
+                            "Cache warm-up of {} {}-{:02} ({}) failed: {}",
+                            kind,
+                            year,
+                            month,
+                            currency.unwrap_or("all currencies"),
+                            e
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(AppError::Internal(format!(
+            "{} of {} cache warm-up entries failed",
+            failed,
+            warmed + failed
+        )));
+    }
+    Ok(warmed)
+}
+
+/// Warm the cache now and then every `interval` (zero: only now).
+///
+/// Failures are logged, counted as `cache_warmup` job runs and retried at
+/// the next run.
+pub fn spawn_budget_cache_warmup(
+    repo: SharedBudgetRepository,
+    cache: BudgetCache,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let started = Instant::now();
+            let outcome = match warm_budget_cache(&repo, &cache).await {
+                Ok(warmed) => {
+                    tracing::info!("Cache warm-up covered {} entries", warmed);
+                    JobOutcome::Success
+                }
+                Err(e) => {
+                    tracing::warn!("Cache warm-up failed: {}", e);
+                    JobOutcome::Failure
+                }
+            };
+            record_job(job::CACHE_WARMUP, outcome, started.elapsed());
+
+            if interval.is_zero() {
+                return;
+            }
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
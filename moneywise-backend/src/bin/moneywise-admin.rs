@@ -0,0 +1,354 @@
+//! Admin CLI for MoneyWise backend.
+//!
+//! Operational tasks that previously needed the shell scripts in `scripts/`
+//! or the OCaml tools, run against the same environment as the server
+//! (`.env`, `DATABASE_URL`, `REDIS_URL`, ...):
+//!
+//! ```text
+//! moneywise-admin migrate [--status]
+//! moneywise-admin seed
+//! moneywise-admin cache flush [--prefix <PREFIX>]
+//! moneywise-admin rate-limits --ip <IP> [--device <DEVICE>] [--reset]
+//! moneywise-admin copy-month --from <YYYY-MM> --to <YYYY-MM> [--overwrite]
+//! moneywise-admin check-config
+//! moneywise-admin backup [--output <FILE>]
+//! moneywise-admin restore [--input <FILE>] [--on-conflict <STRATEGY>]
+//! ```
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::process::ExitCode;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use uuid::Uuid;
+
+use moneywise_backend::{
+    cache::{
+        connection::init_cache, core::service::KEY_PREFIX,
+        domains::budget::keys,
+    },
+    database::{
+        backup::{create_backup, restore_backup, Backup, ConflictStrategy},
+        create_pool,
+        migrations::{migration_status, run_migrations},
+    },
+    maintenance::{
+        copy_month_budgets, seed_sample_data, validate_environment,
+        BudgetPeriod,
+    },
+    rate_limiter::{
+        middleware::validate_device_id, RateLimitConfig, RateLimitService,
+    },
+};
+
+type CliResult = Result<(), Box<dyn std::error::Error>>;
+
+/// Command-line definition
+fn cli() -> Command {
+    Command::new("moneywise-admin")
+        .about("Operational tasks for the MoneyWise backend")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("migrate")
+                .about("Apply pending database migrations")
+                .arg(
+                    Arg::new("status")
+                        .long("status")
+                        .action(ArgAction::SetTrue)
+                        .help("Only list applied and pending migrations"),
+                ),
+        )
+        .subcommand(
+            Command::new("seed")
+                .about("Insert the sample categories and budgets (idempotent)"),
+        )
+        .subcommand(
+            Command::new("cache")
+                .about("Manage the Redis cache")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("flush")
+                        .about("Delete every key in a cache namespace")
+                        .arg(Arg::new("prefix").long("prefix").help(
+                            "Key prefix to delete (default: the budget namespace)",
+                        )),
+                ),
+        )
+        .subcommand(
+            Command::new("rate-limits")
+                .about("Show the rate limit counters of a client")
+                .arg(Arg::new("ip").long("ip").required(true))
+                .arg(Arg::new("device").long("device"))
+                .arg(
+                    Arg::new("reset")
+                        .long("reset")
+                        .action(ArgAction::SetTrue)
+                        .help("Delete the counters after showing them"),
+                ),
+        )
+        .subcommand(
+            Command::new("copy-month")
+                .about("Copy one month's planned budgets to another month")
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .required(true)
+                        .value_parser(clap::value_parser!(BudgetPeriod))
+                        .help("Source month (YYYY-MM)"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .required(true)
+                        .value_parser(clap::value_parser!(BudgetPeriod))
+                        .help("Target month (YYYY-MM)"),
+                )
+                .arg(
+                    Arg::new("overwrite")
+                        .long("overwrite")
+                        .action(ArgAction::SetTrue)
+                        .help("Replace planned amounts already set in the target month"),
+                ),
+        )
+        .subcommand(
+            Command::new("check-config")
+                .about("Validate the environment configuration"),
+        )
+        .subcommand(
+            Command::new("backup")
+                .about("Dump category groups, categories and budgets")
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .default_value("-")
+                        .help("Archive to write ('-' for stdout)"),
+                ),
+        )
+        .subcommand(
+            Command::new("restore")
+                .about("Restore an archive written by `backup`")
+                .arg(
+                    Arg::new("input")
+                        .long("input")
+                        .short('i')
+                        .default_value("-")
+                        .help("Archive to read ('-' for stdin)"),
+                )
+                .arg(
+                    Arg::new("on-conflict")
+                        .long("on-conflict")
+                        .default_value("fail")
+                        .value_parser(clap::value_parser!(ConflictStrategy))
+                        .help("Existing rows: fail, skip, overwrite or replace (empty the tables first)"),
+                ),
+        )
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Logs go to stderr so command output can be piped
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(
+            std::env::var("RUST_LOG")
+                .unwrap_or_else(|_| "warn,sqlx::query=warn".into()),
+        ))
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .init();
+
+    dotenv::dotenv().ok();
+
+    let matches = cli().get_matches();
+    let result = match matches.subcommand() {
+        Some(("migrate", args)) => migrate(args).await,
+        Some(("seed", _)) => seed().await,
+        Some(("cache", args)) => match args.subcommand() {
+            Some(("flush", args)) => flush_cache(args).await,
+            _ => unreachable!("subcommand is required"),
+        },
+        Some(("rate-limits", args)) => rate_limits(args).await,
+        Some(("copy-month", args)) => copy_month(args).await,
+        Some(("check-config", _)) => check_config(),
+        Some(("backup", args)) => backup(args).await,
+        Some(("restore", args)) => restore(args).await,
+        _ => unreachable!("subcommand is required"),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn migrate(args: &ArgMatches) -> CliResult {
+    let pool = create_pool().await?;
+
+    if args.get_flag("status") {
+        let status = migration_status(&pool).await?;
+        for version in &status.applied {
+            println!("applied  {}", version);
+        }
+        for version in &status.pending {
+            println!("pending  {}", version);
+        }
+        return Ok(());
+    }
+
+    let status = run_migrations(&pool).await?;
+    println!(
+        "Applied {} migrations ({} already applied)",
+        status.pending.len(),
+        status.applied.len()
+    );
+    Ok(())
+}
+
+async fn seed() -> CliResult {
+    let pool = create_pool().await?;
+    let inserted = seed_sample_data(&pool).await?;
+    println!("Inserted {} sample rows", inserted);
+    Ok(())
+}
+
+async fn flush_cache(args: &ArgMatches) -> CliResult {
+    let cache = init_cache().await?;
+
+    let (prefix, deleted) = match args.get_one::<String>("prefix") {
+        Some(prefix) => {
+            // Same guard as DELETE /admin/cache/keys: never touch rate
+            // limit counters sharing the Redis instance
+            if !prefix.starts_with(KEY_PREFIX) {
+                return Err(
+                    format!("prefix must start with '{}'", KEY_PREFIX).into()
+                );
+            }
+            (
+                prefix.as_str(),
+                cache.service().flush_namespace(prefix).await?,
+            )
+        }
+        None => (keys::NAMESPACE, cache.flush().await?),
+    };
+
+    println!("Deleted {} keys under {}", deleted, prefix);
+    Ok(())
+}
+
+async fn rate_limits(args: &ArgMatches) -> CliResult {
+    let ip = args.get_one::<String>("ip").expect("required");
+    let device = args.get_one::<String>("device").map(String::as_str);
+    if let Some(device) = device {
+        if !validate_device_id(device) {
+            return Err(
+                "device must be 8-64 characters (letters, digits, '-', '_')"
+                    .into(),
+            );
+        }
+    }
+
+    let rate_limiter = RateLimitService::new(RateLimitConfig::default())
+        .await
+        .map_err(|e| format!("Failed to initialize rate limiter: {}", e))?;
+
+    for status in rate_limiter.inspect(ip, device).await? {
+        println!(
+            "{:<40} {:>3}/{:<3} remaining {:>3} reset in {}",
+            status.key,
+            status.count,
+            status.limit,
+            status.remaining,
+            status
+                .reset_in_seconds
+                .map_or_else(|| "-".to_string(), |s| format!("{}s", s))
+        );
+    }
+
+    if args.get_flag("reset") {
+        let reset = rate_limiter.reset(ip, device).await?;
+        println!("Reset {} counters", reset);
+    }
+    Ok(())
+}
+
+async fn copy_month(args: &ArgMatches) -> CliResult {
+    let from = *args.get_one::<BudgetPeriod>("from").expect("required");
+    let to = *args.get_one::<BudgetPeriod>("to").expect("required");
+    let overwrite = args.get_flag("overwrite");
+
+    let pool = create_pool().await?;
+    let copied = copy_month_budgets(&pool, from, to, overwrite).await?;
+    println!("Copied {} budgets from {} to {}", copied.len(), from, to);
+
+    // Running servers would otherwise serve the target month, and the
+    // overwritten budgets, from cache
+    let cache = init_cache().await?;
+    if let Err(e) = cache
+        .invalidate_period(&to.month.to_string(), &to.year.to_string())
+        .await
+    {
+        eprintln!("warning: failed to invalidate cached {}: {}", to, e);
+    }
+    let ids: Vec<String> = copied.iter().map(Uuid::to_string).collect();
+    if let Err(e) = cache.invalidate_budgets(&ids).await {
+        eprintln!("warning: failed to invalidate cached budgets: {}", e);
+    }
+    Ok(())
+}
+
+fn check_config() -> CliResult {
+    let problems = validate_environment();
+    if problems.is_empty() {
+        println!("Configuration is valid");
+        return Ok(());
+    }
+
+    for problem in &problems {
+        println!("- {}", problem);
+    }
+    Err(format!("{} configuration problems", problems.len()).into())
+}
+
+async fn backup(args: &ArgMatches) -> CliResult {
+    let output = args.get_one::<String>("output").expect("has default");
+
+    let pool = create_pool().await?;
+    let backup = create_backup(&pool).await?;
+    let checksum = if output == "-" {
+        backup.write_to(BufWriter::new(io::stdout().lock()))?
+    } else {
+        backup.write_to(BufWriter::new(File::create(output)?))?
+    };
+
+    // Status goes to stderr: stdout may be the archive itself
+    eprintln!("Backed up {} ({})", backup.counts(), checksum);
+    Ok(())
+}
+
+async fn restore(args: &ArgMatches) -> CliResult {
+    let input = args.get_one::<String>("input").expect("has default");
+    let strategy = *args
+        .get_one::<ConflictStrategy>("on-conflict")
+        .expect("has default");
+
+    // Verify the whole archive before touching the database
+    let backup = if input == "-" {
+        Backup::read_from(io::stdin().lock())?
+    } else {
+        Backup::read_from(BufReader::new(File::open(input)?))?
+    };
+
+    // An empty database gets its schema first
+    let pool = create_pool().await?;
+    run_migrations(&pool).await?;
+    let written = restore_backup(&pool, &backup, strategy).await?;
+    println!("Restored {} from backup of {}", written, backup.created_at);
+
+    let cache = init_cache().await?;
+    if let Err(e) = cache.flush().await {
+        eprintln!("warning: failed to flush the budget cache: {}", e);
+    }
+    Ok(())
+}
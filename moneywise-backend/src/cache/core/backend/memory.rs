@@ -0,0 +1,297 @@
+//! In-process cache backend.
+//!
+//! Values live in a bounded LRU with per-entry expiry; tag sets are kept
+//! beside it with their own expiry. Expired entries are dropped on access,
+//! and the LRU bound caps memory without a cleanup task. Nothing is shared
+//! between processes, so `publish` reaches no one.
+
+use async_trait::async_trait;
+use lru::LruCache;
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::cache::core::backend::CacheBackend;
+use crate::error::Result;
+
+/// Default maximum number of cached values
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+struct Entry {
+    value: Vec<u8>,
+    expires_at: Instant,
+}
+
+struct TagSet {
+    members: HashSet<String>,
+    expires_at: Instant,
+}
+
+struct Inner {
+    entries: LruCache<String, Entry>,
+    sets: HashMap<String, TagSet>,
+}
+
+impl Inner {
+    /// Live value for `key`, dropping it if expired
+    fn live_entry(&mut self, key: &str, now: Instant) -> Option<&Entry> {
+        if self.entries.peek(key).is_some_and(|e| e.expires_at <= now) {
+            self.entries.pop(key);
+        }
+        self.entries.get(key)
+    }
+
+    /// Drop the set for `key` if it has expired
+    fn drop_expired_set(&mut self, key: &str, now: Instant) {
+        if self.sets.get(key).is_some_and(|s| s.expires_at <= now) {
+            self.sets.remove(key);
+        }
+    }
+}
+
+/// Bounded TTL + LRU cache; entries are local to this process
+pub struct InMemoryCacheBackend {
+    inner: Mutex<Inner>,
+}
+
+impl Default for InMemoryCacheBackend {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ENTRIES)
+    }
+}
+
+impl InMemoryCacheBackend {
+    /// Create a backend holding at most `max_entries` values (at least one);
+    /// the least recently used value is evicted when full
+    pub fn new(max_entries: usize) -> Self {
+        let capacity =
+            NonZeroUsize::new(max_entries).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            inner: Mutex::new(Inner {
+                entries: LruCache::new(capacity),
+                sets: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Number of values held (including expired, not yet dropped)
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Whether no values are held
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remaining lifetime of a live value
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        let now = Instant::now();
+        self.lock()
+            .live_entry(key, now)
+            .map(|entry| entry.expires_at.saturating_duration_since(now))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn insert(
+        inner: &mut Inner,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Duration,
+        now: Instant,
+    ) {
+        let entry = Entry {
+            value,
+            expires_at: now + ttl,
+        };
+        inner.entries.put(key.to_string(), entry);
+    }
+}
+
+#[async_trait]
+impl CacheBackend for InMemoryCacheBackend {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut inner = self.lock();
+        Ok(inner
+            .live_entry(key, Instant::now())
+            .map(|entry| entry.value.clone()))
+    }
+
+    async fn get_with_ttl(
+        &self,
+        key: &str,
+    ) -> Result<Option<(Vec<u8>, Option<Duration>)>> {
+        let now = Instant::now();
+        let mut inner = self.lock();
+        Ok(inner.live_entry(key, now).map(|entry| {
+            let ttl = entry.expires_at.saturating_duration_since(now);
+            (entry.value.clone(), Some(ttl))
+        }))
+    }
+
+    async fn set_ex(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl_seconds: usize,
+    ) -> Result<()> {
+        let ttl = Duration::from_secs(ttl_seconds as u64);
+        let mut inner = self.lock();
+        Self::insert(&mut inner, key, value.to_vec(), ttl, Instant::now());
+        Ok(())
+    }
+
+    async fn set_ex_tagged(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl_seconds: usize,
+        tag_keys: &[String],
+    ) -> Result<()> {
+        let now = Instant::now();
+        let ttl = Duration::from_secs(ttl_seconds as u64);
+        let mut inner = self.lock();
+
+        Self::insert(&mut inner, key, value.to_vec(), ttl, now);
+        for tag_key in tag_keys {
+            let expires_at = now + ttl;
+            inner.drop_expired_set(tag_key, now);
+            let set =
+                inner.sets.entry(tag_key.clone()).or_insert_with(|| TagSet {
+                    members: HashSet::new(),
+                    expires_at,
+                });
+            set.members.insert(key.to_string());
+            set.expires_at = set.expires_at.max(expires_at);
+        }
+        Ok(())
+    }
+
+    async fn del(&self, keys: &[&str]) -> Result<()> {
+        let mut inner = self.lock();
+        for key in keys {
+            inner.entries.pop(*key);
+            inner.sets.remove(*key);
+        }
+        Ok(())
+    }
+
+    async fn scan(&self, pattern: &str) -> Result<Vec<String>> {
+        let now = Instant::now();
+        let inner = self.lock();
+        let values = inner
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.expires_at > now)
+            .map(|(key, _)| key);
+        let sets = inner
+            .sets
+            .iter()
+            .filter(|(_, set)| set.expires_at > now)
+            .map(|(key, _)| key);
+
+        Ok(values
+            .chain(sets)
+            .filter(|key| glob_match(pattern, key))
+            .cloned()
+            .collect())
+    }
+
+    async fn take_set(&self, key: &str) -> Result<Vec<String>> {
+        let mut inner = self.lock();
+        inner.drop_expired_set(key, Instant::now());
+        Ok(inner
+            .sets
+            .remove(key)
+            .map(|set| set.members.into_iter().collect())
+            .unwrap_or_default())
+    }
+
+    async fn try_lock(
+        &self,
+        key: &str,
+        token: &str,
+        ttl_millis: u64,
+    ) -> Result<bool> {
+        let now = Instant::now();
+        let mut inner = self.lock();
+        if inner.live_entry(key, now).is_some() {
+            return Ok(false);
+        }
+        let ttl = Duration::from_millis(ttl_millis);
+        Self::insert(&mut inner, key, token.as_bytes().to_vec(), ttl, now);
+        Ok(true)
+    }
+
+    async fn unlock(&self, key: &str, token: &str) -> Result<()> {
+        let mut inner = self.lock();
+        let held = inner
+            .live_entry(key, Instant::now())
+            .is_some_and(|entry| entry.value == token.as_bytes());
+        if held {
+            inner.entries.pop(key);
+        }
+        Ok(())
+    }
+
+    async fn publish(&self, _channel: &str, _message: &str) -> Result<usize> {
+        Ok(0)
+    }
+
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+}
+
+/// Match `text` against a Redis glob pattern (`*`, `?` and `\` escapes;
+/// character classes are not needed by the cache and not supported)
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text index it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+                continue;
+            }
+            Some('?') => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            Some('\\') if pattern.get(p + 1) == Some(&text[t]) => {
+                p += 2;
+                t += 1;
+                continue;
+            }
+            Some(&c) if c != '\\' && c == text[t] => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            _ => {}
+        }
+        match backtrack {
+            Some((star_p, star_t)) => {
+                p = star_p;
+                t = star_t + 1;
+                backtrack = Some((star_p, star_t + 1));
+            }
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
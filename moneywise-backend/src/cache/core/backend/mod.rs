@@ -0,0 +1,136 @@
+//! Storage backends for cached values.
+//!
+//! - redis.rs: Shared cache in Redis for multi-node deployments
+//! - memory.rs: Bounded in-process cache (TTL + LRU) for tests and
+//!   Redis-less local development
+//!
+//! The backend is selected with `CACHE_BACKEND` (`redis` or `memory`).
+//! Values are opaque, already codec-encoded bytes.
+
+pub mod memory;
+pub mod redis;
+
+pub use memory::InMemoryCacheBackend;
+pub use redis::RedisCacheBackend;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::error::Result;
+
+/// Key-value storage used by `CacheService`
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// Read a value; `None` on a miss or an expired key
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Read a value with its remaining lifetime (`None` if it never expires)
+    async fn get_with_ttl(
+        &self,
+        key: &str,
+    ) -> Result<Option<(Vec<u8>, Option<Duration>)>>;
+
+    /// Write a value that expires after `ttl_seconds`
+    async fn set_ex(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl_seconds: usize,
+    ) -> Result<()>;
+
+    /// Write a value like `set_ex` and add `key` to each set in `tag_keys`,
+    /// extending each set's lifetime to at least `ttl_seconds`
+    async fn set_ex_tagged(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl_seconds: usize,
+        tag_keys: &[String],
+    ) -> Result<()>;
+
+    /// Delete keys (values or sets); missing keys are ignored
+    async fn del(&self, keys: &[&str]) -> Result<()>;
+
+    /// List keys matching a Redis glob `pattern` (`*`, `?`, `\` escapes)
+    async fn scan(&self, pattern: &str) -> Result<Vec<String>>;
+
+    /// Return the members of a set and delete it, atomically
+    async fn take_set(&self, key: &str) -> Result<Vec<String>>;
+
+    /// Take a lock held by `token` for `ttl_millis`; `false` if already held
+    async fn try_lock(
+        &self,
+        key: &str,
+        token: &str,
+        ttl_millis: u64,
+    ) -> Result<bool>;
+
+    /// Release a lock only if `token` still holds it
+    async fn unlock(&self, key: &str, token: &str) -> Result<()>;
+
+    /// Broadcast `message` to other instances sharing this backend;
+    /// returns the number of receivers
+    async fn publish(&self, channel: &str, message: &str) -> Result<usize>;
+
+    /// `false` while calls are short-circuited (e.g. an open circuit
+    /// breaker); reads then miss and writes fail without reaching storage
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// Check that the storage answers; in-process backends always do
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Short backend name for logs
+    fn name(&self) -> &'static str;
+}
+
+/// Glob pattern matching every key that starts with `prefix`, with the
+/// pattern characters of `prefix` (`*`, `?`, `[`, `]`, `\`) escaped
+pub fn prefix_pattern(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('*');
+    pattern
+}
+
+/// Which backend to build from configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackendKind {
+    /// Shared cache in Redis (default)
+    Redis,
+    /// Per-process cache; entries are not shared between instances
+    Memory,
+}
+
+impl fmt::Display for CacheBackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Redis => write!(f, "redis"),
+            Self::Memory => write!(f, "memory"),
+        }
+    }
+}
+
+impl FromStr for CacheBackendKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "redis" => Ok(Self::Redis),
+            "memory" => Ok(Self::Memory),
+            other => Err(format!("Unknown cache backend '{}'", other)),
+        }
+    }
+}
@@ -0,0 +1,320 @@
+//! Redis cache backend.
+//!
+//! Uses a simple round-robin pool of connections to improve concurrency and
+//! match the configured `max_connections`. Connections follow
+//! `CacheConfig::redis_topology` (standalone, Sentinel or Cluster) and are
+//! opened on first use, so a Redis outage never blocks startup. Each
+//! operation delegates to `cache::core::operations`, which adds retries,
+//! timeouts and logging; a `CircuitBreaker` bypasses Redis while it keeps
+//! failing.
+
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use tracing::{debug, error, info, warn};
+
+use crate::cache::core::backend::CacheBackend;
+use crate::cache::core::circuit::CircuitBreaker;
+use crate::cache::core::config::CacheConfig;
+use crate::cache::core::operations::{
+    delete_keys, get_raw, get_raw_with_ttl, publish, release_lock, scan_keys,
+    set_with_ttl, set_with_ttl_and_tags, take_tag_members, try_acquire_lock,
+};
+use crate::cache::core::retry::{is_transient_error, timeout_error};
+use crate::error::{AppError, Result};
+use crate::metrics::jobs::{job, record_job, JobOutcome};
+use crate::redis_topology::RedisConnection;
+
+/// Cache backend shared by all instances through Redis
+pub struct RedisCacheBackend {
+    /// Simple pool of Redis connections for concurrency; each slot connects
+    /// on first use and retries on the next use if that fails
+    connection_pool: Arc<Vec<OnceCell<RedisConnection>>>,
+    /// Next index for round-robin selection
+    next_index: AtomicUsize,
+    /// Retry, timeout and topology settings for every operation
+    config: CacheConfig,
+    /// Open while Redis is unhealthy; shared with the background probe
+    circuit: Arc<CircuitBreaker>,
+}
+
+impl RedisCacheBackend {
+    /// Create a pool of `config.max_connections` connections without
+    /// connecting yet; fails only on an invalid configuration.
+    pub fn new(config: &CacheConfig) -> Result<Self> {
+        let topology = &config.redis_topology;
+        topology.validate(&config.redis_url).map_err(|e| {
+            error!("Invalid Redis configuration: {}", e);
+            AppError::Cache(e)
+        })?;
+
+        info!(
+            "Redis cache backend initialized ({}) with a pool of {} connections (timeout {}ms)",
+            topology.mode(),
+            config.max_connections,
+            config.connection_timeout.as_millis()
+        );
+
+        Ok(Self {
+            connection_pool: Arc::new(
+                (0..config.max_connections)
+                    .map(|_| OnceCell::new())
+                    .collect(),
+            ),
+            next_index: AtomicUsize::new(0),
+            config: config.clone(),
+            circuit: Arc::new(CircuitBreaker::new(
+                config.circuit_failure_threshold,
+            )),
+        })
+    }
+
+    /// Select a pool slot via round-robin and connect it if needed.
+    async fn select_connection(&self) -> Result<RedisConnection> {
+        let idx = self.next_index.fetch_add(1, Ordering::Relaxed)
+            % self.connection_pool.len();
+        let conn = self.connection_pool[idx]
+            .get_or_try_init(|| connect(&self.config))
+            .await?;
+        Ok(conn.clone())
+    }
+
+    /// Run `operation` on a pooled connection unless the circuit is open,
+    /// and feed its outcome to the circuit breaker
+    async fn call<T, F, Fut>(&self, operation: F) -> Result<T>
+    where
+        F: FnOnce(RedisConnection) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if self.circuit.is_open() {
+            return Err(AppError::Cache(
+                (redis::ErrorKind::IoError, "Cache circuit open").into(),
+            ));
+        }
+
+        let result = match self.select_connection().await {
+            Ok(conn) => operation(conn).await,
+            Err(e) => Err(e),
+        };
+        match &result {
+            Ok(_) => self.circuit.record_success(),
+            Err(e) if is_transient_error(e) => {
+                if self.circuit.record_failure() {
+                    warn!(
+                        "Redis unavailable, bypassing the cache until it recovers: {}",
+                        e
+                    );
+                    spawn_probe(self.config.clone(), self.circuit.clone());
+                }
+            }
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// `call` for reads: errors and an open circuit are treated as misses,
+    /// so the caller falls back to the database
+    async fn read<T, F, Fut>(
+        &self,
+        key: &str,
+        operation: F,
+    ) -> Result<Option<T>>
+    where
+        F: FnOnce(RedisConnection) -> Fut,
+        Fut: Future<Output = Result<Option<T>>>,
+    {
+        if self.circuit.is_open() {
+            debug!("Cache circuit open, skipping Redis read of {}", key);
+            return Ok(None);
+        }
+        match self.call(operation).await {
+            Ok(value) => Ok(value),
+            Err(e) => {
+                warn!(
+                    "Redis read failed for key {}, falling back to database: {}",
+                    key, e
+                );
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Open one connection, bounded by `connection_timeout`
+async fn connect(config: &CacheConfig) -> Result<RedisConnection> {
+    let connecting = config.redis_topology.connect(&config.redis_url);
+    match tokio::time::timeout(config.connection_timeout, connecting).await {
+        Ok(Ok(conn)) => Ok(conn),
+        Ok(Err(e)) => {
+            error!("Failed to create Redis connection: {}", e);
+            Err(AppError::Cache(e))
+        }
+        Err(_) => {
+            error!(
+                "Timed out connecting to Redis after {}ms",
+                config.connection_timeout.as_millis()
+            );
+            Err(timeout_error(config.connection_timeout))
+        }
+    }
+}
+
+/// Ping Redis on a fresh connection every `circuit_probe_interval` until it
+/// answers, then close `circuit`.
+fn spawn_probe(config: CacheConfig, circuit: Arc<CircuitBreaker>) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(config.circuit_probe_interval).await;
+            let started = Instant::now();
+            let probe = async {
+                let mut conn = connect(&config).await?;
+                redis::cmd("PING")
+                    .query_async::<String>(&mut conn)
+                    .await
+                    .map_err(AppError::from)
+            };
+            let outcome =
+                tokio::time::timeout(config.connection_timeout, probe).await;
+            let elapsed = started.elapsed();
+            match outcome {
+                Ok(Ok(_)) => {
+                    record_job(
+                        job::CACHE_CIRCUIT_PROBE,
+                        JobOutcome::Success,
+                        elapsed,
+                    );
+                    circuit.close();
+                    info!("Redis is reachable again, cache re-enabled");
+                    return;
+                }
+                Ok(Err(e)) => debug!("Redis probe failed: {}", e),
+                Err(_) => debug!("Redis probe timed out"),
+            }
+            record_job(job::CACHE_CIRCUIT_PROBE, JobOutcome::Failure, elapsed);
+        }
+    });
+}
+
+#[async_trait]
+impl CacheBackend for RedisCacheBackend {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.read(key, |conn| async move {
+            get_raw(&conn, &self.config, key).await
+        })
+        .await
+    }
+
+    async fn get_with_ttl(
+        &self,
+        key: &str,
+    ) -> Result<Option<(Vec<u8>, Option<Duration>)>> {
+        self.read(key, |conn| async move {
+            get_raw_with_ttl(&conn, &self.config, key).await
+        })
+        .await
+    }
+
+    async fn set_ex(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl_seconds: usize,
+    ) -> Result<()> {
+        self.call(|conn| async move {
+            set_with_ttl(&conn, &self.config, key, value, ttl_seconds).await
+        })
+        .await
+    }
+
+    async fn set_ex_tagged(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl_seconds: usize,
+        tag_keys: &[String],
+    ) -> Result<()> {
+        self.call(|conn| async move {
+            set_with_ttl_and_tags(
+                &conn,
+                &self.config,
+                key,
+                value,
+                ttl_seconds,
+                tag_keys,
+            )
+            .await
+        })
+        .await
+    }
+
+    async fn del(&self, keys: &[&str]) -> Result<()> {
+        self.call(
+            |conn| async move { delete_keys(&conn, &self.config, keys).await },
+        )
+        .await
+    }
+
+    async fn scan(&self, pattern: &str) -> Result<Vec<String>> {
+        self.call(|conn| async move {
+            scan_keys(&conn, &self.config, pattern).await
+        })
+        .await
+    }
+
+    async fn take_set(&self, key: &str) -> Result<Vec<String>> {
+        self.call(|conn| async move {
+            take_tag_members(&conn, &self.config, key).await
+        })
+        .await
+    }
+
+    async fn try_lock(
+        &self,
+        key: &str,
+        token: &str,
+        ttl_millis: u64,
+    ) -> Result<bool> {
+        self.call(|conn| async move {
+            try_acquire_lock(&conn, &self.config, key, token, ttl_millis).await
+        })
+        .await
+    }
+
+    async fn unlock(&self, key: &str, token: &str) -> Result<()> {
+        self.call(|conn| async move {
+            release_lock(&conn, &self.config, key, token).await
+        })
+        .await
+    }
+
+    async fn publish(&self, channel: &str, message: &str) -> Result<usize> {
+        self.call(|conn| async move {
+            publish(&conn, &self.config, channel, message).await
+        })
+        .await
+    }
+
+    fn is_available(&self) -> bool {
+        !self.circuit.is_open()
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.call(|mut conn| async move {
+            redis::cmd("PING")
+                .query_async::<String>(&mut conn)
+                .await
+                .map(|_| ())
+                .map_err(AppError::from)
+        })
+        .await
+    }
+
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+}
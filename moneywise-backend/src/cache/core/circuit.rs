@@ -0,0 +1,67 @@
+//! Circuit breaker for the Redis cache backend.
+//!
+//! After `failure_threshold` consecutive availability failures (timeouts,
+//! I/O and cluster errors, after retries) the circuit opens: cache calls
+//! return at once and requests go straight to the database. While open, the
+//! owner probes Redis in the background and closes the circuit on the first
+//! successful probe. Other errors (e.g. a wrong value type) leave it alone.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use crate::cache::core::metrics::set_circuit_open;
+
+/// Consecutive-failure circuit breaker; see the module documentation
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    /// Failures in a row that open the circuit (zero: never open)
+    failure_threshold: u32,
+    consecutive_failures: AtomicU32,
+    open: AtomicBool,
+}
+
+impl CircuitBreaker {
+    /// A closed circuit that opens after `failure_threshold` failures
+    pub fn new(failure_threshold: u32) -> Self {
+        Self {
+            failure_threshold,
+            consecutive_failures: AtomicU32::new(0),
+            open: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether calls should skip the backend
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    /// Reset the failure count after a successful call
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Release);
+    }
+
+    /// Count a failed call; returns `true` if this failure opened the
+    /// circuit, in which case the caller starts probing
+    pub fn record_failure(&self) -> bool {
+        let failures =
+            self.consecutive_failures.fetch_add(1, Ordering::AcqRel) + 1;
+        if self.failure_threshold == 0 || failures < self.failure_threshold {
+            return false;
+        }
+        let opened = self
+            .open
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok();
+        if opened {
+            set_circuit_open(true);
+        }
+        opened
+    }
+
+    /// Close the circuit after a successful probe
+    pub fn close(&self) {
+        self.consecutive_failures.store(0, Ordering::Release);
+        if self.open.swap(false, Ordering::AcqRel) {
+            set_circuit_open(false);
+        }
+    }
+}
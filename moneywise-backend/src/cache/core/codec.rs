@@ -0,0 +1,265 @@
+//! Pluggable encoding for cached values.
+//!
+//! Values are written with the codec selected in `CacheConfig` (JSON,
+//! MessagePack or bincode), optionally compressed with zstd or lz4 once
+//! they reach a size threshold. Every encoded value starts with a header
+//! byte recording the format and compression, so any instance can decode
+//! values written by any other, whatever its own configuration.
+//!
+//! Values without a header byte are plain JSON written before codecs
+//! existed; JSON text always starts with an ASCII byte, which never falls in
+//! the header range (`0xC0..=0xDF`).
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use tracing::{error, warn};
+
+use crate::error::{AppError, Result};
+
+/// High bits shared by every header byte (`0b110x_xxxx`)
+const HEADER_MARKER: u8 = 0xC0;
+/// Mask selecting the marker bits of a header byte
+const HEADER_MARKER_MASK: u8 = 0xE0;
+/// zstd level: fast, with most of the ratio of higher levels on JSON-like data
+const ZSTD_LEVEL: i32 = 3;
+
+/// Serialization format of cached values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheFormat {
+    Json,
+    MessagePack,
+    Bincode,
+}
+
+impl CacheFormat {
+    fn id(self) -> u8 {
+        match self {
+            CacheFormat::Json => 1,
+            CacheFormat::MessagePack => 2,
+            CacheFormat::Bincode => 3,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(CacheFormat::Json),
+            2 => Some(CacheFormat::MessagePack),
+            3 => Some(CacheFormat::Bincode),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CacheFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CacheFormat::Json => "json",
+            CacheFormat::MessagePack => "msgpack",
+            CacheFormat::Bincode => "bincode",
+        })
+    }
+}
+
+impl FromStr for CacheFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(CacheFormat::Json),
+            "msgpack" | "messagepack" => Ok(CacheFormat::MessagePack),
+            "bincode" => Ok(CacheFormat::Bincode),
+            other => Err(format!(
+                "unknown cache codec '{}' (expected json, msgpack or bincode)",
+                other
+            )),
+        }
+    }
+}
+
+/// Compression applied to encoded values above the threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheCompression {
+    None,
+    Zstd,
+    Lz4,
+}
+
+impl CacheCompression {
+    fn id(self) -> u8 {
+        match self {
+            CacheCompression::None => 0,
+            CacheCompression::Zstd => 1,
+            CacheCompression::Lz4 => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(CacheCompression::None),
+            1 => Some(CacheCompression::Zstd),
+            2 => Some(CacheCompression::Lz4),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CacheCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CacheCompression::None => "none",
+            CacheCompression::Zstd => "zstd",
+            CacheCompression::Lz4 => "lz4",
+        })
+    }
+}
+
+impl FromStr for CacheCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "none" | "off" => Ok(CacheCompression::None),
+            "zstd" => Ok(CacheCompression::Zstd),
+            "lz4" => Ok(CacheCompression::Lz4),
+            other => Err(format!(
+                "unknown cache compression '{}' (expected none, zstd or lz4)",
+                other
+            )),
+        }
+    }
+}
+
+/// Codec used to write cached values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Codec {
+    pub format: CacheFormat,
+    pub compression: CacheCompression,
+    /// Encoded size (bytes) from which `compression` is applied
+    pub compression_threshold: usize,
+}
+
+impl Default for Codec {
+    /// Uncompressed JSON, matching what older instances write
+    fn default() -> Self {
+        Self {
+            format: CacheFormat::Json,
+            compression: CacheCompression::None,
+            compression_threshold: 1024,
+        }
+    }
+}
+
+impl Codec {
+    /// Encode `data` as header byte + (possibly compressed) payload
+    pub fn encode<T: Serialize>(&self, data: &T) -> Result<Vec<u8>> {
+        let payload = match self.format {
+            CacheFormat::Json => serde_json::to_vec(data).map_err(encode_error),
+            CacheFormat::MessagePack => {
+                rmp_serde::to_vec(data).map_err(encode_error)
+            }
+            CacheFormat::Bincode => {
+                bincode::serialize(data).map_err(encode_error)
+            }
+        }?;
+
+        let compression = if payload.len() >= self.compression_threshold {
+            self.compression
+        } else {
+            CacheCompression::None
+        };
+        let payload = match compression {
+            CacheCompression::None => payload,
+            CacheCompression::Zstd => {
+                zstd::bulk::compress(&payload, ZSTD_LEVEL)
+                    .map_err(encode_error)?
+            }
+            CacheCompression::Lz4 => lz4_flex::compress_prepend_size(&payload),
+        };
+
+        let mut value = Vec::with_capacity(payload.len() + 1);
+        value.push(header(self.format, compression));
+        value.extend_from_slice(&payload);
+        Ok(value)
+    }
+}
+
+/// Decode a value written by any codec (or legacy plain JSON).
+///
+/// Returns `Ok(None)` when the value is corrupt or does not match `T`, so
+/// callers treat it as a cache miss.
+pub fn decode<T: DeserializeOwned>(value: &[u8]) -> Result<Option<T>> {
+    let Some((&first, rest)) = value.split_first() else {
+        warn!("Empty cached value");
+        return Ok(None);
+    };
+
+    let Some((format, compression)) = parse_header(first) else {
+        // No header: legacy JSON string
+        return Ok(log_decode_error(serde_json::from_slice(value)));
+    };
+
+    let decompressed;
+    let payload = match compression {
+        CacheCompression::None => rest,
+        CacheCompression::Zstd => {
+            match zstd::stream::decode_all(rest) {
+                Ok(bytes) => decompressed = bytes,
+                Err(e) => {
+                    warn!("Failed to decompress cached value (zstd): {}", e);
+                    return Ok(None);
+                }
+            }
+            &decompressed
+        }
+        CacheCompression::Lz4 => {
+            match lz4_flex::decompress_size_prepended(rest) {
+                Ok(bytes) => decompressed = bytes,
+                Err(e) => {
+                    warn!("Failed to decompress cached value (lz4): {}", e);
+                    return Ok(None);
+                }
+            }
+            &decompressed
+        }
+    };
+
+    Ok(match format {
+        CacheFormat::Json => log_decode_error(serde_json::from_slice(payload)),
+        CacheFormat::MessagePack => {
+            log_decode_error(rmp_serde::from_slice(payload))
+        }
+        CacheFormat::Bincode => log_decode_error(bincode::deserialize(payload)),
+    })
+}
+
+/// Format and compression recorded in a value's header, if it has one
+pub fn inspect(value: &[u8]) -> Option<(CacheFormat, CacheCompression)> {
+    value.first().and_then(|&first| parse_header(first))
+}
+
+fn header(format: CacheFormat, compression: CacheCompression) -> u8 {
+    HEADER_MARKER | (compression.id() << 3) | format.id()
+}
+
+fn parse_header(byte: u8) -> Option<(CacheFormat, CacheCompression)> {
+    if byte & HEADER_MARKER_MASK != HEADER_MARKER {
+        return None;
+    }
+    let format = CacheFormat::from_id(byte & 0x07)?;
+    let compression = CacheCompression::from_id((byte >> 3) & 0x03)?;
+    Some((format, compression))
+}
+
+fn encode_error(e: impl fmt::Display) -> AppError {
+    error!("Failed to encode data for cache: {}", e);
+    AppError::Internal(format!("Cache serialization failed: {}", e))
+}
+
+fn log_decode_error<T, E: fmt::Display>(
+    result: std::result::Result<T, E>,
+) -> Option<T> {
+    result
+        .map_err(|e| warn!("Failed to deserialize cached data: {}", e))
+        .ok()
+}
@@ -4,16 +4,24 @@
 //! connection sizing, and retry behavior. The defaults are production-friendly
 //! but can be overridden via environment variables.
 
-use crate::connections::parse_redis_url_from_env;
+use crate::cache::core::backend::{memory, CacheBackendKind};
+use crate::cache::core::codec::{CacheCompression, CacheFormat, Codec};
+use crate::connections::{parse_env_with_default, parse_redis_url_from_env};
+use crate::redis_topology::RedisTopology;
 use std::time::Duration;
-use tracing::warn;
 
 /// Cache configuration with TTL settings and Redis connection parameters.
 /// Different data types have different cache durations based on update frequency.
 #[derive(Clone)]
 pub struct CacheConfig {
+    /// Where cached values are stored (Redis, or in-process for local use)
+    pub backend: CacheBackendKind,
+    /// Maximum number of values held by the in-memory backend
+    pub memory_max_entries: usize,
     /// Redis connection URL (e.g., "redis://localhost:6379")
     pub redis_url: String,
+    /// Standalone, Sentinel or Cluster deployment behind `redis_url`
+    pub redis_topology: RedisTopology,
     /// TTL for budget overview data (15 minutes - overview changes infrequently)
     pub overview_ttl: Duration,
     /// TTL for category budget data (5 minutes - more volatile than overview)
@@ -22,10 +30,35 @@ pub struct CacheConfig {
     pub budget_ttl: Duration,
     /// Maximum number of Redis connections in the pool
     pub max_connections: usize,
-    /// Connection timeout for Redis operations
+    /// Limit for opening a Redis connection and for each operation attempt
     pub connection_timeout: Duration,
     /// Retry attempts for failed Redis operations
     pub retry_attempts: u32,
+    /// Consecutive failed Redis calls after which the cache is bypassed
+    /// until Redis answers again (zero disables the circuit breaker)
+    pub circuit_failure_threshold: u32,
+    /// Time between background probes of Redis while the cache is bypassed
+    pub circuit_probe_interval: Duration,
+    /// Take a Redis `SET NX` lock before filling a missed key, so only one
+    /// instance recomputes it (in-process coalescing is always on)
+    pub fill_lock: bool,
+    /// Lifetime of the fill lock; also how long other instances wait for it
+    pub fill_lock_ttl: Duration,
+    /// Keep a bounded in-process L1 copy of hot keys in front of Redis
+    pub l1_enabled: bool,
+    /// Maximum number of entries in the L1 cache (least recently used evicted)
+    pub l1_max_entries: usize,
+    /// Lifetime of an L1 entry; kept short since pub/sub delivery is best-effort
+    pub l1_ttl: Duration,
+    /// Encoding used for new cache writes; any encoding can be read back
+    pub codec: Codec,
+    /// Refresh entries in the background once their remaining TTL drops
+    /// below this, while still serving them (zero disables)
+    pub refresh_ahead: Duration,
+    /// Precompute the current and previous month at startup and on a schedule
+    pub warmup_enabled: bool,
+    /// Time between scheduled warm-ups (zero: warm up at startup only)
+    pub warmup_interval: Duration,
 }
 
 impl Default for CacheConfig {
@@ -37,6 +70,12 @@ impl Default for CacheConfig {
     /// that cannot be parsed as the expected types. This is intentional for
     /// configuration errors that should be caught at startup.
     fn default() -> Self {
+        let backend =
+            parse_env_with_default("CACHE_BACKEND", CacheBackendKind::Redis);
+        let memory_max_entries = parse_env_with_default(
+            "CACHE_MEMORY_MAX_ENTRIES",
+            memory::DEFAULT_MAX_ENTRIES,
+        );
         let redis_url = parse_redis_url_from_env("REDIS_URL");
 
         let overview_ttl =
@@ -49,39 +88,61 @@ impl Default for CacheConfig {
         let connection_timeout =
             parse_env_with_default("REDIS_CONNECTION_TIMEOUT_SECS", 5);
         let retry_attempts = parse_env_with_default("REDIS_RETRY_ATTEMPTS", 3);
+        let circuit_failure_threshold =
+            parse_env_with_default("CACHE_CIRCUIT_FAILURE_THRESHOLD", 5);
+        let circuit_probe_interval =
+            parse_env_with_default("CACHE_CIRCUIT_PROBE_INTERVAL_SECS", 5);
+        let fill_lock = parse_env_with_default("CACHE_FILL_LOCK", false);
+        let fill_lock_ttl =
+            parse_env_with_default("CACHE_FILL_LOCK_TTL_MS", 5000);
+        let l1_enabled = parse_env_with_default("CACHE_L1_ENABLED", false);
+        let l1_max_entries =
+            parse_env_with_default("CACHE_L1_MAX_ENTRIES", 1000);
+        let l1_ttl = parse_env_with_default("CACHE_L1_TTL_SECS", 30);
+        let refresh_ahead =
+            parse_env_with_default("CACHE_REFRESH_AHEAD_SECS", 60);
+        let warmup_enabled =
+            parse_env_with_default("CACHE_WARMUP_ENABLED", true);
+        let warmup_interval =
+            parse_env_with_default("CACHE_WARMUP_INTERVAL_SECS", 600);
+        let default_codec = Codec::default();
+        let codec = Codec {
+            format: parse_env_with_default::<CacheFormat>(
+                "CACHE_CODEC",
+                default_codec.format,
+            ),
+            compression: parse_env_with_default::<CacheCompression>(
+                "CACHE_COMPRESSION",
+                default_codec.compression,
+            ),
+            compression_threshold: parse_env_with_default(
+                "CACHE_COMPRESSION_THRESHOLD_BYTES",
+                default_codec.compression_threshold,
+            ),
+        };
 
         Self {
+            backend,
+            memory_max_entries,
             redis_url,
+            redis_topology: RedisTopology::from_env(),
             overview_ttl: Duration::from_secs(overview_ttl),
             categories_ttl: Duration::from_secs(categories_ttl),
             budget_ttl: Duration::from_secs(budget_ttl),
             max_connections,
             connection_timeout: Duration::from_secs(connection_timeout),
             retry_attempts,
+            circuit_failure_threshold,
+            circuit_probe_interval: Duration::from_secs(circuit_probe_interval),
+            fill_lock,
+            fill_lock_ttl: Duration::from_millis(fill_lock_ttl),
+            l1_enabled,
+            l1_max_entries,
+            l1_ttl: Duration::from_secs(l1_ttl),
+            codec,
+            refresh_ahead: Duration::from_secs(refresh_ahead),
+            warmup_enabled,
+            warmup_interval: Duration::from_secs(warmup_interval),
         }
     }
 }
-
-/// Parse an environment variable with a default value, logging warnings for invalid values.
-///
-/// This function provides better error visibility than `unwrap_or()` by logging
-/// when environment variables contain invalid values that cannot be parsed.
-fn parse_env_with_default<T>(var_name: &str, default_value: T) -> T
-where
-    T: std::str::FromStr + std::fmt::Display + Clone,
-    T::Err: std::fmt::Display,
-{
-    match std::env::var(var_name) {
-        Ok(value) => match value.parse::<T>() {
-            Ok(parsed) => parsed,
-            Err(e) => {
-                warn!(
-                    "Invalid value '{}' for environment variable '{}': {}. Using default: {}",
-                    value, var_name, e, default_value
-                );
-                default_value
-            }
-        },
-        Err(_) => default_value,
-    }
-}
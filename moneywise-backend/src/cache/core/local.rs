@@ -0,0 +1,106 @@
+//! In-process L1 cache in front of Redis.
+//!
+//! A bounded LRU of encoded values with a short per-entry TTL. Hits are
+//! served without a Redis round trip; invalidations are fanned out to every
+//! instance over Redis pub/sub (see `INVALIDATION_CHANNEL`) so that a write
+//! on one instance evicts the L1 entries on all of them.
+
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Redis pub/sub channel carrying keys evicted from L1 caches
+pub const INVALIDATION_CHANNEL: &str = "moneywise:cache:invalidate";
+
+/// Encoded value with its local expiry
+struct Entry {
+    value: Vec<u8>,
+    expires_at: Instant,
+}
+
+/// Size- and TTL-bounded LRU cache of encoded values
+pub struct LocalCache {
+    entries: Mutex<LruCache<String, Entry>>,
+    ttl: Duration,
+}
+
+impl LocalCache {
+    /// Create a cache holding at most `max_entries` values for `ttl` each
+    /// (`max_entries` of 0 is treated as 1)
+    pub fn new(max_entries: usize, ttl: Duration) -> Self {
+        let capacity =
+            NonZeroUsize::new(max_entries).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ttl,
+        }
+    }
+
+    /// Get the encoded value for `key` if present and not expired
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let mut entries = self.lock();
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                Some(entry.value.clone())
+            }
+            Some(_) => {
+                entries.pop(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store `value` under `key`, evicting the least recently used entry
+    /// when full. The local TTL never outlives the Redis one.
+    pub fn insert(&self, key: &str, value: Vec<u8>, ttl_seconds: usize) {
+        let ttl = self.ttl.min(Duration::from_secs(ttl_seconds as u64));
+        let entry = Entry {
+            value,
+            expires_at: Instant::now() + ttl,
+        };
+        self.lock().put(key.to_string(), entry);
+    }
+
+    /// Evict `keys` from this instance
+    pub fn remove<S: AsRef<str>>(&self, keys: &[S]) {
+        let mut entries = self.lock();
+        for key in keys {
+            entries.pop(key.as_ref());
+        }
+    }
+
+    /// Drop every entry, e.g. after missing invalidations while disconnected
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Number of entries currently held (including expired, not yet evicted)
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether the cache holds no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruCache<String, Entry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Encode invalidated keys as a pub/sub message
+pub fn encode_invalidation<S: AsRef<str>>(keys: &[S]) -> String {
+    let keys: Vec<&str> = keys.iter().map(AsRef::as_ref).collect();
+    serde_json::to_string(&keys).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Decode a pub/sub message into keys; `None` if malformed
+pub fn decode_invalidation(payload: &str) -> Option<Vec<String>> {
+    serde_json::from_str(payload).ok()
+}
@@ -0,0 +1,262 @@
+//! Cache hit/miss counters and latency histograms.
+//!
+//! Exposed as:
+//! - `moneywise_cache_lookups_total{domain, result}` where `result` is
+//!   `hit`, `miss` or `corrupt` (undecodable value, purged)
+//! - `moneywise_cache_retries_total` (transient backend errors retried)
+//! - `moneywise_cache_operation_duration_seconds{domain, operation}` for
+//!   `get` and `set`
+//! - `moneywise_cache_circuit_open` (1 while Redis calls are short-circuited)
+//!
+//! The domain is the key segment after `moneywise:` (e.g. `budget`), with
+//! any hash-tag braces removed.
+
+use prometheus::{
+    core::Collector, HistogramOpts, HistogramVec, IntCounter, IntCounterVec,
+    IntGauge, Opts,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::cache::core::service::KEY_PREFIX;
+use crate::metrics::{register, METRICS_NAMESPACE};
+
+/// Outcome of a cache read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup {
+    Hit,
+    Miss,
+    /// Value found but undecodable; it was deleted and treated as a miss
+    Corrupt,
+}
+
+impl fmt::Display for Lookup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hit => write!(f, "hit"),
+            Self::Miss => write!(f, "miss"),
+            Self::Corrupt => write!(f, "corrupt"),
+        }
+    }
+}
+
+/// Timed backend operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Get,
+    Set,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Get => write!(f, "get"),
+            Self::Set => write!(f, "set"),
+        }
+    }
+}
+
+fn lookups() -> &'static IntCounterVec {
+    static LOOKUPS: OnceLock<IntCounterVec> = OnceLock::new();
+    LOOKUPS.get_or_init(|| {
+        let counter = IntCounterVec::new(
+            Opts::new(
+                "cache_lookups_total",
+                "Cache reads by domain and result",
+            )
+            .namespace(METRICS_NAMESPACE),
+            &["domain", "result"],
+        )
+        .expect("valid cache lookup metric definition");
+        register(counter)
+    })
+}
+
+fn retries() -> &'static IntCounter {
+    static RETRIES: OnceLock<IntCounter> = OnceLock::new();
+    RETRIES.get_or_init(|| {
+        let counter = IntCounter::with_opts(
+            Opts::new(
+                "cache_retries_total",
+                "Cache backend operations retried after a transient error",
+            )
+            .namespace(METRICS_NAMESPACE),
+        )
+        .expect("valid cache retry metric definition");
+        register(counter)
+    })
+}
+
+fn circuit_open() -> &'static IntGauge {
+    static CIRCUIT_OPEN: OnceLock<IntGauge> = OnceLock::new();
+    CIRCUIT_OPEN.get_or_init(|| {
+        let gauge = IntGauge::with_opts(
+            Opts::new(
+                "cache_circuit_open",
+                "1 while the cache circuit breaker bypasses Redis",
+            )
+            .namespace(METRICS_NAMESPACE),
+        )
+        .expect("valid cache circuit metric definition");
+        register(gauge)
+    })
+}
+
+fn latencies() -> &'static HistogramVec {
+    static LATENCIES: OnceLock<HistogramVec> = OnceLock::new();
+    LATENCIES.get_or_init(|| {
+        let histogram = HistogramVec::new(
+            HistogramOpts::new(
+                "cache_operation_duration_seconds",
+                "Cache read/write latency by domain, including decoding",
+            )
+            .namespace(METRICS_NAMESPACE)
+            .buckets(vec![
+                0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25,
+                0.5, 1.0,
+            ]),
+            &["domain", "operation"],
+        )
+        .expect("valid cache latency metric definition");
+        register(histogram)
+    })
+}
+
+/// Domain label of a cache key: the segment after `moneywise:`, so
+/// `moneywise:{budget:2025-08}:...` is counted as `budget`
+pub fn domain_of(key: &str) -> &str {
+    key.strip_prefix(KEY_PREFIX)
+        .and_then(|rest| rest.split(':').next())
+        .map(|domain| domain.trim_start_matches('{').trim_end_matches('}'))
+        .filter(|domain| !domain.is_empty())
+        .unwrap_or("other")
+}
+
+/// Count one read of `key`
+pub fn record_lookup(key: &str, lookup: Lookup) {
+    lookups()
+        .with_label_values(&[domain_of(key), &lookup.to_string()])
+        .inc();
+}
+
+/// Count one retried backend operation
+pub fn record_retry() {
+    retries().inc();
+}
+
+/// Record whether the circuit breaker is open
+pub fn set_circuit_open(open: bool) {
+    circuit_open().set(open as i64);
+}
+
+/// Record how long an operation on `key` took
+pub fn observe(key: &str, operation: Operation, elapsed: Duration) {
+    latencies()
+        .with_label_values(&[domain_of(key), &operation.to_string()])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Latency summary of one operation
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencyStats {
+    pub count: u64,
+    pub mean_ms: f64,
+}
+
+/// Read counters and latencies of one domain
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DomainStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub corrupt: u64,
+    pub hit_ratio: Option<f64>, // None until the first read
+    pub latency: BTreeMap<String, LatencyStats>, // Keyed by operation
+}
+
+/// Process-wide cache statistics since startup
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheStats {
+    pub backend: &'static str,
+    pub hits: u64,
+    pub misses: u64,
+    pub corrupt: u64,
+    pub hit_ratio: Option<f64>,
+    pub retries: u64,
+    /// Whether Redis calls are currently short-circuited to the database
+    pub circuit_open: bool,
+    pub domains: BTreeMap<String, DomainStats>,
+}
+
+fn hit_ratio(hits: u64, misses: u64, corrupt: u64) -> Option<f64> {
+    let reads = hits + misses + corrupt;
+    (reads > 0).then(|| hits as f64 / reads as f64)
+}
+
+/// Collect the current counters, grouped by domain
+pub fn snapshot(backend: &'static str) -> CacheStats {
+    let mut stats = CacheStats {
+        backend,
+        retries: retries().get(),
+        ..CacheStats::default()
+    };
+
+    for family in lookups().collect() {
+        for metric in family.get_metric() {
+            let (mut domain, mut result) = ("", "");
+            for label in metric.get_label() {
+                match label.get_name() {
+                    "domain" => domain = label.get_value(),
+                    "result" => result = label.get_value(),
+                    _ => {}
+                }
+            }
+            let count = metric.get_counter().get_value() as u64;
+            let entry = stats.domains.entry(domain.to_string()).or_default();
+            match result {
+                "hit" => entry.hits += count,
+                "miss" => entry.misses += count,
+                "corrupt" => entry.corrupt += count,
+                _ => {}
+            }
+        }
+    }
+
+    for family in latencies().collect() {
+        for metric in family.get_metric() {
+            let (mut domain, mut operation) = ("", "");
+            for label in metric.get_label() {
+                match label.get_name() {
+                    "domain" => domain = label.get_value(),
+                    "operation" => operation = label.get_value(),
+                    _ => {}
+                }
+            }
+            let histogram = metric.get_histogram();
+            let count = histogram.get_sample_count();
+            let mean_ms = if count > 0 {
+                histogram.get_sample_sum() * 1000.0 / count as f64
+            } else {
+                0.0
+            };
+            stats
+                .domains
+                .entry(domain.to_string())
+                .or_default()
+                .latency
+                .insert(operation.to_string(), LatencyStats { count, mean_ms });
+        }
+    }
+
+    for domain in stats.domains.values_mut() {
+        domain.hit_ratio =
+            hit_ratio(domain.hits, domain.misses, domain.corrupt);
+        stats.hits += domain.hits;
+        stats.misses += domain.misses;
+        stats.corrupt += domain.corrupt;
+    }
+    stats.hit_ratio = hit_ratio(stats.hits, stats.misses, stats.corrupt);
+    stats
+}
@@ -0,0 +1,97 @@
+//! Invalidations that did not reach the cache backend.
+//!
+//! When Redis is down (or a `DEL` fails), deleting stale entries fails but
+//! the entries stay in Redis and would be served again once it recovers.
+//! `CacheService` records such keys and tags here and replays them before
+//! its next backend read. Past `MAX_MISSED` entries only a flag is kept and
+//! the whole cache is flushed instead.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// Keys and tags remembered individually before falling back to a flush
+pub const MAX_MISSED: usize = 10_000;
+
+/// What to delete once the backend is reachable again
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MissedBatch {
+    pub keys: Vec<String>,
+    pub tags: Vec<String>,
+    /// Too many were missed to track; delete every cache key instead
+    pub flush_all: bool,
+}
+
+impl MissedBatch {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.tags.is_empty() && !self.flush_all
+    }
+}
+
+#[derive(Debug, Default)]
+struct Missed {
+    keys: HashSet<String>,
+    tags: HashSet<String>,
+    flush_all: bool,
+}
+
+/// Set of invalidations waiting to be replayed
+#[derive(Debug, Default)]
+pub struct MissedInvalidations {
+    inner: Mutex<Missed>,
+}
+
+impl MissedInvalidations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember that deleting `keys` failed
+    pub fn record_keys(&self, keys: &[&str]) {
+        let mut missed = self.inner.lock().unwrap();
+        missed.keys.extend(keys.iter().map(|key| key.to_string()));
+        missed.check_capacity();
+    }
+
+    /// Remember that invalidating `tag` failed
+    pub fn record_tag(&self, tag: &str) {
+        let mut missed = self.inner.lock().unwrap();
+        missed.tags.insert(tag.to_string());
+        missed.check_capacity();
+    }
+
+    /// Put back a batch whose replay failed
+    pub fn restore(&self, batch: MissedBatch) {
+        let mut missed = self.inner.lock().unwrap();
+        missed.keys.extend(batch.keys);
+        missed.tags.extend(batch.tags);
+        missed.flush_all |= batch.flush_all;
+        missed.check_capacity();
+    }
+
+    /// Whether anything is waiting to be replayed
+    pub fn is_empty(&self) -> bool {
+        let missed = self.inner.lock().unwrap();
+        missed.keys.is_empty() && missed.tags.is_empty() && !missed.flush_all
+    }
+
+    /// Take everything recorded so far
+    pub fn take(&self) -> MissedBatch {
+        let mut missed = self.inner.lock().unwrap();
+        let missed = std::mem::take(&mut *missed);
+        MissedBatch {
+            keys: missed.keys.into_iter().collect(),
+            tags: missed.tags.into_iter().collect(),
+            flush_all: missed.flush_all,
+        }
+    }
+}
+
+impl Missed {
+    fn check_capacity(&mut self) {
+        if self.keys.len() + self.tags.len() > MAX_MISSED {
+            self.keys.clear();
+            self.tags.clear();
+            self.flush_all = true;
+        }
+    }
+}
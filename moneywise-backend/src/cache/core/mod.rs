@@ -3,8 +3,16 @@
 //! This module contains the generic caching components that can be reused
 //! across different domains (budget, transactions, goals, etc.).
 
+pub mod backend;
+pub mod circuit;
+pub mod codec;
 pub mod config;
+pub mod local;
+pub mod metrics;
+pub mod missed;
 pub mod operations;
 pub mod retry;
+pub mod schema;
 pub mod serialization;
 pub mod service;
+pub mod single_flight;
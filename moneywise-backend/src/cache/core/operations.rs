@@ -1,33 +1,45 @@
 //! Low-level Redis operations for cache management.
 //!
 //! Wraps `redis::AsyncCommands` with retry, error mapping, and logging.
-//! These functions are used by the higher-level `CacheService`.
+//! These functions back `RedisCacheBackend`; the higher-level
+//! `CacheService` only sees the `CacheBackend` trait.
 //!
 //! Organization:
 //! - set_with_ttl: write path with TTL
-//! - get_value: read path with JSON deserialize and self-healing
-//! - delete_keys: invalidate one or more keys
+//! - set_with_ttl_and_tags: write path that also records the key in tag sets
+//! - get_raw: read path returning the stored (codec-encoded) bytes
+//! - get_raw_with_ttl: read path that also returns the remaining TTL
+//! - delete_keys: invalidate one or more keys, one `DEL` per cluster slot
+//! - take_tag_members: read and clear a tag set in one step
+//! - scan_keys: list keys matching a pattern with `SCAN` (never `KEYS`),
+//!   on every primary of a cluster
+//! - try_acquire_lock / release_lock: `SET NX` fill lock across instances
+//! - publish: broadcast a message to other instances
 
-use redis::{aio::ConnectionManager, AsyncCommands};
-use tracing::{debug, error, warn};
+use redis::AsyncCommands;
+use std::time::Duration;
+use tracing::{debug, warn};
 
 use crate::cache::core::config::CacheConfig;
 use crate::cache::core::retry::with_retry;
-use crate::cache::core::serialization::deserialize;
 use crate::error::{AppError, Result};
+use crate::redis_topology::{group_by_slot, RedisConnection};
+
+/// Keys requested per `SCAN` round trip
+const SCAN_BATCH_SIZE: usize = 500;
 
 /// Set a key-value pair in Redis with TTL (seconds).
-/// Uses `SETEX` for atomic TTL setting.
+/// Uses `SETEX` for atomic TTL setting; `value` is already codec-encoded.
 pub async fn set_with_ttl(
-    conn: &ConnectionManager,
+    conn: &RedisConnection,
     config: &CacheConfig,
     key: &str,
-    value: &str,
+    value: &[u8],
     ttl_seconds: usize,
 ) -> Result<()> {
     let conn = conn.clone();
     let key = key.to_string();
-    let value = value.to_string();
+    let value = value.to_vec();
 
     with_retry(config, || {
         let key = key.clone();
@@ -56,101 +68,346 @@ pub async fn set_with_ttl(
     .await
 }
 
-/// Get a value from Redis by key.
-/// Returns deserialized data or `None` if not found or invalid.
-pub async fn get_value<T: serde::de::DeserializeOwned + Send + 'static>(
-    conn: &ConnectionManager,
+/// Set a key with TTL and add it to each tag set in `tag_keys`, atomically.
+///
+/// Tag sets are extended to live at least as long as the key, so a tag
+/// never expires while one of its members is still cached.
+/// In Cluster mode `key` and `tag_keys` must share a hash tag.
+pub async fn set_with_ttl_and_tags(
+    conn: &RedisConnection,
     config: &CacheConfig,
     key: &str,
-) -> Result<Option<T>> {
+    value: &[u8],
+    ttl_seconds: usize,
+    tag_keys: &[String],
+) -> Result<()> {
+    let script = redis::Script::new(
+        r#"
+        local ttl = tonumber(ARGV[2])
+        redis.call("SET", KEYS[1], ARGV[1], "EX", ttl)
+        for i = 2, #KEYS do
+            redis.call("SADD", KEYS[i], KEYS[1])
+            if redis.call("TTL", KEYS[i]) < ttl then
+                redis.call("EXPIRE", KEYS[i], ttl)
+            end
+        end
+        return 1
+        "#,
+    );
     let conn = conn.clone();
     let key = key.to_string();
+    let value = value.to_vec();
+    let tag_keys = tag_keys.to_vec();
 
-    match with_retry(config, || {
+    with_retry(config, || {
         let key = key.clone();
+        let value = value.clone();
+        let tag_keys = tag_keys.clone();
+        let script = script.clone();
         let mut conn = conn.clone();
 
         async move {
-            match conn.get::<_, Option<String>>(&key).await {
-                Ok(Some(json)) => {
-                    match deserialize::<T>(json) {
-                        Ok(Some(data)) => {
-                            debug!("Cache hit for key {}", key);
-                            Ok(Some(data))
-                        }
-                        Ok(None) => {
-                            warn!("Invalid cached data for key {}", key);
-                            Ok(None)
-                        }
-                        Err(e) => {
-                            error!("Failed to deserialize cached data for key {}: {}", key, e);
-                            // Self-heal: purge the corrupt key and return None
-                            // Clone again inside to satisfy Send bounds for retry wrapper
-                            let inner_conn = conn.clone();
-                            let inner_key = key.clone();
-                            let keys: [&str; 1] = [&inner_key];
-                            let _ = delete_keys(&inner_conn, config, &keys).await;
-                            Ok(None)
-                        }
-                    }
-                }
-                Ok(None) => {
-                    debug!("Cache miss for key {}", key);
-                    Ok(None)
+            match script
+                .key(&key)
+                .key(&tag_keys)
+                .arg(&value)
+                .arg(ttl_seconds)
+                .invoke_async::<i32>(&mut conn)
+                .await
+            {
+                Ok(_) => {
+                    debug!(
+                        "Cached data for key {} with TTL {}s and tags {:?}",
+                        key, ttl_seconds, tag_keys
+                    );
+                    Ok(())
                 }
                 Err(e) => {
-                    warn!("Redis error for key {}: {}", key, e);
+                    warn!("Failed to cache data for key {}: {}", key, e);
                     Err(AppError::from(e))
                 }
             }
         }
-    }).await {
-        Ok(result) => Ok(result),
-        Err(_) => {
-            // Graceful degradation - fall back to database on retry failure
-            warn!("Redis retry failed for key {}, falling back to database", key);
-            Ok(None)
+    })
+    .await
+}
+
+/// Get the raw stored bytes for a key; `None` on a miss.
+pub async fn get_raw(
+    conn: &RedisConnection,
+    config: &CacheConfig,
+    key: &str,
+) -> Result<Option<Vec<u8>>> {
+    let conn = conn.clone();
+    let key = key.to_string();
+
+    with_retry(config, || {
+        let key = key.clone();
+        let mut conn = conn.clone();
+
+        async move {
+            conn.get::<_, Option<Vec<u8>>>(&key).await.map_err(|e| {
+                warn!("Redis error for key {}: {}", key, e);
+                AppError::from(e)
+            })
         }
-    }
+    })
+    .await
+}
+
+/// Get the raw stored bytes for a key with its remaining lifetime, in one
+/// round trip (`GET` + `PTTL`). The lifetime is `None` for keys without a
+/// TTL. Returns `None` on a miss.
+pub async fn get_raw_with_ttl(
+    conn: &RedisConnection,
+    config: &CacheConfig,
+    key: &str,
+) -> Result<Option<(Vec<u8>, Option<Duration>)>> {
+    let conn = conn.clone();
+    let key = key.to_string();
+
+    with_retry(config, || {
+        let key = key.clone();
+        let mut conn = conn.clone();
+
+        async move {
+            let (value, pttl): (Option<Vec<u8>>, i64) = redis::pipe()
+                .get(&key)
+                .pttl(&key)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| {
+                    warn!("Redis error for key {}: {}", key, e);
+                    AppError::from(e)
+                })?;
+            // PTTL is -1 without a TTL and -2 if the key expired in between
+            let ttl = u64::try_from(pttl).ok().map(Duration::from_millis);
+            Ok(value.map(|value| (value, ttl)))
+        }
+    })
+    .await
 }
 
 /// Delete keys from Redis.
-/// Supports single key and batch deletion.
+/// Supports single key and batch deletion; on a cluster, one `DEL` is sent
+/// per hash slot.
 pub async fn delete_keys(
-    conn: &ConnectionManager,
+    conn: &RedisConnection,
     config: &CacheConfig,
     keys: &[&str],
 ) -> Result<()> {
+    let groups = match conn {
+        RedisConnection::Cluster(_) => group_by_slot(keys),
+        _ => vec![keys.to_vec()],
+    };
+
+    for group in groups {
+        let keys: Vec<String> = group.iter().map(|k| k.to_string()).collect();
+
+        with_retry(config, || {
+            let keys = keys.clone();
+            let mut conn = conn.clone();
+
+            async move {
+                match conn.del::<_, ()>(&keys).await {
+                    Ok(_) => {
+                        debug!("Deleted keys: {:?}", keys);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        warn!("Failed to delete keys {:?}: {}", keys, e);
+                        Err(AppError::from(e))
+                    }
+                }
+            }
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+/// Return the members of a tag set and delete the set, atomically.
+/// Keys tagged after this call start a fresh set.
+pub async fn take_tag_members(
+    conn: &RedisConnection,
+    config: &CacheConfig,
+    tag_key: &str,
+) -> Result<Vec<String>> {
+    let script = redis::Script::new(
+        r#"
+        local members = redis.call("SMEMBERS", KEYS[1])
+        redis.call("DEL", KEYS[1])
+        return members
+        "#,
+    );
     let conn = conn.clone();
-    let keys: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+    let tag_key = tag_key.to_string();
 
-    match with_retry(config, || {
-        let keys = keys.clone();
+    with_retry(config, || {
+        let tag_key = tag_key.clone();
+        let script = script.clone();
         let mut conn = conn.clone();
 
         async move {
-            match conn.del::<_, ()>(&keys).await {
-                Ok(_) => {
-                    debug!("Deleted keys: {:?}", keys);
-                    Ok(())
-                }
-                Err(e) => {
-                    warn!("Failed to delete keys {:?}: {}", keys, e);
-                    Err(AppError::from(e))
-                }
+            script
+                .key(&tag_key)
+                .invoke_async::<Vec<String>>(&mut conn)
+                .await
+                .map_err(|e| {
+                    warn!("Failed to read tag set {}: {}", tag_key, e);
+                    AppError::from(e)
+                })
+        }
+    })
+    .await
+}
+
+/// List every key matching `pattern` using cursor-based `SCAN`.
+///
+/// Unlike `KEYS`, this never blocks Redis for the whole keyspace; keys
+/// created or removed during the scan may or may not be returned. A
+/// cluster is scanned primary by primary.
+pub async fn scan_keys(
+    conn: &RedisConnection,
+    config: &CacheConfig,
+    pattern: &str,
+) -> Result<Vec<String>> {
+    let nodes = with_retry(config, || async {
+        conn.scan_targets().await.map_err(AppError::from)
+    })
+    .await?;
+    let mut keys = Vec::new();
+
+    for node in &nodes {
+        let mut cursor: u64 = 0;
+        loop {
+            let (next, batch): (u64, Vec<String>) =
+                with_retry(config, || async move {
+                    let cmd = redis::cmd("SCAN")
+                        .arg(cursor)
+                        .arg("MATCH")
+                        .arg(pattern)
+                        .arg("COUNT")
+                        .arg(SCAN_BATCH_SIZE)
+                        .clone();
+                    conn.query_node(node.as_ref(), &cmd).await.map_err(|e| {
+                        warn!(
+                            "Failed to scan keys matching {}: {}",
+                            pattern, e
+                        );
+                        AppError::from(e)
+                    })
+                })
+                .await?;
+
+            keys.extend(batch);
+            if next == 0 {
+                break;
             }
+            cursor = next;
+        }
+    }
+
+    debug!("Scanned {} keys matching {}", keys.len(), pattern);
+    Ok(keys)
+}
+
+/// Try to take a short-lived lock with `SET key token NX PX ttl`.
+/// Returns `true` when this caller now holds the lock.
+pub async fn try_acquire_lock(
+    conn: &RedisConnection,
+    config: &CacheConfig,
+    key: &str,
+    token: &str,
+    ttl_millis: u64,
+) -> Result<bool> {
+    let conn = conn.clone();
+    let key = key.to_string();
+    let token = token.to_string();
+
+    with_retry(config, || {
+        let key = key.clone();
+        let token = token.clone();
+        let mut conn = conn.clone();
+
+        async move {
+            let acquired: Option<String> = redis::cmd("SET")
+                .arg(&key)
+                .arg(&token)
+                .arg("NX")
+                .arg("PX")
+                .arg(ttl_millis)
+                .query_async(&mut conn)
+                .await
+                .map_err(AppError::from)?;
+            Ok(acquired.is_some())
         }
     })
     .await
-    {
-        Ok(_) => Ok(()),
-        Err(_) => {
-            // Don't fail the operation if cache deletion fails
-            warn!(
-                "Redis retry failed for key deletion {:?}, continuing anyway",
-                keys
-            );
+}
+
+/// Release a lock taken with `try_acquire_lock`, only if `token` still owns it
+/// (an expired lock may already belong to another instance).
+pub async fn release_lock(
+    conn: &RedisConnection,
+    config: &CacheConfig,
+    key: &str,
+    token: &str,
+) -> Result<()> {
+    let script = redis::Script::new(
+        r#"
+        if redis.call("GET", KEYS[1]) == ARGV[1] then
+            return redis.call("DEL", KEYS[1])
+        end
+        return 0
+        "#,
+    );
+    let conn = conn.clone();
+    let key = key.to_string();
+    let token = token.to_string();
+
+    with_retry(config, || {
+        let key = key.clone();
+        let token = token.clone();
+        let script = script.clone();
+        let mut conn = conn.clone();
+
+        async move {
+            let _: i32 = script
+                .key(&key)
+                .arg(&token)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(AppError::from)?;
+            debug!("Released fill lock {}", key);
             Ok(())
         }
-    }
+    })
+    .await
+}
+
+/// Publish `message` on a pub/sub `channel`.
+/// Returns the number of subscribers that received it.
+pub async fn publish(
+    conn: &RedisConnection,
+    config: &CacheConfig,
+    channel: &str,
+    message: &str,
+) -> Result<usize> {
+    let conn = conn.clone();
+    let channel = channel.to_string();
+    let message = message.to_string();
+
+    with_retry(config, || {
+        let channel = channel.clone();
+        let message = message.clone();
+        let mut conn = conn.clone();
+
+        async move {
+            conn.publish::<_, _, usize>(&channel, &message)
+                .await
+                .map_err(AppError::from)
+        }
+    })
+    .await
 }
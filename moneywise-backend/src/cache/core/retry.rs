@@ -2,7 +2,7 @@
 //!
 //! Implements exponential backoff with full jitter for transient failures.
 //! Classification is based on `redis::ErrorKind` to avoid brittle
-//! string matching.
+//! string matching. Retries are counted in `cache::core::metrics`.
 //!
 
 use std::time::Duration;
@@ -12,9 +12,9 @@ use tokio_retry::{
 };
 use tracing::{error, warn};
 
-use crate::cache::core::config::CacheConfig;
+use crate::cache::core::{config::CacheConfig, metrics::record_retry};
 use crate::error::{AppError, Result};
-use redis::ErrorKind as RedisErrorKind;
+use redis::{ErrorKind as RedisErrorKind, RedisError};
 
 /// Determine if a Redis error is transient and should be retried.
 /// Returns true for network/cluster and redirection issues.
@@ -28,6 +28,9 @@ pub fn is_transient_error(error: &AppError) -> bool {
             | RedisErrorKind::Moved
             | RedisErrorKind::Ask
             | RedisErrorKind::ClusterDown => true,
+            // Demoted primary after a Sentinel failover: retried once the
+            // connection has switched to the new primary
+            RedisErrorKind::ReadOnly => true,
             // Auth, type, or client-side parse errors are permanent
             RedisErrorKind::AuthenticationFailed
             | RedisErrorKind::TypeError
@@ -40,11 +43,21 @@ pub fn is_transient_error(error: &AppError) -> bool {
     }
 }
 
+/// Error for a Redis call that exceeded `timeout`; classified as transient
+pub fn timeout_error(timeout: Duration) -> AppError {
+    AppError::Cache(RedisError::from((
+        RedisErrorKind::IoError,
+        "Redis call timed out",
+        format!("no response within {}ms", timeout.as_millis()),
+    )))
+}
+
 /// Wrap an async Redis operation in a retry loop that:
-/// 1. uses exponential backoff + full jitter
-/// 2. only retries if `is_transient_error` returns true
-/// 3. stops immediately on permanent errors
-/// 4. logs each failure and the final give-up
+/// 1. bounds each attempt by `CacheConfig::connection_timeout`
+/// 2. uses exponential backoff + full jitter
+/// 3. only retries if `is_transient_error` returns true
+/// 4. stops immediately on permanent errors
+/// 5. logs each failure and the final give-up
 pub async fn with_retry<F, Fut, T>(
     config: &CacheConfig,
     mut operation: F,
@@ -60,15 +73,31 @@ where
         .take(config.retry_attempts as usize)
         .map(jitter);
 
+    // Every call after the first is a retry
+    let mut attempts = 0u32;
+
     // RetryIf lets us decide per-error whether we should retry
     let result =
-    RetryIf::spawn(
+    RetryIf::start(
         retry_strategy,
         // this closure is re-invoked on each attempt
         ||{
+            if attempts > 0 {
+                record_retry();
+            }
+            attempts += 1;
             let fut = operation();
             async move {
-                match fut.await{
+                let attempt = match tokio::time::timeout(
+                    config.connection_timeout,
+                    fut,
+                )
+                .await
+                {
+                    Ok(result) => result,
+                    Err(_) => Err(timeout_error(config.connection_timeout)),
+                };
+                match attempt {
                     Ok(val) => Ok (val),
                     Err(e) if is_transient_error(&e) => {
                         // instruct RetryIf to retry
@@ -0,0 +1,81 @@
+//! Schema fingerprints for cached models.
+//!
+//! Cache keys embed a short version derived from the shape of the cached
+//! type, so instances running different model definitions during a rolling
+//! deploy read and write disjoint keys instead of purging each other's
+//! entries as "corrupt".
+//!
+//! The fingerprint is computed from what serde's derive exposes without an
+//! instance: the struct name and its field names. A change that keeps the
+//! field names but alters a field's type is not detected; bump the
+//! domain's schema revision for those.
+
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use sha2::{Digest, Sha256};
+
+/// Number of hex characters of the digest kept in keys
+const FINGERPRINT_LEN: usize = 8;
+
+/// Short, stable version string for `T`, e.g. `v1a2b3c4d`.
+///
+/// `revision` is mixed in so a manual bump changes the version even when
+/// the field list does not.
+pub fn schema_version<T: DeserializeOwned>(revision: u32) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(revision.to_le_bytes());
+    match struct_shape::<T>() {
+        Some((name, fields)) => {
+            hasher.update(name.as_bytes());
+            for field in fields {
+                hasher.update(b"\0");
+                hasher.update(field.as_bytes());
+            }
+        }
+        // Not a plain struct: fall back to the Rust type name
+        None => hasher.update(std::any::type_name::<T>().as_bytes()),
+    }
+
+    let digest = hex::encode(hasher.finalize());
+    format!("v{}", &digest[..FINGERPRINT_LEN])
+}
+
+/// Struct name and field names of `T`, if it deserializes as a struct
+pub fn struct_shape<T: DeserializeOwned>(
+) -> Option<(&'static str, &'static [&'static str])> {
+    let mut shape = None;
+    // Always errors: the collector only records the requested shape
+    let _ = T::deserialize(ShapeCollector { shape: &mut shape });
+    shape
+}
+
+/// Deserializer that records the shape a type asks for and then bails out
+struct ShapeCollector<'a> {
+    shape: &'a mut Option<(&'static str, &'static [&'static str])>,
+}
+
+impl<'de> Deserializer<'de> for ShapeCollector<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.shape = Some((name, fields));
+        Err(de::Error::custom("shape collected"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
@@ -1,116 +1,624 @@
 //! Generic cache service for MoneyWise backend.
 //!
-//! Provides high-level, domain-agnostic operations on top of Redis.
-//! Uses a simple round-robin pool of `ConnectionManager`s to improve
-//! concurrency and match configured `max_connections`.
+//! Provides high-level, domain-agnostic operations on top of a
+//! `CacheBackend` (Redis in production, in-memory for tests and local
+//! development without Redis).
 //!
+//! Cache fills go through `get_or_fill`, which coalesces concurrent misses
+//! for the same key (and, optionally, across instances via a backend lock).
+//! `get_or_refresh` adds stale-while-revalidate on top: entries close to
+//! expiry are served while being refreshed in the background.
+//!
+//! Entries can carry tags (e.g. `period:2025-08`); `invalidate_tag` removes
+//! every key written with a tag, whatever the exact key variants were. On a
+//! Redis Cluster, a key and its tags must share a `{hash tag}`.
+//!
+//! With `CacheConfig::l1_enabled`, reads are served from an in-process
+//! `LocalCache` first. Invalidations evict locally and are published on
+//! `INVALIDATION_CHANNEL`, which every instance subscribes to.
+//!
+//! Invalidations that fail (e.g. while Redis is down) are returned as
+//! errors and remembered in `MissedInvalidations`; they are replayed before
+//! the next backend read once the backend is available again, so entries
+//! written before an outage are not served after it.
 
-use redis::{aio::ConnectionManager, Client};
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
-};
-use tracing::{error, info};
+use futures_util::StreamExt;
+use std::future::Future;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
 
-use crate::error::{AppError, Result};
+use crate::error::Result;
+use crate::metrics::jobs::{job, record_job, JobOutcome};
+use crate::redis_topology::{group_by_slot, RedisTopology};
 
 use crate::cache::core::{
+    backend::{
+        prefix_pattern, CacheBackend, CacheBackendKind, InMemoryCacheBackend,
+        RedisCacheBackend,
+    },
+    codec::decode,
     config::CacheConfig,
-    operations::{delete_keys, get_value, set_with_ttl},
-    serialization::serialize,
+    local::{
+        decode_invalidation, encode_invalidation, LocalCache,
+        INVALIDATION_CHANNEL,
+    },
+    metrics::{self, CacheStats, Lookup, Operation},
+    missed::{MissedBatch, MissedInvalidations},
+    single_flight::SingleFlight,
 };
 
-/// Generic Redis-based cache service for managing distributed caching operations
+/// Prefix of every key written by the cache (values, tags and locks)
+pub const KEY_PREFIX: &str = "moneywise:";
+
+/// How often an instance waiting on another instance's fill lock re-reads the key
+const FILL_LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Keys deleted per `DEL` when flushing a namespace
+const FLUSH_BATCH_SIZE: usize = 500;
+
+/// Backoff bounds for re-subscribing to invalidations after a disconnect
+const SUBSCRIBE_RETRY_MIN: Duration = Duration::from_millis(500);
+const SUBSCRIBE_RETRY_MAX: Duration = Duration::from_secs(30);
+
+/// Outcome of trying to take the cross-instance fill lock
+enum FillLock<T> {
+    /// We hold the lock and must fill the key, then release with this token
+    Held(String),
+    /// Another instance filled the key while we waited
+    Filled(T),
+    /// Lock unavailable (backend error or holder timed out); fill without it
+    Skipped,
+}
+
+/// Generic cache service for managing distributed caching operations
 /// This service is domain-agnostic and provides core caching functionality
 #[derive(Clone)]
 pub struct CacheService {
-    /// Simple pool of Redis connection managers for concurrency
-    connection_pool: Arc<Vec<ConnectionManager>>,
-    /// Next index for round-robin selection
-    next_index: Arc<AtomicUsize>,
+    /// Storage for encoded values (Redis or in-memory)
+    backend: Arc<dyn CacheBackend>,
     /// Cache configuration with TTL (time to live) settings and connection parameters
     config: CacheConfig,
+    /// Coalesces concurrent fills of the same key in this process
+    single_flight: Arc<SingleFlight>,
+    /// Optional in-process L1 tier (see `CacheConfig::l1_enabled`)
+    l1: Option<Arc<LocalCache>>,
+    /// Invalidations to replay once the backend is reachable again
+    missed: Arc<MissedInvalidations>,
+    /// Held while replaying, so reads wait for stale entries to be gone
+    replaying: Arc<tokio::sync::Mutex<()>>,
 }
 
 impl CacheService {
-    /// Creates a new Redis cache service with a simple connection pool.
+    /// Creates a cache service on the backend selected by `config.backend`.
     pub async fn new(config: CacheConfig) -> Result<Self> {
-        // Build a simple pool of ConnectionManager instances to honor max_connections
-        let mut pool = Vec::with_capacity(config.max_connections);
-        for _ in 0..config.max_connections {
-            let client =
-                Client::open(config.redis_url.clone()).map_err(|e| {
-                    error!("Failed to create Redis client: {}", e);
-                    AppError::Cache(e)
-                })?;
-            let manager =
-                ConnectionManager::new(client).await.map_err(|e| {
-                    error!("Failed to create Redis connection manager: {}", e);
-                    AppError::Cache(e)
-                })?;
-            pool.push(manager);
-        }
-
-        info!(
-            "Redis cache service initialized with a pool of {} connections (timeout {}s)",
-            config.max_connections,
-            config.connection_timeout.as_secs()
-        );
+        match config.backend {
+            CacheBackendKind::Redis => {
+                let backend = RedisCacheBackend::new(&config)?;
+                let topology = config.redis_topology.clone();
+                let redis_url = config.redis_url.clone();
+                let service = Self::with_backend(config, Arc::new(backend));
+                if let Some(l1) = &service.l1 {
+                    spawn_invalidation_listener(
+                        topology,
+                        redis_url,
+                        Arc::downgrade(l1),
+                    );
+                }
+                Ok(service)
+            }
+            CacheBackendKind::Memory => {
+                warn!(
+                    "Using the in-memory cache backend; cached data is not shared between instances"
+                );
+                let backend =
+                    InMemoryCacheBackend::new(config.memory_max_entries);
+                Ok(Self::with_backend(config, Arc::new(backend)))
+            }
+        }
+    }
+
+    /// Creates a cache service on an existing backend.
+    ///
+    /// No invalidation subscription is started, so an L1 tier only sees
+    /// invalidations made through this service.
+    pub fn with_backend(
+        config: CacheConfig,
+        backend: Arc<dyn CacheBackend>,
+    ) -> Self {
+        let l1 = config.l1_enabled.then(|| {
+            info!(
+                "L1 cache enabled ({} entries, TTL {}s)",
+                config.l1_max_entries,
+                config.l1_ttl.as_secs()
+            );
+            Arc::new(LocalCache::new(config.l1_max_entries, config.l1_ttl))
+        });
 
-        Ok(Self {
-            connection_pool: Arc::new(pool),
-            next_index: Arc::new(AtomicUsize::new(0)),
+        info!("Cache service using the {} backend", backend.name());
+        Self {
+            backend,
             config,
-        })
+            single_flight: Arc::new(SingleFlight::new()),
+            l1,
+            missed: Arc::new(MissedInvalidations::new()),
+            replaying: Arc::new(tokio::sync::Mutex::new(())),
+        }
     }
 
-    /// Select a connection via round-robin.
-    fn select_connection(&self) -> &ConnectionManager {
-        let idx = self.next_index.fetch_add(1, Ordering::Relaxed)
-            % self.connection_pool.len();
-        &self.connection_pool[idx]
+    /// The backend storing cached values
+    pub fn backend(&self) -> &Arc<dyn CacheBackend> {
+        &self.backend
     }
 
     /// Cache any serializable data with a custom key and TTL (seconds).
     ///
-    /// - Serializes using JSON
-    /// - Uses `SETEX` under the hood for atomic TTL
+    /// - Encodes with the configured `CacheConfig::codec`
+    /// - Writes value and TTL atomically (`SETEX` on Redis)
     pub async fn cache_data<T: serde::Serialize>(
         &self,
         key: &str,
         data: &T,
         ttl_seconds: usize,
     ) -> Result<()> {
-        let value = serialize(data)?;
+        self.cache_data_tagged(key, data, ttl_seconds, &[]).await
+    }
+
+    /// Cache data like `cache_data`, recording `key` under each of `tags`
+    /// so it can later be removed with `invalidate_tag`.
+    pub async fn cache_data_tagged<T: serde::Serialize>(
+        &self,
+        key: &str,
+        data: &T,
+        ttl_seconds: usize,
+        tags: &[&str],
+    ) -> Result<()> {
+        let started = Instant::now();
+        let value = self.config.codec.encode(data)?;
+
+        if tags.is_empty() {
+            self.backend.set_ex(key, &value, ttl_seconds).await?;
+        } else {
+            let tag_keys: Vec<String> =
+                tags.iter().map(|tag| tag_key(tag)).collect();
+            self.backend
+                .set_ex_tagged(key, &value, ttl_seconds, &tag_keys)
+                .await?;
+        }
 
-        let conn = self.select_connection().clone();
-        set_with_ttl(&conn, &self.config, key, &value, ttl_seconds).await
+        if let Some(l1) = &self.l1 {
+            l1.insert(key, value, ttl_seconds);
+        }
+        metrics::observe(key, Operation::Set, started.elapsed());
+        Ok(())
     }
 
     /// Retrieve cached data by key (typed).
     ///
     /// Returns `Ok(None)` on cache miss or when deserialization fails
-    /// (corrupted data is purged proactively).
+    /// (corrupted data is purged proactively). With L1 enabled, L1 is
+    /// checked first and backend hits are copied into it.
+    ///
+    /// Every call is counted as a hit, miss or corrupt read in
+    /// `cache::core::metrics`.
     pub async fn get_cached_data<
         T: serde::de::DeserializeOwned + Send + 'static,
     >(
         &self,
         key: &str,
     ) -> Result<Option<T>> {
-        let conn = self.select_connection().clone();
-        get_value::<T>(&conn, &self.config, key).await
+        let started = Instant::now();
+        let (data, lookup) = self.read::<T>(key).await?;
+        if lookup != Lookup::Corrupt {
+            metrics::record_lookup(key, lookup);
+        }
+        metrics::observe(key, Operation::Get, started.elapsed());
+        Ok(data)
+    }
+
+    /// Read and decode `key`. Only corrupt values are counted here, so the
+    /// re-checks of `get_or_fill` don't inflate hits and misses.
+    async fn read<T: serde::de::DeserializeOwned + Send + 'static>(
+        &self,
+        key: &str,
+    ) -> Result<(Option<T>, Lookup)> {
+        let (data, lookup, _) = self.read_entry(key, false).await?;
+        Ok((data, lookup))
+    }
+
+    /// Like `read`, also returning the remaining backend TTL when
+    /// `with_ttl` is set and the value came from the backend (L1 hits have
+    /// no TTL to report and are always considered fresh).
+    async fn read_entry<T: serde::de::DeserializeOwned + Send + 'static>(
+        &self,
+        key: &str,
+        with_ttl: bool,
+    ) -> Result<(Option<T>, Lookup, Option<Duration>)> {
+        self.replay_missed().await;
+
+        if let Some(l1) = &self.l1 {
+            if let Some(value) = l1.get(key) {
+                if let Some(data) = decode::<T>(&value)? {
+                    debug!("L1 cache hit for key {}", key);
+                    return Ok((Some(data), Lookup::Hit, None));
+                }
+                l1.remove(&[key]);
+            }
+        }
+
+        let entry = if with_ttl {
+            self.backend.get_with_ttl(key).await?
+        } else {
+            self.backend.get(key).await?.map(|value| (value, None))
+        };
+        let Some((value, ttl)) = entry else {
+            debug!("Cache miss for key {}", key);
+            return Ok((None, Lookup::Miss, None));
+        };
+        let Some(data) = decode::<T>(&value)? else {
+            warn!("Purging undecodable cache value for key {}", key);
+            let _ = self.backend.del(&[key]).await;
+            metrics::record_lookup(key, Lookup::Corrupt);
+            return Ok((None, Lookup::Corrupt, None));
+        };
+
+        if let Some(l1) = &self.l1 {
+            // Without a backend TTL, the L1 TTL alone bounds the copy
+            let ttl_seconds =
+                ttl.map_or(usize::MAX, |ttl| ttl.as_secs() as usize);
+            l1.insert(key, value, ttl_seconds);
+        }
+        Ok((Some(data), Lookup::Hit, ttl))
+    }
+
+    /// Read `key`, or compute it with `load` and cache it under `tags` on a
+    /// miss.
+    ///
+    /// Concurrent misses for the same key in this process share one `load`
+    /// call. With `CacheConfig::fill_lock`, instances also coordinate through
+    /// a backend lock: the holder loads, the others wait for its value
+    /// (up to `fill_lock_ttl`) before falling back to loading themselves.
+    pub async fn get_or_fill<T, F, Fut>(
+        &self,
+        key: &str,
+        ttl_seconds: usize,
+        tags: &[&str],
+        load: F,
+    ) -> Result<T>
+    where
+        T: serde::Serialize
+            + serde::de::DeserializeOwned
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if let Some(cached) = self.get_cached_data::<T>(key).await? {
+            return Ok(cached);
+        }
+
+        self.single_flight
+            .run(key, || async move {
+                // A previous flight may have filled the key while we queued
+                if let (Some(cached), _) = self.read::<T>(key).await? {
+                    return Ok(cached);
+                }
+
+                let lock_token = if self.config.fill_lock {
+                    match self.acquire_fill_lock::<T>(key).await {
+                        FillLock::Held(token) => Some(token),
+                        FillLock::Filled(value) => return Ok(value),
+                        FillLock::Skipped => None,
+                    }
+                } else {
+                    None
+                };
+
+                let result = load().await;
+                if let Ok(value) = &result {
+                    // Don't fail the request if the cache write fails
+                    let _ = self
+                        .cache_data_tagged(key, value, ttl_seconds, tags)
+                        .await;
+                }
+
+                if let Some(token) = lock_token {
+                    let _ =
+                        self.backend.unlock(&fill_lock_key(key), &token).await;
+                }
+
+                result
+            })
+            .await
+    }
+
+    /// Like `get_or_fill`, but with stale-while-revalidate: a hit whose
+    /// remaining TTL is below `CacheConfig::refresh_ahead` is served as is
+    /// while `load` refreshes it in the background, so hot keys are
+    /// rewritten before they expire instead of turning into misses.
+    ///
+    /// Only one refresh per key runs at a time across instances (guarded by
+    /// a backend lock); `load` must therefore be `'static`.
+    pub async fn get_or_refresh<T, F, Fut>(
+        &self,
+        key: &str,
+        ttl_seconds: usize,
+        tags: &[&str],
+        load: F,
+    ) -> Result<T>
+    where
+        T: serde::Serialize
+            + serde::de::DeserializeOwned
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+    {
+        let refresh_ahead = self.config.refresh_ahead;
+        if refresh_ahead.is_zero() {
+            return self.get_or_fill(key, ttl_seconds, tags, load).await;
+        }
+
+        let started = Instant::now();
+        let (data, _, ttl) = self.read_entry::<T>(key, true).await?;
+        let Some(data) = data else {
+            // Counted as a miss by `get_or_fill`
+            return self.get_or_fill(key, ttl_seconds, tags, load).await;
+        };
+        metrics::record_lookup(key, Lookup::Hit);
+        metrics::observe(key, Operation::Get, started.elapsed());
+
+        if ttl.is_some_and(|ttl| ttl < refresh_ahead) {
+            self.spawn_refresh(key, ttl_seconds, tags, load);
+        }
+        Ok(data)
+    }
+
+    /// Recompute `key` with `load` in the background, unless another
+    /// refresh of it is already running
+    fn spawn_refresh<T, F, Fut>(
+        &self,
+        key: &str,
+        ttl_seconds: usize,
+        tags: &[&str],
+        load: F,
+    ) where
+        T: serde::Serialize + Send + Sync + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+    {
+        let service = self.clone();
+        let key = key.to_string();
+        let tags: Vec<String> =
+            tags.iter().map(|tag| tag.to_string()).collect();
+
+        tokio::spawn(async move {
+            let started = Instant::now();
+            let lock_key = refresh_lock_key(&key);
+            let token = Uuid::new_v4().to_string();
+            let lock_ttl = service.config.fill_lock_ttl.as_millis() as u64;
+            let record = |outcome| {
+                record_job(job::CACHE_REFRESH, outcome, started.elapsed())
+            };
+            match service.backend.try_lock(&lock_key, &token, lock_ttl).await {
+                Ok(true) => {}
+                Ok(false) => return record(JobOutcome::Skipped),
+                Err(e) => {
+                    warn!("Failed to take refresh lock {}: {}", lock_key, e);
+                    return record(JobOutcome::Failure);
+                }
+            }
+
+            debug!("Refreshing {} ahead of expiry", key);
+            let outcome = match load().await {
+                Ok(value) => {
+                    let tags: Vec<&str> =
+                        tags.iter().map(String::as_str).collect();
+                    match service
+                        .cache_data_tagged(&key, &value, ttl_seconds, &tags)
+                        .await
+                    {
+                        Ok(()) => JobOutcome::Success,
+                        Err(e) => {
+                            warn!("Failed to store refreshed {}: {}", key, e);
+                            JobOutcome::Failure
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Background refresh of {} failed: {}", key, e);
+                    JobOutcome::Failure
+                }
+            };
+
+            let _ = service.backend.unlock(&lock_key, &token).await;
+            record(outcome);
+        });
+    }
+
+    /// Take the fill lock for `key`, or wait for the instance holding it
+    async fn acquire_fill_lock<T>(&self, key: &str) -> FillLock<T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let lock_key = fill_lock_key(key);
+        let token = Uuid::new_v4().to_string();
+        let ttl = self.config.fill_lock_ttl;
+
+        match self
+            .backend
+            .try_lock(&lock_key, &token, ttl.as_millis() as u64)
+            .await
+        {
+            Ok(true) => return FillLock::Held(token),
+            Ok(false) => {
+                debug!("Fill lock {} held elsewhere, waiting", lock_key)
+            }
+            Err(e) => {
+                warn!("Failed to take fill lock {}: {}", lock_key, e);
+                return FillLock::Skipped;
+            }
+        }
+
+        let deadline = Instant::now() + ttl;
+        while Instant::now() < deadline {
+            tokio::time::sleep(FILL_LOCK_POLL_INTERVAL).await;
+            if let Ok((Some(value), _)) = self.read::<T>(key).await {
+                return FillLock::Filled(value);
+            }
+        }
+
+        warn!(
+            "Fill lock {} not released within {:?}, loading anyway",
+            lock_key, ttl
+        );
+        FillLock::Skipped
     }
 
     /// Invalidate a single cache key.
     pub async fn invalidate_cache(&self, key: &str) -> Result<()> {
-        let conn = self.select_connection().clone();
-        delete_keys(&conn, &self.config, &[key]).await
+        self.invalidate_multiple_keys(&[key]).await
     }
 
     /// Invalidate multiple cache keys.
+    ///
+    /// With L1 enabled, the keys are also evicted from this instance's L1
+    /// and broadcast so other instances evict them too. If the backend
+    /// fails, the error is returned and the keys are deleted on a later
+    /// read, once the backend is available.
     pub async fn invalidate_multiple_keys(&self, keys: &[&str]) -> Result<()> {
-        let conn = self.select_connection().clone();
-        delete_keys(&conn, &self.config, keys).await
+        if let Some(l1) = &self.l1 {
+            l1.remove(keys);
+        }
+        if let Err(e) = self.backend.del(keys).await {
+            warn!(
+                "Failed to invalidate {:?}, retrying once the cache is reachable: {}",
+                keys, e
+            );
+            self.missed.record_keys(keys);
+            return Err(e);
+        }
+
+        if self.l1.is_some() {
+            let message = encode_invalidation(keys);
+            if let Err(e) =
+                self.backend.publish(INVALIDATION_CHANNEL, &message).await
+            {
+                // Peers fall back to their short L1 TTL
+                warn!("Failed to broadcast invalidation of {:?}: {}", keys, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Invalidate every key cached with `tag` and return how many there were.
+    ///
+    /// Failures are retried like those of `invalidate_multiple_keys`.
+    pub async fn invalidate_tag(&self, tag: &str) -> Result<usize> {
+        let keys = match self.backend.take_set(&tag_key(tag)).await {
+            Ok(keys) => keys,
+            Err(e) => {
+                warn!(
+                    "Failed to invalidate tag {}, retrying once the cache is reachable: {}",
+                    tag, e
+                );
+                self.missed.record_tag(tag);
+                return Err(e);
+            }
+        };
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        debug!("Invalidating {} keys tagged {}", keys.len(), tag);
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        self.invalidate_multiple_keys(&keys).await?;
+        Ok(keys.len())
+    }
+
+    /// Replay invalidations that failed earlier, if the backend is
+    /// available again
+    async fn replay_missed(&self) {
+        if self.missed.is_empty() || !self.backend.is_available() {
+            return;
+        }
+        let _replaying = self.replaying.lock().await;
+        let batch = self.missed.take();
+        if batch.is_empty() {
+            // Replayed by another caller while we waited
+            return;
+        }
+
+        match self.apply_missed(&batch).await {
+            Ok(()) => info!(
+                "Replayed {} missed cache invalidations",
+                batch.keys.len() + batch.tags.len()
+            ),
+            Err(e) => {
+                warn!("Failed to replay missed cache invalidations: {}", e);
+                self.missed.restore(batch);
+            }
+        }
+    }
+
+    async fn apply_missed(&self, batch: &MissedBatch) -> Result<()> {
+        if batch.flush_all {
+            if let Some(l1) = &self.l1 {
+                l1.clear();
+            }
+            self.flush_namespace(KEY_PREFIX).await?;
+            return Ok(());
+        }
+
+        for tag in &batch.tags {
+            self.invalidate_tag(tag).await?;
+        }
+        for chunk in batch.keys.chunks(FLUSH_BATCH_SIZE) {
+            let chunk: Vec<&str> = chunk.iter().map(String::as_str).collect();
+            self.invalidate_multiple_keys(&chunk).await?;
+        }
+        Ok(())
+    }
+
+    /// Delete every key starting with `prefix` (e.g. a whole domain namespace,
+    /// all schema versions included) and return how many were deleted.
+    ///
+    /// Keys are found with `SCAN` and deleted in batches of one hash slot
+    /// each, so this is safe to run against a live Redis or Redis Cluster.
+    pub async fn flush_namespace(&self, prefix: &str) -> Result<usize> {
+        let keys = self.keys_with_prefix(prefix).await?;
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+        for slot in group_by_slot(&keys) {
+            for batch in slot.chunks(FLUSH_BATCH_SIZE) {
+                self.invalidate_multiple_keys(batch).await?;
+            }
+        }
+
+        info!("Flushed {} cache keys under {}", keys.len(), prefix);
+        Ok(keys.len())
+    }
+
+    /// List every key starting with `prefix`, found with `SCAN`.
+    ///
+    /// Glob characters in `prefix` match literally.
+    pub async fn keys_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        self.backend.scan(&prefix_pattern(prefix)).await
+    }
+
+    /// Hit/miss counters and latencies recorded since startup.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            circuit_open: !self.backend.is_available(),
+            ..metrics::snapshot(self.backend.name())
+        }
     }
 
     /// Get the cache configuration.
@@ -118,3 +626,82 @@ impl CacheService {
         &self.config
     }
 }
+
+/// Subscribe to `INVALIDATION_CHANNEL` and evict received keys from `l1`.
+///
+/// Reconnects with exponential backoff (to the current primary under
+/// Sentinel) and clears `l1` after each (re)subscription, since
+/// invalidations sent while disconnected are lost.
+/// Exits once the cache service owning `l1` has been dropped.
+fn spawn_invalidation_listener(
+    topology: RedisTopology,
+    redis_url: String,
+    l1: Weak<LocalCache>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut backoff = SUBSCRIBE_RETRY_MIN;
+        loop {
+            match topology.pubsub(&redis_url).await {
+                Ok(mut pubsub) => {
+                    match pubsub.subscribe(INVALIDATION_CHANNEL).await {
+                        Ok(()) => {
+                            let Some(cache) = l1.upgrade() else { return };
+                            cache.clear();
+                            drop(cache);
+                            backoff = SUBSCRIBE_RETRY_MIN;
+                            debug!("Subscribed to {}", INVALIDATION_CHANNEL);
+
+                            let mut messages = pubsub.on_message();
+                            while let Some(msg) = messages.next().await {
+                                let Some(cache) = l1.upgrade() else { return };
+                                let keys = msg
+                                    .get_payload::<String>()
+                                    .ok()
+                                    .and_then(|p| decode_invalidation(&p));
+                                match keys {
+                                    Some(keys) => cache.remove(&keys),
+                                    None => warn!(
+                                        "Ignoring malformed message on {}",
+                                        INVALIDATION_CHANNEL
+                                    ),
+                                }
+                            }
+                            warn!(
+                                "Lost subscription to {}, reconnecting",
+                                INVALIDATION_CHANNEL
+                            );
+                        }
+                        Err(e) => warn!(
+                            "Failed to subscribe to {}: {}",
+                            INVALIDATION_CHANNEL, e
+                        ),
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to open Redis pub/sub connection: {}", e)
+                }
+            }
+
+            if l1.strong_count() == 0 {
+                return;
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(SUBSCRIBE_RETRY_MAX);
+        }
+    })
+}
+
+/// Set holding the keys cached with `tag`
+fn tag_key(tag: &str) -> String {
+    format!("{}tag:{}", KEY_PREFIX, tag)
+}
+
+/// Key of the cross-instance fill lock for `key`
+fn fill_lock_key(key: &str) -> String {
+    format!("{}:fill_lock", key)
+}
+
+/// Key of the lock held while `key` is refreshed in the background
+fn refresh_lock_key(key: &str) -> String {
+    format!("{}:refresh_lock", key)
+}
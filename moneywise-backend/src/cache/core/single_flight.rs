@@ -0,0 +1,107 @@
+//! In-process request coalescing for cache fills.
+//!
+//! When many requests miss the same key at once, only the first one (the
+//! leader) runs the loader; the others await its result instead of hitting
+//! the database themselves.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+use crate::error::{AppError, Result};
+
+/// Type-erased result shared with followers; `None` if the leader failed
+type Shared = Option<Arc<dyn Any + Send + Sync>>;
+
+/// Per-key in-flight slot
+type Slot = Arc<OnceCell<Shared>>;
+
+/// Coalesces concurrent loads of the same key within one process
+#[derive(Default)]
+pub struct SingleFlight {
+    inflight: Mutex<HashMap<String, Slot>>,
+}
+
+impl SingleFlight {
+    /// Create an empty coalescing group
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `load` for `key` unless a load for the same key is already in
+    /// flight, in which case wait for it and return a clone of its value.
+    ///
+    /// The leader gets its own `Result`; followers get
+    /// `AppError::Internal` if the leader failed.
+    pub async fn run<T, F, Fut>(&self, key: &str, load: F) -> Result<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let slot = self.slot(key);
+
+        let mut own: Option<Result<T>> = None;
+        let shared = slot
+            .get_or_init(|| async {
+                let result = load().await;
+                let shared: Shared = match &result {
+                    Ok(value) => Some(Arc::new(value.clone())),
+                    Err(_) => None,
+                };
+                own = Some(result);
+                shared
+            })
+            .await
+            .clone();
+
+        self.finish(key, &slot);
+
+        // Leader: return its own result (including the original error)
+        if let Some(result) = own {
+            return result;
+        }
+
+        shared
+            .and_then(|value| value.downcast_ref::<T>().cloned())
+            .ok_or_else(|| {
+                AppError::Internal(format!(
+                    "Concurrent cache fill for key {} failed",
+                    key
+                ))
+            })
+    }
+
+    /// Number of keys currently being loaded
+    pub fn in_flight(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Get or create the slot for `key`
+    fn slot(&self, key: &str) -> Slot {
+        self.lock()
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(OnceCell::new()))
+            .clone()
+    }
+
+    /// Drop the slot once its load has completed, so the next miss starts a
+    /// fresh load instead of reusing a stale value
+    fn finish(&self, key: &str, slot: &Slot) {
+        let mut inflight = self.lock();
+        if inflight
+            .get(key)
+            .is_some_and(|current| Arc::ptr_eq(current, slot))
+        {
+            inflight.remove(key);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Slot>> {
+        self.inflight
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -1,41 +1,108 @@
 //! Budget domain cache key management.
 //!
 //! Provides consistent key generation for budget-related cache operations.
-//! All keys use the `moneywise:budget:` namespace prefix for organization.
+//! All keys start with the `moneywise:{budget:` namespace prefix, and embed
+//! the schema version of the cached model (see `cache::core::schema`), so a
+//! model change moves its entries to new keys.
+//!
+//! Month-level keys and their period tag carry a per-month hash tag such as
+//! `{budget:2025-08}`, so in Redis Cluster a value and its tag set live on
+//! one slot and can be written by a single script, while different months
+//! spread over the cluster. Individual budgets are looked up by id alone, so
+//! each one gets its own `{budget:item:<id>}` hash tag and no tag set.
+
+use std::sync::OnceLock;
+
+use crate::cache::core::schema::schema_version;
+use crate::models::{BudgetApi, BudgetOverviewApi, CategoryBudgetApi};
+
+/// Prefix shared by every budget cache key, across all periods and schema
+/// versions
+pub const NAMESPACE: &str = "moneywise:{budget:";
+
+/// Bump when a cached budget model changes a field's type or meaning
+/// without renaming it (field additions/removals are picked up automatically)
+pub const SCHEMA_REVISION: u32 = 1;
+
+/// Schema version of `BudgetOverviewApi` as embedded in overview keys
+pub fn overview_version() -> &'static str {
+    static VERSION: OnceLock<String> = OnceLock::new();
+    VERSION.get_or_init(|| schema_version::<BudgetOverviewApi>(SCHEMA_REVISION))
+}
+
+/// Schema version of `CategoryBudgetApi` as embedded in categories keys
+pub fn categories_version() -> &'static str {
+    static VERSION: OnceLock<String> = OnceLock::new();
+    VERSION.get_or_init(|| schema_version::<CategoryBudgetApi>(SCHEMA_REVISION))
+}
+
+/// Schema version of `BudgetApi` as embedded in budget item keys
+pub fn budget_version() -> &'static str {
+    static VERSION: OnceLock<String> = OnceLock::new();
+    VERSION.get_or_init(|| schema_version::<BudgetApi>(SCHEMA_REVISION))
+}
+
+/// Normalized "<year>-<month>" form of a period, with a two-digit month
+fn period(month: &str, year: &str) -> String {
+    match month.parse::<u32>() {
+        Ok(m) => format!("{}-{:02}", year, m),
+        Err(_) => format!("{}-{}", year, month),
+    }
+}
+
+/// Redis Cluster hash tag shared by every key and tag of a month.
+/// Format: "{budget:<year>-<month>}", e.g. "{budget:2025-08}"
+pub fn period_hash_tag(month: &str, year: &str) -> String {
+    format!("{{budget:{}}}", period(month, year))
+}
 
 /// Generate cache key for budget overview data with namespace prefix.
-/// Key format: "moneywise:budget:overview:{month}:{year}" or with currency
-///             "moneywise:budget:overview:{month}:{year}:{currency}"
+/// Key format: "moneywise:{budget:<period>}:<version>:overview:<month>:<year>"
+///             or with currency "...:overview:<month>:<year>:<currency>"
 /// Used for caching monthly budget overview summaries
 pub fn overview_key(month: &str, year: &str, currency: Option<&str>) -> String {
+    let prefix = format!(
+        "moneywise:{}:{}",
+        period_hash_tag(month, year),
+        overview_version()
+    );
     match currency {
-        Some(c) => {
-            format!("moneywise:budget:overview:{}:{}:{}", month, year, c)
-        }
-        None => format!("moneywise:budget:overview:{}:{}", month, year),
+        Some(c) => format!("{}:overview:{}:{}:{}", prefix, month, year, c),
+        None => format!("{}:overview:{}:{}", prefix, month, year),
     }
 }
 
 /// Generate cache key for category budget data with namespace prefix.
-/// Key format: "moneywise:budget:categories:{month}:{year}" or with currency
-///             "moneywise:budget:categories:{month}:{year}:{currency}"
+/// Key format: "moneywise:{budget:<period>}:<version>:categories:<month>:<year>"
+///             or with currency "...:categories:<month>:<year>:<currency>"
 /// Used for caching category-specific budget breakdowns
 pub fn categories_key(
     month: &str,
     year: &str,
     currency: Option<&str>,
 ) -> String {
+    let prefix = format!(
+        "moneywise:{}:{}",
+        period_hash_tag(month, year),
+        categories_version()
+    );
     match currency {
-        Some(c) => {
-            format!("moneywise:budget:categories:{}:{}:{}", month, year, c)
-        }
-        None => format!("moneywise:budget:categories:{}:{}", month, year),
+        Some(c) => format!("{}:categories:{}:{}:{}", prefix, month, year, c),
+        None => format!("{}:categories:{}:{}", prefix, month, year),
     }
 }
 
 /// Generate cache key for individual budget data with namespace prefix.
-/// Key format: "moneywise:budget:item:{id}"
+/// Key format: "moneywise:{budget:item:<id>}:<version>"
 /// Used for caching individual budget entries
 pub fn budget_key(id: &str) -> String {
-    format!("moneywise:budget:item:{}", id)
+    format!("{}item:{}}}:{}", NAMESPACE, id, budget_version())
+}
+
+/// Generate the tag shared by all cached data for a month.
+/// Tag format: "{budget:<year>-<month>}:period" with a two-digit month,
+/// e.g. "{budget:2025-08}:period"
+/// Covers every currency variant of the overview and category keys
+pub fn period_tag(month: &str, year: &str) -> String {
+    format!("{}:period", period_hash_tag(month, year))
 }
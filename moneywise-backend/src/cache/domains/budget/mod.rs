@@ -3,12 +3,20 @@
 //! Provides budget-specific caching functionality on top of the generic
 //! `CacheService`, including key management and TTL selection.
 //!
+//! Month-level entries are tagged with `keys::period_tag`, so writes can
+//! invalidate by tag instead of guessing every key variant. Individual
+//! budgets are invalidated by id.
+//!
 
 pub mod keys;
 
 use crate::{error::Result, models::*};
+use std::future::Future;
+use std::sync::Arc;
 
-use crate::cache::core::{config::CacheConfig, service::CacheService};
+use crate::cache::core::{
+    backend::CacheBackend, config::CacheConfig, service::CacheService,
+};
 
 /// Budget-specific cache service that wraps the generic cache service
 /// with budget-specific key generation and TTL management.
@@ -25,6 +33,21 @@ impl BudgetCache {
         Ok(Self { cache_service })
     }
 
+    /// Create a budget cache on an existing backend (e.g. an
+    /// `InMemoryCacheBackend` in tests).
+    pub fn with_backend(
+        config: CacheConfig,
+        backend: Arc<dyn CacheBackend>,
+    ) -> Self {
+        let cache_service = CacheService::with_backend(config, backend);
+        Self { cache_service }
+    }
+
+    /// The generic cache service (stats and key inspection).
+    pub fn service(&self) -> &CacheService {
+        &self.cache_service
+    }
+
     /// Cache budget overview data with appropriate TTL.
     pub async fn cache_budget_overview(
         &self,
@@ -34,11 +57,12 @@ impl BudgetCache {
         overview: &BudgetOverviewApi,
     ) -> Result<()> {
         let key = keys::overview_key(month, year, currency);
+        let tag = keys::period_tag(month, year);
         let ttl_seconds =
             self.cache_service.config().overview_ttl.as_secs() as usize;
 
         self.cache_service
-            .cache_data(&key, overview, ttl_seconds)
+            .cache_data_tagged(&key, overview, ttl_seconds, &[&tag])
             .await
     }
 
@@ -56,6 +80,30 @@ impl BudgetCache {
             .await
     }
 
+    /// Return the cached overview, or compute it with `load` on a miss.
+    /// Concurrent misses share a single `load` call, and an entry close to
+    /// expiry is refreshed with `load` in the background while served.
+    pub async fn get_or_fill_budget_overview<F, Fut>(
+        &self,
+        month: &str,
+        year: &str,
+        currency: Option<&str>,
+        load: F,
+    ) -> Result<BudgetOverviewApi>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<BudgetOverviewApi>> + Send + 'static,
+    {
+        let key = keys::overview_key(month, year, currency);
+        let tag = keys::period_tag(month, year);
+        let ttl_seconds =
+            self.cache_service.config().overview_ttl.as_secs() as usize;
+
+        self.cache_service
+            .get_or_refresh(&key, ttl_seconds, &[&tag], load)
+            .await
+    }
+
     /// Cache category budget data with appropriate TTL.
     pub async fn cache_category_budgets(
         &self,
@@ -65,11 +113,12 @@ impl BudgetCache {
         categories: &[CategoryBudgetApi],
     ) -> Result<()> {
         let key = keys::categories_key(month, year, currency);
+        let tag = keys::period_tag(month, year);
         let ttl_seconds =
             self.cache_service.config().categories_ttl.as_secs() as usize;
 
         self.cache_service
-            .cache_data(&key, &categories.to_vec(), ttl_seconds)
+            .cache_data_tagged(&key, &categories.to_vec(), ttl_seconds, &[&tag])
             .await
     }
 
@@ -87,6 +136,30 @@ impl BudgetCache {
             .await
     }
 
+    /// Return the cached category budgets, or compute them with `load` on a
+    /// miss. Concurrent misses share a single `load` call, and an entry close
+    /// to expiry is refreshed with `load` in the background while served.
+    pub async fn get_or_fill_category_budgets<F, Fut>(
+        &self,
+        month: &str,
+        year: &str,
+        currency: Option<&str>,
+        load: F,
+    ) -> Result<Vec<CategoryBudgetApi>>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Vec<CategoryBudgetApi>>> + Send + 'static,
+    {
+        let key = keys::categories_key(month, year, currency);
+        let tag = keys::period_tag(month, year);
+        let ttl_seconds =
+            self.cache_service.config().categories_ttl.as_secs() as usize;
+
+        self.cache_service
+            .get_or_refresh(&key, ttl_seconds, &[&tag], load)
+            .await
+    }
+
     /// Cache individual budget data with TTL.
     pub async fn cache_budget(
         &self,
@@ -112,25 +185,40 @@ impl BudgetCache {
         self.cache_service.get_cached_data::<BudgetApi>(&key).await
     }
 
-    /// Invalidate cache entries for a specific month/year (overview + categories).
-    pub async fn invalidate_month_cache(
+    /// Invalidate the overviews and category budgets cached for a
+    /// month/year, in every currency.
+    pub async fn invalidate_period(
         &self,
         month: &str,
         year: &str,
-        currency: Option<&str>,
-    ) -> Result<()> {
-        let overview_key = keys::overview_key(month, year, currency);
-        let categories_key = keys::categories_key(month, year, currency);
-
+    ) -> Result<usize> {
         self.cache_service
-            .invalidate_multiple_keys(&[&overview_key, &categories_key])
+            .invalidate_tag(&keys::period_tag(month, year))
             .await
     }
 
+    /// Delete every budget cache entry, including entries written under
+    /// older schema versions. Returns the number of keys deleted.
+    pub async fn flush(&self) -> Result<usize> {
+        self.cache_service.flush_namespace(keys::NAMESPACE).await
+    }
+
     /// Invalidate cache for a specific budget ID.
     pub async fn invalidate_budget_cache(&self, id: &str) -> Result<()> {
         let key = keys::budget_key(id);
 
         self.cache_service.invalidate_cache(&key).await
     }
+
+    /// Invalidate cache for several budget IDs at once.
+    pub async fn invalidate_budgets(&self, ids: &[String]) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let keys: Vec<String> =
+            ids.iter().map(|id| keys::budget_key(id)).collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+        self.cache_service.invalidate_multiple_keys(&keys).await
+    }
 }
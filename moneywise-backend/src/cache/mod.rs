@@ -4,11 +4,17 @@
 //!
 //! Core Infrastructure:
 //! - core/ - Generic caching infrastructure
+//!   - backend/: Storage backends (Redis, in-memory TTL+LRU)
+//!   - codec.rs: Pluggable value encoding (JSON/MessagePack/bincode + compression)
 //!   - config.rs: Configuration structures and settings
-//!   - operations.rs: Core Redis operations
+//!   - local.rs: Optional in-process L1 cache, invalidated over Redis pub/sub
+//!   - metrics.rs: Hit/miss counters and latency histograms
+//!   - operations.rs: Core Redis operations (behind the Redis backend)
 //!   - retry.rs: Retry logic and error handling
+//!   - schema.rs: Model fingerprints embedded in cache keys
 //!   - serialization.rs: JSON serialization/deserialization utilities
 //!   - service.rs: Main caching service with high-level operations
+//!   - single_flight.rs: In-process coalescing of concurrent cache fills
 //!
 //! Domain-Specific Caches:
 //! - domains/ - Domain-specific cache implementations
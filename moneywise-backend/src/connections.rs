@@ -5,10 +5,9 @@
 
 use crate::cache::connection::init_cache;
 use crate::database::connection::init_database;
-use crate::rate_limiter::{RateLimitConfig, RateLimitService};
+use crate::rate_limiter::{ApiKeyStore, RateLimitConfig, RateLimitService};
 use crate::server::config::init_server_config;
 use sqlx::PgPool;
-use tracing;
 
 /// Default Redis URL when no environment variable is set
 pub const DEFAULT_REDIS_URL: &str = "redis://localhost:6379";
@@ -35,6 +34,30 @@ pub fn parse_redis_url_from_env(env_var: &str) -> String {
         .unwrap_or_else(|_| DEFAULT_REDIS_URL.to_string())
 }
 
+/// Parse an environment variable with a default value, logging warnings for invalid values.
+///
+/// This function provides better error visibility than `unwrap_or()` by logging
+/// when environment variables contain invalid values that cannot be parsed.
+pub fn parse_env_with_default<T>(var_name: &str, default_value: T) -> T
+where
+    T: std::str::FromStr + std::fmt::Display + Clone,
+    T::Err: std::fmt::Display,
+{
+    match std::env::var(var_name) {
+        Ok(value) => match value.parse::<T>() {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                tracing::warn!(
+                    "Invalid value '{}' for environment variable '{}': {}. Using default: {}",
+                    value, var_name, e, default_value
+                );
+                default_value
+            }
+        },
+        Err(_) => default_value,
+    }
+}
+
 /// Initialize all connections (database, cache, rate limiter, and server configuration)
 /// Returns a tuple containing the database pool, cache service, rate limiter, and server config
 pub async fn init_connections() -> Result<
@@ -52,11 +75,12 @@ pub async fn init_connections() -> Result<
     let cache_service = init_cache().await?;
     let server_config = init_server_config()?;
 
-    // Initialize rate limiter
+    // Initialize rate limiter with per-API-key quotas backed by the database
     let rate_limiter_config = RateLimitConfig::default();
     let rate_limiter = RateLimitService::new(rate_limiter_config)
         .await
-        .map_err(|e| format!("Failed to initialize rate limiter: {}", e))?;
+        .map_err(|e| format!("Failed to initialize rate limiter: {}", e))?
+        .with_api_keys(ApiKeyStore::new(pool.clone()));
 
     tracing::info!(
         "All connections and configurations initialized successfully"
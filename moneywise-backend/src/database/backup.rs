@@ -0,0 +1,599 @@
+//! Logical backup and restore for MoneyWise backend.
+//!
+//! Dumps `category_groups`, `categories` and `budgets` into a JSON-lines
+//! archive, without needing `pg_dump`:
+//!
+//! ```text
+//! {"format":"moneywise-backup","version":1,"created_at":"...","counts":{...}}
+//! {"table":"category_groups","row":{...}}
+//! {"table":"categories","row":{...}}
+//! {"table":"budgets","row":{...}}
+//! {"records":3,"checksum":"sha256:..."}
+//! ```
+//!
+//! The trailer holds the SHA-256 of every line before it, so truncated or
+//! edited archives are rejected before anything is written. Restores run
+//! in one transaction, parents first, with a `ConflictStrategy` for rows
+//! that already exist.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use std::fmt;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Value of the header's `format` field
+pub const BACKUP_FORMAT: &str = "moneywise-backup";
+
+/// Archive layout written by this binary; older versions stay readable
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Prefix of the trailer's checksum
+const CHECKSUM_PREFIX: &str = "sha256:";
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Invalid backup archive: {0}")]
+    Format(String),
+
+    #[error("Backup archive version {0} is newer than this binary supports ({BACKUP_VERSION})")]
+    UnsupportedVersion(u32),
+
+    #[error(
+        "Backup checksum mismatch (expected {expected}, computed {actual})"
+    )]
+    Checksum { expected: String, actual: String },
+
+    #[error("Row already exists in {table}: {message}")]
+    Conflict {
+        table: &'static str,
+        message: String,
+    },
+}
+
+/// A `category_groups` row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct CategoryGroupRow {
+    pub id: Uuid,
+    pub name: String,
+    pub sort_order: Option<i32>,
+    pub color: String,
+    pub icon: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A `categories` row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct CategoryRow {
+    pub id: Uuid,
+    pub name: String,
+    pub group_id: Option<Uuid>,
+    #[serde(rename = "type")]
+    #[sqlx(rename = "type")]
+    pub category_type: String, // 'expense' or 'income'
+    pub icon: Option<String>,
+    pub color: String,
+    pub is_default: Option<bool>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A `budgets` row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct BudgetRow {
+    pub id: Uuid,
+    pub month: i16,
+    pub year: i32,
+    pub category_id: Uuid,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub planned: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub spent: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub carryover: Decimal,
+    pub currency: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Rows per table
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub struct TableCounts {
+    pub category_groups: u64,
+    pub categories: u64,
+    pub budgets: u64,
+}
+
+impl fmt::Display for TableCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} category groups, {} categories, {} budgets",
+            self.category_groups, self.categories, self.budgets
+        )
+    }
+}
+
+/// Contents of a backup archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    pub created_at: DateTime<Utc>,
+    pub category_groups: Vec<CategoryGroupRow>,
+    pub categories: Vec<CategoryRow>,
+    pub budgets: Vec<BudgetRow>,
+}
+
+/// First line of an archive
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+    created_at: DateTime<Utc>,
+    counts: TableCounts,
+}
+
+/// One row of an archive
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "table", content = "row", rename_all = "snake_case")]
+enum Record {
+    CategoryGroups(CategoryGroupRow),
+    Categories(CategoryRow),
+    Budgets(BudgetRow),
+}
+
+/// Last line of an archive
+#[derive(Debug, Serialize, Deserialize)]
+struct Trailer {
+    records: u64,
+    checksum: String,
+}
+
+/// What to do with archived rows whose key already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStrategy {
+    /// Abort the restore (nothing is written)
+    #[default]
+    Fail,
+    /// Keep the existing row
+    Skip,
+    /// Replace the existing row with the same id (its `updated_at`
+    /// becomes the restore time, set by the table's trigger)
+    Overwrite,
+    /// Empty the three tables first, leaving exactly the archived rows
+    Replace,
+}
+
+impl fmt::Display for ConflictStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Fail => "fail",
+            Self::Skip => "skip",
+            Self::Overwrite => "overwrite",
+            Self::Replace => "replace",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for ConflictStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "fail" => Ok(Self::Fail),
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            "replace" => Ok(Self::Replace),
+            other => Err(format!(
+                "unknown conflict strategy '{}' (expected fail, skip, overwrite or replace)",
+                other
+            )),
+        }
+    }
+}
+
+impl Backup {
+    /// Rows per table in this backup
+    pub fn counts(&self) -> TableCounts {
+        TableCounts {
+            category_groups: self.category_groups.len() as u64,
+            categories: self.categories.len() as u64,
+            budgets: self.budgets.len() as u64,
+        }
+    }
+
+    /// Write the archive; returns the checksum recorded in its trailer
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<String, BackupError> {
+        let mut writer = HashingWriter {
+            inner: writer,
+            hasher: Sha256::new(),
+        };
+
+        let header = Header {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            created_at: self.created_at,
+            counts: self.counts(),
+        };
+        write_line(&mut writer, &header)?;
+
+        let records = self
+            .category_groups
+            .iter()
+            .cloned()
+            .map(Record::CategoryGroups)
+            .chain(self.categories.iter().cloned().map(Record::Categories))
+            .chain(self.budgets.iter().cloned().map(Record::Budgets));
+        let mut count = 0;
+        for record in records {
+            write_line(&mut writer, &record)?;
+            count += 1;
+        }
+
+        let checksum = format!(
+            "{}{}",
+            CHECKSUM_PREFIX,
+            hex::encode(writer.hasher.finalize_reset())
+        );
+        let trailer = Trailer {
+            records: count,
+            checksum: checksum.clone(),
+        };
+        write_line(&mut writer.inner, &trailer)?;
+        writer.inner.flush()?;
+        Ok(checksum)
+    }
+
+    /// Read and verify an archive written by `write_to`
+    pub fn read_from<R: BufRead>(mut reader: R) -> Result<Self, BackupError> {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 {
+            if !line.trim().is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.clear();
+        }
+
+        let (trailer, body) = lines
+            .split_last()
+            .ok_or_else(|| BackupError::Format("archive is empty".into()))?;
+        let (header, records) = body.split_first().ok_or_else(|| {
+            BackupError::Format("archive has no header".into())
+        })?;
+
+        // Check the header first, so archives from a newer binary get a
+        // clearer error than a checksum or parse failure
+        let header: Header = parse_line(header, "header")?;
+        if header.format != BACKUP_FORMAT {
+            return Err(BackupError::Format(format!(
+                "unknown format '{}'",
+                header.format
+            )));
+        }
+        if header.version > BACKUP_VERSION {
+            return Err(BackupError::UnsupportedVersion(header.version));
+        }
+
+        let trailer: Trailer = serde_json::from_str(trailer).map_err(|_| {
+            BackupError::Format(
+                "archive has no trailer (truncated?)".to_string(),
+            )
+        })?;
+        let mut hasher = Sha256::new();
+        for line in body {
+            hasher.update(line.as_bytes());
+        }
+        let actual =
+            format!("{}{}", CHECKSUM_PREFIX, hex::encode(hasher.finalize()));
+        if actual != trailer.checksum {
+            return Err(BackupError::Checksum {
+                expected: trailer.checksum,
+                actual,
+            });
+        }
+        if trailer.records != records.len() as u64 {
+            return Err(BackupError::Format(format!(
+                "trailer lists {} records, archive has {}",
+                trailer.records,
+                records.len()
+            )));
+        }
+
+        let mut backup = Backup {
+            created_at: header.created_at,
+            category_groups: Vec::new(),
+            categories: Vec::new(),
+            budgets: Vec::new(),
+        };
+        for (index, line) in records.iter().enumerate() {
+            match parse_line(line, &format!("record {}", index + 1))? {
+                Record::CategoryGroups(row) => backup.category_groups.push(row),
+                Record::Categories(row) => backup.categories.push(row),
+                Record::Budgets(row) => backup.budgets.push(row),
+            }
+        }
+
+        if backup.counts() != header.counts {
+            return Err(BackupError::Format(format!(
+                "header lists {}, archive has {}",
+                header.counts,
+                backup.counts()
+            )));
+        }
+        Ok(backup)
+    }
+}
+
+/// Read all three tables in one consistent snapshot
+pub async fn create_backup(pool: &PgPool) -> Result<Backup, BackupError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut tx)
+        .await?;
+
+    let category_groups = sqlx::query_as::<_, CategoryGroupRow>(
+        r#"
+        SELECT id, name, sort_order, color, icon, created_at, updated_at
+        FROM category_groups
+        ORDER BY id
+        "#,
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    let categories = sqlx::query_as::<_, CategoryRow>(
+        r#"
+        SELECT id, name, group_id, type, icon, color, is_default, created_at, updated_at
+        FROM categories
+        ORDER BY id
+        "#,
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    let budgets = sqlx::query_as::<_, BudgetRow>(
+        r#"
+        SELECT id, month, year, category_id, planned, spent, carryover, currency, created_at, updated_at
+        FROM budgets
+        ORDER BY year, month, id
+        "#,
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Backup {
+        created_at: Utc::now(),
+        category_groups,
+        categories,
+        budgets,
+    })
+}
+
+/// Write `backup` into the database in one transaction; returns the rows
+/// written per table (skipped rows are not counted).
+///
+/// With `Overwrite`, a budget whose (year, month, category) is already
+/// budgeted under another id still conflicts and aborts the restore.
+pub async fn restore_backup(
+    pool: &PgPool,
+    backup: &Backup,
+    strategy: ConflictStrategy,
+) -> Result<TableCounts, BackupError> {
+    let mut tx = pool.begin().await?;
+    if strategy == ConflictStrategy::Replace {
+        sqlx::query("TRUNCATE budgets, categories, category_groups")
+            .execute(&mut tx)
+            .await?;
+    }
+
+    let written = TableCounts {
+        category_groups: restore_category_groups(
+            &mut tx,
+            &backup.category_groups,
+            strategy,
+        )
+        .await?,
+        categories: restore_categories(&mut tx, &backup.categories, strategy)
+            .await?,
+        budgets: restore_budgets(&mut tx, &backup.budgets, strategy).await?,
+    };
+
+    tx.commit().await?;
+    tracing::info!("Restored {} ({} strategy)", written, strategy);
+    Ok(written)
+}
+
+async fn restore_category_groups(
+    tx: &mut Transaction<'_, Postgres>,
+    rows: &[CategoryGroupRow],
+    strategy: ConflictStrategy,
+) -> Result<u64, BackupError> {
+    let query = format!(
+        r#"
+        INSERT INTO category_groups (id, name, sort_order, color, icon, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        {}
+        "#,
+        on_conflict(strategy, &["name", "sort_order", "color", "icon"])
+    );
+
+    let mut written = 0;
+    for row in rows {
+        let result = sqlx::query(&query)
+            .bind(row.id)
+            .bind(&row.name)
+            .bind(row.sort_order)
+            .bind(&row.color)
+            .bind(&row.icon)
+            .bind(row.created_at)
+            .bind(row.updated_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| conflict_error("category_groups", e))?;
+        written += result.rows_affected();
+    }
+    Ok(written)
+}
+
+async fn restore_categories(
+    tx: &mut Transaction<'_, Postgres>,
+    rows: &[CategoryRow],
+    strategy: ConflictStrategy,
+) -> Result<u64, BackupError> {
+    let query = format!(
+        r#"
+        INSERT INTO categories (id, name, group_id, type, icon, color, is_default, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        {}
+        "#,
+        on_conflict(
+            strategy,
+            &["name", "group_id", "type", "icon", "color", "is_default"]
+        )
+    );
+
+    let mut written = 0;
+    for row in rows {
+        let result = sqlx::query(&query)
+            .bind(row.id)
+            .bind(&row.name)
+            .bind(row.group_id)
+            .bind(&row.category_type)
+            .bind(&row.icon)
+            .bind(&row.color)
+            .bind(row.is_default)
+            .bind(row.created_at)
+            .bind(row.updated_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| conflict_error("categories", e))?;
+        written += result.rows_affected();
+    }
+    Ok(written)
+}
+
+async fn restore_budgets(
+    tx: &mut Transaction<'_, Postgres>,
+    rows: &[BudgetRow],
+    strategy: ConflictStrategy,
+) -> Result<u64, BackupError> {
+    let query = format!(
+        r#"
+        INSERT INTO budgets (id, month, year, category_id, planned, spent, carryover, currency, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        {}
+        "#,
+        on_conflict(
+            strategy,
+            &[
+                "month",
+                "year",
+                "category_id",
+                "planned",
+                "spent",
+                "carryover",
+                "currency"
+            ]
+        )
+    );
+
+    let mut written = 0;
+    for row in rows {
+        let result = sqlx::query(&query)
+            .bind(row.id)
+            .bind(row.month)
+            .bind(row.year)
+            .bind(row.category_id)
+            .bind(row.planned)
+            .bind(row.spent)
+            .bind(row.carryover)
+            .bind(&row.currency)
+            .bind(row.created_at)
+            .bind(row.updated_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| conflict_error("budgets", e))?;
+        written += result.rows_affected();
+    }
+    Ok(written)
+}
+
+/// `ON CONFLICT` clause for `strategy`; `columns` are updated on overwrite
+fn on_conflict(strategy: ConflictStrategy, columns: &[&str]) -> String {
+    match strategy {
+        ConflictStrategy::Fail | ConflictStrategy::Replace => String::new(),
+        ConflictStrategy::Skip => "ON CONFLICT DO NOTHING".to_string(),
+        ConflictStrategy::Overwrite => {
+            let updates: Vec<String> = columns
+                .iter()
+                .map(|column| format!("{0} = EXCLUDED.{0}", column))
+                .collect();
+            format!("ON CONFLICT (id) DO UPDATE SET {}", updates.join(", "))
+        }
+    }
+}
+
+/// Report unique violations as conflicts, naming the table
+fn conflict_error(table: &'static str, error: sqlx::Error) -> BackupError {
+    if let sqlx::Error::Database(db_err) = &error {
+        if db_err.code().as_deref() == Some("23505") {
+            return BackupError::Conflict {
+                table,
+                message: db_err.message().to_string(),
+            };
+        }
+    }
+    BackupError::Database(error)
+}
+
+fn write_line<W: Write, T: Serialize>(
+    writer: &mut W,
+    value: &T,
+) -> Result<(), BackupError> {
+    serde_json::to_writer(&mut *writer, value)
+        .map_err(|e| BackupError::Format(e.to_string()))?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+fn parse_line<T: serde::de::DeserializeOwned>(
+    line: &str,
+    what: &str,
+) -> Result<T, BackupError> {
+    serde_json::from_str(line)
+        .map_err(|e| BackupError::Format(format!("{}: {}", what, e)))
+}
+
+/// Hashes everything written through it
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -3,15 +3,22 @@
 //! This module handles the initialization of PostgreSQL database connections
 //! with proper error handling and configuration management.
 
+use crate::connections::parse_env_with_default;
 use crate::database::create_pool;
+use crate::database::migrations::ensure_schema;
 use sqlx::PgPool;
 use tracing;
 
 /// Initialize database connection pool
-/// Returns a PostgreSQL connection pool configured from environment variables
+/// Returns a PostgreSQL connection pool configured from environment variables,
+/// once the schema matches this binary: pending migrations are applied unless
+/// `DATABASE_MIGRATE_ON_STARTUP=false`, in which case they fail startup
 pub async fn init_database() -> Result<PgPool, Box<dyn std::error::Error>> {
     tracing::info!("Initializing database connection pool");
     let pool = create_pool().await?;
     tracing::info!("Database connection pool initialized successfully");
+
+    let migrate = parse_env_with_default("DATABASE_MIGRATE_ON_STARTUP", true);
+    ensure_schema(&pool, migrate).await?;
     Ok(pool)
 }
@@ -0,0 +1,207 @@
+//! Embedded schema migrations for MoneyWise backend.
+//!
+//! The SQL files in `database/migrations` are compiled into the binary and
+//! tracked in the `_sqlx_migrations` table, with a checksum per migration.
+//! They are applied at startup (unless `DATABASE_MIGRATE_ON_STARTUP=false`)
+//! or with `moneywise-backend migrate`. Startup is refused when:
+//! - an applied migration was edited afterwards (checksum mismatch)
+//! - the database has migrations this binary does not know (it is ahead,
+//!   e.g. after a rollback to an older release)
+//! - migrations are pending and startup migrations are disabled
+//!
+//! A few migrations were edited after release; their earlier checksums are
+//! listed in `LEGACY_CHECKSUMS`, accepted as a match and rewritten to the
+//! current checksum when migrations run.
+
+use sqlx::migrate::{AppliedMigration, Migrate, MigrateError, Migrator};
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// Migrations embedded from `database/migrations`
+pub static MIGRATOR: Migrator = sqlx::migrate!("./database/migrations");
+
+/// Checksums (SHA-384, hex) of released versions of migrations edited
+/// since.
+///
+/// `20250827000000` used to insert the sample data, which is now only
+/// written by `moneywise-admin seed`; the rows already inserted are kept.
+pub const LEGACY_CHECKSUMS: &[(i64, &str)] = &[(
+    20250827000000,
+    "8377770f439b0a136ce8946027f244789fe15fcee24293cfc4042a8ae00f1b97bd2e1e362cd05233daf7fd64b2214c6f",
+)];
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Whether `checksum` is a released earlier version of migration `version`
+fn is_legacy_checksum(version: i64, checksum: &[u8]) -> bool {
+    let checksum = to_hex(checksum);
+    LEGACY_CHECKSUMS
+        .iter()
+        .any(|(v, legacy)| *v == version && *legacy == checksum)
+}
+
+/// Embedded migrations compared with a database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    /// Versions already applied, in order
+    pub applied: Vec<i64>,
+    /// Versions still to apply, in order
+    pub pending: Vec<i64>,
+}
+
+/// Compare `applied` migrations with `migrator`.
+///
+/// Fails with `VersionMissing` when a migration was applied that
+/// `migrator` does not contain, and `VersionMismatch` when an applied
+/// migration's checksum differs from the embedded one (and is not one of
+/// its `LEGACY_CHECKSUMS`).
+pub fn check_applied(
+    migrator: &Migrator,
+    applied: &[AppliedMigration],
+) -> Result<MigrationStatus, MigrateError> {
+    let embedded: HashMap<i64, &[u8]> = migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| (m.version, m.checksum.as_ref()))
+        .collect();
+
+    for migration in applied {
+        match embedded.get(&migration.version) {
+            None => {
+                return Err(MigrateError::VersionMissing(migration.version))
+            }
+            Some(checksum)
+                if *checksum != migration.checksum.as_ref()
+                    && !is_legacy_checksum(
+                        migration.version,
+                        &migration.checksum,
+                    ) =>
+            {
+                return Err(MigrateError::VersionMismatch(migration.version))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let mut applied: Vec<i64> = applied.iter().map(|m| m.version).collect();
+    applied.sort_unstable();
+    let mut pending: Vec<i64> = embedded
+        .keys()
+        .filter(|version| applied.binary_search(version).is_err())
+        .copied()
+        .collect();
+    pending.sort_unstable();
+
+    Ok(MigrationStatus { applied, pending })
+}
+
+/// Read which embedded migrations the database has applied, without
+/// changing anything
+pub async fn migration_status(
+    pool: &PgPool,
+) -> Result<MigrationStatus, MigrateError> {
+    let tracked: Option<String> =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations')::text")
+            .fetch_one(pool)
+            .await?;
+    if tracked.is_none() {
+        return check_applied(&MIGRATOR, &[]);
+    }
+
+    let mut conn = pool.acquire().await?;
+    if let Some(version) = conn.dirty_version().await? {
+        return Err(MigrateError::Dirty(version));
+    }
+    let applied = conn.list_applied_migrations().await?;
+    check_applied(&MIGRATOR, &applied)
+}
+
+/// Apply pending migrations; returns the status before applying them.
+///
+/// Concurrent instances are serialized by an advisory lock, so only one of
+/// them applies each migration.
+pub async fn run_migrations(
+    pool: &PgPool,
+) -> Result<MigrationStatus, MigrateError> {
+    let status = migration_status(pool).await?;
+    upgrade_legacy_checksums(pool).await?;
+    MIGRATOR.run(pool).await?;
+
+    for version in &status.pending {
+        tracing::info!("Applied database migration {}", version);
+    }
+    Ok(status)
+}
+
+/// Record the current checksum of migrations applied in a legacy version,
+/// so `Migrator::run` does not reject them
+async fn upgrade_legacy_checksums(pool: &PgPool) -> Result<(), MigrateError> {
+    let tracked: Option<String> =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations')::text")
+            .fetch_one(pool)
+            .await?;
+    if tracked.is_none() {
+        return Ok(());
+    }
+
+    for migration in MIGRATOR.iter() {
+        for (version, legacy) in LEGACY_CHECKSUMS {
+            if *version != migration.version {
+                continue;
+            }
+            let upgraded = sqlx::query(
+                "UPDATE _sqlx_migrations SET checksum = $1 \
+                 WHERE version = $2 AND encode(checksum, 'hex') = $3",
+            )
+            .bind(migration.checksum.as_ref())
+            .bind(migration.version)
+            .bind(*legacy)
+            .execute(pool)
+            .await?;
+            if upgraded.rows_affected() > 0 {
+                tracing::info!(
+                    "Updated checksum of edited migration {}",
+                    version
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bring the schema up to date at startup, or only verify it when
+/// `migrate` is false; fails if the schema does not match this binary
+pub async fn ensure_schema(
+    pool: &PgPool,
+    migrate: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = if migrate {
+        run_migrations(pool).await
+    } else {
+        migration_status(pool).await
+    };
+
+    let status = result.map_err(|e| match e {
+        MigrateError::VersionMissing(version) => format!(
+            "Database schema is ahead of this binary (migration {} is unknown); refusing to start",
+            version
+        ),
+        other => format!("Database migration check failed: {}", other),
+    })?;
+
+    if !migrate && !status.pending.is_empty() {
+        return Err(format!(
+            "Database schema is behind this binary (pending migrations {:?}); run `moneywise-backend migrate`",
+            status.pending
+        )
+        .into());
+    }
+
+    tracing::info!(
+        "Database schema up to date ({} migrations)",
+        status.applied.len() + status.pending.len()
+    );
+    Ok(())
+}
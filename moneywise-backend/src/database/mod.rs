@@ -4,7 +4,6 @@
 
 use sqlx::postgres::{PgPool, PgPoolOptions};
 use std::time::Duration;
-use tracing;
 
 /// Create a connection pool to the PostgreSQL database
 /// This function initializes a connection pool with configurable settings
@@ -37,5 +36,9 @@ pub async fn create_pool() -> Result<PgPool, sqlx::Error> {
         .await
 }
 
+// Logical backup and restore
+pub mod backup;
 // Connection management submodule
 pub mod connection;
+// Embedded schema migrations
+pub mod migrations;
@@ -2,6 +2,8 @@
 //!
 //! Provides centralized error types and HTTP response conversion.
 
+use crate::rate_limiter::types::RateLimitError;
+use crate::server::request_id::insert_request_id;
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
@@ -29,6 +31,15 @@ pub enum AppError {
     Cache(#[from] RedisError),
 }
 
+impl From<RateLimitError> for AppError {
+    fn from(error: RateLimitError) -> Self {
+        match error {
+            RateLimitError::RedisError(e) => AppError::Cache(e),
+            RateLimitError::DatabaseError(e) => AppError::Database(e),
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
@@ -54,12 +65,13 @@ impl IntoResponse for AppError {
             }
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "error": error_message,
             "status": status.as_u16()
-        }));
+        });
+        insert_request_id(&mut body);
 
-        (status, body).into_response()
+        (status, Json(body)).into_response()
     }
 }
 
@@ -4,12 +4,17 @@
 //! by re-exporting the main modules and types.
 
 // Re-export main modules
+pub mod api;
 pub mod cache;
 pub mod connections;
 pub mod database;
 pub mod error;
+pub mod maintenance;
+pub mod metrics;
 pub mod models;
 pub mod rate_limiter;
+pub mod redis_topology;
+pub mod repository;
 pub mod server;
 
 // Re-export main types for convenience
@@ -3,51 +3,84 @@
 //! This module initializes the HTTP server with all necessary middleware,
 //! database connections, caching, and rate limiting.
 
-use axum::{middleware, Router};
+use axum::{middleware, Extension};
 use tower_http::cors::{Any, CorsLayer};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-mod api;
-mod cache;
-mod connections;
-mod database;
-mod error;
-mod models;
-mod rate_limiter;
-mod server;
-
-use api::create_api_router;
-use connections::init_connections;
-use rate_limiter::middleware::rate_limit_middleware;
+use moneywise_backend::api::{
+    create_admin_router, create_api_router, create_health_router,
+    create_metrics_router, health::ReadinessProbe,
+    warmup::spawn_budget_cache_warmup,
+};
+use moneywise_backend::connections::init_connections;
+use moneywise_backend::database::{create_pool, migrations::run_migrations};
+use moneywise_backend::metrics::{
+    http::track_http_metrics, pool::register_pool,
+};
+use moneywise_backend::rate_limiter::{
+    middleware::rate_limit_middleware, ApiKeyStore, ClassifiedRouter,
+};
+use moneywise_backend::repository::{
+    PgBudgetRepository, SharedBudgetRepository,
+};
+use moneywise_backend::server::{
+    request_id::request_id_middleware,
+    telemetry::{init_tracing, TelemetryConfig},
+};
 use std::sync::Arc;
 
 /// Main entry point for the MoneyWise backend server
 /// This function initializes the application, sets up logging, database connection,
 /// CORS configuration, and starts the HTTP server
 ///
+/// Run as `moneywise-backend migrate` to only apply database migrations.
 #[tokio::main]
 async fn main() {
-    // Initialize tracing for structured logging
-    // This sets up logging with environment-based configuration
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            // Use RUST_LOG environment variable or default to "info" level
-            // Exclude sqlx query logs to reduce noise in production
-            std::env::var("RUST_LOG")
-                .unwrap_or_else(|_| "info,sqlx::query=warn".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
     // Load environment variables from .env file
     // This allows configuration through environment variables
     dotenv::dotenv().ok();
 
+    // Initialize tracing for structured logging
+    // RUST_LOG filters, LOG_FORMAT picks text or JSON lines, and
+    // OTEL_EXPORTER_OTLP_ENDPOINT enables span export
+    let _telemetry = init_tracing(&TelemetryConfig::default())
+        .expect("Failed to initialize tracing");
+
+    // `moneywise-backend migrate` applies pending migrations and exits
+    if std::env::args().nth(1).as_deref() == Some("migrate") {
+        let pool = create_pool()
+            .await
+            .expect("Failed to connect to the database");
+        let status = run_migrations(&pool)
+            .await
+            .expect("Failed to apply database migrations");
+        tracing::info!(
+            "Applied {} migrations ({} already applied)",
+            status.pending.len(),
+            status.applied.len()
+        );
+        return;
+    }
+
     // Initialize database, Redis connections, rate limiter, and server configuration
     let (pool, cache_service, rate_limiter, server_config) = init_connections()
         .await
         .expect("Failed to initialize connections and configuration");
 
+    // Handlers reach the database through the budget repository
+    let repo: SharedBudgetRepository =
+        Arc::new(PgBudgetRepository::new(pool.clone()));
+
+    // Precompute the current and previous month so the first dashboard
+    // loads after a deploy are served from cache
+    let cache_config = cache_service.service().config();
+    if cache_config.warmup_enabled {
+        spawn_budget_cache_warmup(
+            repo.clone(),
+            cache_service.clone(),
+            cache_config.warmup_interval,
+        );
+    }
+
     // Configure CORS (Cross-Origin Resource Sharing) settings
     // This allows the API to be accessed from different origins (domains)
     let cors = CorsLayer::new()
@@ -55,15 +88,37 @@ async fn main() {
         .allow_methods(Any) // Allow all HTTP methods (GET, POST, PUT, DELETE, etc.)
         .allow_headers(Any); // Allow all headers
 
-    // Build the application router with routes and middleware
-    let app = Router::new()
+    // Build the application routes; each route declares its rate-limit class
+    let (routes, route_table) = ClassifiedRouter::new()
         .nest("/api", create_api_router()) // Mount all API routes under /api path
+        .nest("/admin", create_admin_router()) // Operator-only routes, guarded by ADMIN_API_TOKEN
+        .nest("/health", create_health_router()) // Liveness and readiness probes
+        .merge(create_metrics_router()) // Prometheus scrape endpoint at /metrics
+        .into_parts();
+
+    // Startup check: report routes that never declared a rate-limit class
+    route_table.report();
+    let rate_limiter = Arc::new(rate_limiter.with_route_table(route_table));
+    register_pool(pool.clone());
+    let readiness = Arc::new(ReadinessProbe::new(
+        pool.clone(),
+        cache_service.clone(),
+        rate_limiter.clone(),
+    ));
+
+    // Attach middleware to the routes
+    let app = routes
+        .layer(Extension(rate_limiter.clone())) // Admin rate limit inspection
+        .layer(Extension(ApiKeyStore::new(pool))) // Admin API key issuance
+        .layer(Extension(readiness)) // Dependency checks for /health/ready
         .layer(middleware::from_fn_with_state(
-            Arc::new(rate_limiter),
+            rate_limiter,
             rate_limit_middleware,
         )) // Apply rate limiting middleware
+        .layer(middleware::from_fn(track_http_metrics)) // Count and time requests, including rate-limited ones
+        .layer(middleware::from_fn(request_id_middleware)) // Assign x-request-id and the request span
         .layer(cors) // Apply CORS middleware
-        .with_state((pool, cache_service)); // Inject database pool and cache service as application state
+        .with_state((repo, cache_service)); // Inject budget repository and cache service as application state
 
     // Log the server address for debugging and monitoring
     tracing::info!("listening on {}", server_config.addr);
@@ -0,0 +1,246 @@
+//! Operational tasks for MoneyWise backend.
+//!
+//! Database and configuration helpers behind the `moneywise-admin` binary
+//! (seeding sample data, copying a month's budgets, validating the
+//! environment). They live in the library so the CLI shares the server's
+//! connection, database and cache code.
+
+use sqlx::{Executor, PgPool};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::{
+    cache::{
+        core::{
+            backend::CacheBackendKind,
+            codec::{CacheCompression, CacheFormat},
+        },
+        CacheConfig,
+    },
+    error::{AppError, Result},
+    rate_limiter::{
+        backend::RateLimitBackendKind, types::DegradationPolicy,
+        RateLimitConfig,
+    },
+    redis_topology::RedisMode,
+    server::telemetry::LogFormat,
+};
+
+/// Sample category groups, categories and budgets (December 2024 and
+/// August 2025); every insert is `ON CONFLICT DO NOTHING`
+pub const SAMPLE_DATA: &str =
+    include_str!("../database/schema/sample_data.sql");
+
+/// A budget month, written `YYYY-MM` (e.g. `2025-08`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetPeriod {
+    pub month: i16,
+    pub year: i32,
+}
+
+impl fmt::Display for BudgetPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+impl FromStr for BudgetPeriod {
+    type Err = String;
+
+    /// Parse `YYYY-MM`, with the same bounds as the budgets table
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("invalid month '{}': expected YYYY-MM", s);
+        let (year, month) = s.trim().split_once('-').ok_or_else(invalid)?;
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let month: i16 = month.parse().map_err(|_| invalid())?;
+
+        if !(1..=12).contains(&month) {
+            return Err(format!("invalid month '{}': month must be 1-12", s));
+        }
+        if year < 2000 {
+            return Err(format!(
+                "invalid month '{}': year must be 2000 or later",
+                s
+            ));
+        }
+        Ok(Self { month, year })
+    }
+}
+
+/// Insert the sample data; rows that already exist are left untouched.
+/// Returns the number of rows inserted.
+pub async fn seed_sample_data(pool: &PgPool) -> Result<u64> {
+    let result = pool.execute(SAMPLE_DATA).await?;
+    Ok(result.rows_affected())
+}
+
+/// Copy the planned budgets of `from` into `to`; returns the ids of the
+/// budgets written, so their cached copies can be invalidated.
+///
+/// Copies start a fresh month: `spent` and `carryover` are zero and each
+/// budget gets a new id. Categories already budgeted in `to` are skipped,
+/// unless `overwrite` is set, in which case their planned amount and
+/// currency are replaced (what was spent is kept).
+pub async fn copy_month_budgets(
+    pool: &PgPool,
+    from: BudgetPeriod,
+    to: BudgetPeriod,
+    overwrite: bool,
+) -> Result<Vec<Uuid>> {
+    if from == to {
+        return Err(AppError::Validation(
+            "source and target months are the same".to_string(),
+        ));
+    }
+
+    let on_conflict = if overwrite {
+        "DO UPDATE SET planned = EXCLUDED.planned, currency = EXCLUDED.currency"
+    } else {
+        "DO NOTHING"
+    };
+    let query = format!(
+        r#"
+        INSERT INTO budgets (id, month, year, category_id, planned, currency)
+        SELECT uuid_generate_v4(), $3, $4, category_id, planned, currency
+        FROM budgets
+        WHERE month = $1 AND year = $2
+        ON CONFLICT (year, month, category_id) {}
+        RETURNING id
+        "#,
+        on_conflict
+    );
+
+    let ids = sqlx::query_scalar(&query)
+        .bind(from.month)
+        .bind(from.year)
+        .bind(to.month)
+        .bind(to.year)
+        .fetch_all(pool)
+        .await?;
+    Ok(ids)
+}
+
+/// Check every environment variable the server reads; returns one message
+/// per problem (empty when the configuration is valid).
+///
+/// Unlike the server, which logs a warning and falls back to the default,
+/// this reports values that do not parse, and Redis URLs that the selected
+/// topology rejects.
+pub fn validate_environment() -> Vec<String> {
+    let mut problems = Vec::new();
+
+    match std::env::var("DATABASE_URL") {
+        Ok(url) => {
+            if let Err(e) = url.parse::<sqlx::postgres::PgConnectOptions>() {
+                problems.push(format!("DATABASE_URL is invalid: {}", e));
+            }
+        }
+        Err(_) => problems.push("DATABASE_URL is not set".to_string()),
+    }
+
+    if let Ok(url) = std::env::var("REDIS_URL") {
+        if !(url.starts_with("redis://") || url.starts_with("rediss://")) {
+            problems
+                .push("REDIS_URL must start with redis:// or rediss://".into());
+        }
+    }
+
+    check_var::<u32>("DATABASE_MAX_CONNECTIONS", &mut problems);
+    check_var::<bool>("DATABASE_MIGRATE_ON_STARTUP", &mut problems);
+    check_var::<u16>("PORT", &mut problems);
+    if std::env::var("PORT").as_deref() == Ok("0") {
+        problems.push("PORT cannot be 0".to_string());
+    }
+    if let Ok(host) = std::env::var("HOST") {
+        if format!("{}:0", host)
+            .parse::<std::net::SocketAddr>()
+            .is_err()
+        {
+            problems.push(format!("HOST '{}' is not an IP address", host));
+        }
+    }
+
+    check_var::<CacheBackendKind>("CACHE_BACKEND", &mut problems);
+    for var in [
+        "CACHE_MEMORY_MAX_ENTRIES",
+        "CACHE_L1_MAX_ENTRIES",
+        "CACHE_COMPRESSION_THRESHOLD_BYTES",
+    ] {
+        check_var::<usize>(var, &mut problems);
+    }
+    for var in [
+        "CACHE_OVERVIEW_TTL_SECS",
+        "CACHE_CATEGORIES_TTL_SECS",
+        "CACHE_BUDGET_TTL_SECS",
+        "REDIS_CONNECTION_TIMEOUT_SECS",
+        "CACHE_CIRCUIT_PROBE_INTERVAL_SECS",
+        "CACHE_FILL_LOCK_TTL_MS",
+        "CACHE_L1_TTL_SECS",
+        "CACHE_REFRESH_AHEAD_SECS",
+        "CACHE_WARMUP_INTERVAL_SECS",
+        "RATE_LIMIT_CLEANUP_INTERVAL_SECS",
+    ] {
+        check_var::<u64>(var, &mut problems);
+    }
+    for var in [
+        "REDIS_MAX_CONNECTIONS",
+        "REDIS_RETRY_ATTEMPTS",
+        "CACHE_CIRCUIT_FAILURE_THRESHOLD",
+    ] {
+        check_var::<u32>(var, &mut problems);
+    }
+    for var in [
+        "CACHE_FILL_LOCK",
+        "CACHE_L1_ENABLED",
+        "CACHE_WARMUP_ENABLED",
+    ] {
+        check_var::<bool>(var, &mut problems);
+    }
+    check_var::<CacheFormat>("CACHE_CODEC", &mut problems);
+    check_var::<CacheCompression>("CACHE_COMPRESSION", &mut problems);
+
+    check_var::<RateLimitBackendKind>("RATE_LIMIT_BACKEND", &mut problems);
+    if std::env::var("RATE_LIMIT_CLEANUP_INTERVAL_SECS").as_deref() == Ok("0") {
+        problems
+            .push("RATE_LIMIT_CLEANUP_INTERVAL_SECS cannot be 0".to_string());
+    }
+    check_var::<usize>("RATE_LIMIT_MEMORY_SHARDS", &mut problems);
+    for var in [
+        "RATE_LIMIT_DEGRADATION_MODIFICATION",
+        "RATE_LIMIT_DEGRADATION_READ",
+        "RATE_LIMIT_DEGRADATION_OVERVIEW",
+    ] {
+        check_var::<DegradationPolicy>(var, &mut problems);
+    }
+
+    check_var::<RedisMode>("REDIS_MODE", &mut problems);
+    check_var::<LogFormat>("LOG_FORMAT", &mut problems);
+    let cache = CacheConfig::default();
+    let uses_redis = cache.backend == CacheBackendKind::Redis
+        || RateLimitConfig::default().backend == RateLimitBackendKind::Redis;
+    if uses_redis {
+        if let Err(e) = cache.redis_topology.validate(&cache.redis_url) {
+            problems.push(format!(
+                "Redis {} configuration is invalid: {}",
+                cache.redis_topology.mode(),
+                e
+            ));
+        }
+    }
+
+    problems
+}
+
+/// Record a problem if `var` is set but does not parse as `T`
+fn check_var<T>(var: &str, problems: &mut Vec<String>)
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    if let Ok(value) = std::env::var(var) {
+        if let Err(e) = value.parse::<T>() {
+            problems.push(format!("{}='{}' is invalid: {}", var, value, e));
+        }
+    }
+}
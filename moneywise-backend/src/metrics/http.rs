@@ -0,0 +1,95 @@
+//! HTTP request counters and latency histograms.
+//!
+//! Exposed as:
+//! - `moneywise_http_requests_total{method, route, status}`
+//! - `moneywise_http_request_duration_seconds{method, route, status}`
+//!
+//! `route` is the matched route template (`/api/budgets/:id`), never the raw
+//! path, so ids do not create new series; requests matching no route are
+//! counted as `unmatched`.
+
+use axum::{
+    body::Body,
+    extract::MatchedPath,
+    http::{Method, Request},
+    middleware::Next,
+    response::Response,
+};
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use crate::metrics::{register, METRICS_NAMESPACE};
+
+/// Route label of requests that matched no route
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+fn requests() -> &'static IntCounterVec {
+    static REQUESTS: OnceLock<IntCounterVec> = OnceLock::new();
+    REQUESTS.get_or_init(|| {
+        let counter = IntCounterVec::new(
+            Opts::new(
+                "http_requests_total",
+                "HTTP requests by method, route and status",
+            )
+            .namespace(METRICS_NAMESPACE),
+            &["method", "route", "status"],
+        )
+        .expect("valid HTTP request metric definition");
+        register(counter)
+    })
+}
+
+fn durations() -> &'static HistogramVec {
+    static DURATIONS: OnceLock<HistogramVec> = OnceLock::new();
+    DURATIONS.get_or_init(|| {
+        let histogram = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency by method, route and status",
+            )
+            .namespace(METRICS_NAMESPACE)
+            .buckets(vec![
+                0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0,
+                2.5, 5.0,
+            ]),
+            &["method", "route", "status"],
+        )
+        .expect("valid HTTP latency metric definition");
+        register(histogram)
+    })
+}
+
+/// Count and time every request.
+///
+/// Install as the outermost route layer so rate-limited and rejected
+/// requests are recorded with their final status.
+pub async fn track_http_metrics(
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let started = Instant::now();
+    let method = req.method().clone();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+
+    let res = next.run(req).await;
+
+    let route = route.as_deref().unwrap_or(UNMATCHED_ROUTE);
+    let status = res.status().as_u16().to_string();
+    let labels = [method.as_str(), route, status.as_str()];
+    requests().with_label_values(&labels).inc();
+    durations()
+        .with_label_values(&labels)
+        .observe(started.elapsed().as_secs_f64());
+    res
+}
+
+/// Requests counted so far for a method, route and status
+pub fn request_count(method: &Method, route: &str, status: u16) -> u64 {
+    requests()
+        .with_label_values(&[method.as_str(), route, &status.to_string()])
+        .get()
+}
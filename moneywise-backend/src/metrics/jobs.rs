@@ -0,0 +1,92 @@
+//! Background job outcome counters and durations.
+//!
+//! Exposed as:
+//! - `moneywise_background_jobs_total{job, outcome}` where `outcome` is
+//!   `success`, `failure` or `skipped` (another instance or run did the work)
+//! - `moneywise_background_job_duration_seconds{job}`
+
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts};
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::metrics::{register, METRICS_NAMESPACE};
+
+/// Background job label values
+pub mod job {
+    /// Periodic budget cache warm-up (`api::warmup`)
+    pub const CACHE_WARMUP: &str = "cache_warmup";
+    /// Refresh of a cache entry ahead of its expiry
+    pub const CACHE_REFRESH: &str = "cache_refresh";
+    /// Redis reachability probe while the cache circuit is open
+    pub const CACHE_CIRCUIT_PROBE: &str = "cache_circuit_probe";
+    /// Purge of expired in-memory rate limit counters
+    pub const RATE_LIMIT_CLEANUP: &str = "rate_limit_cleanup";
+}
+
+/// Outcome of one background job run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobOutcome {
+    Success,
+    Failure,
+    /// Nothing to do, e.g. another instance holds the refresh lock
+    Skipped,
+}
+
+impl fmt::Display for JobOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Success => write!(f, "success"),
+            Self::Failure => write!(f, "failure"),
+            Self::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+fn runs() -> &'static IntCounterVec {
+    static RUNS: OnceLock<IntCounterVec> = OnceLock::new();
+    RUNS.get_or_init(|| {
+        let counter = IntCounterVec::new(
+            Opts::new(
+                "background_jobs_total",
+                "Background job runs by job and outcome",
+            )
+            .namespace(METRICS_NAMESPACE),
+            &["job", "outcome"],
+        )
+        .expect("valid background job metric definition");
+        register(counter)
+    })
+}
+
+fn durations() -> &'static HistogramVec {
+    static DURATIONS: OnceLock<HistogramVec> = OnceLock::new();
+    DURATIONS.get_or_init(|| {
+        let histogram = HistogramVec::new(
+            HistogramOpts::new(
+                "background_job_duration_seconds",
+                "Background job run time by job",
+            )
+            .namespace(METRICS_NAMESPACE)
+            .buckets(vec![
+                0.001, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0,
+            ]),
+            &["job"],
+        )
+        .expect("valid background job duration metric definition");
+        register(histogram)
+    })
+}
+
+/// Count one run of `job` that took `elapsed`
+pub fn record_job(job: &str, outcome: JobOutcome, elapsed: Duration) {
+    runs().with_label_values(&[job, &outcome.to_string()]).inc();
+    durations()
+        .with_label_values(&[job])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Runs of `job` with `outcome` so far
+pub fn job_count(job: &str, outcome: JobOutcome) -> u64 {
+    runs().with_label_values(&[job, &outcome.to_string()]).get()
+}
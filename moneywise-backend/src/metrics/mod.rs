@@ -0,0 +1,44 @@
+//! Metrics registry for MoneyWise backend.
+//!
+//! All components register their Prometheus collectors in one process-wide
+//! registry so they can be rendered together in the text exposition format.
+//! Series owned by a single component (cache, rate limiter) live next to it;
+//! the submodules here cover HTTP requests, the connection pool and
+//! background jobs.
+
+use prometheus::{Encoder, Registry, TextEncoder};
+use std::sync::OnceLock;
+
+pub mod http;
+pub mod jobs;
+pub mod pool;
+
+/// Prefix shared by all MoneyWise metric names
+pub const METRICS_NAMESPACE: &str = "moneywise";
+
+/// Process-wide metrics registry
+pub fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(Registry::new)
+}
+
+/// Render every registered metric in the Prometheus text format
+pub fn render() -> String {
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&registry().gather(), &mut buffer)
+    {
+        tracing::error!("Failed to encode metrics: {}", e);
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+/// Register a collector, logging instead of failing on duplicates
+pub fn register<C>(collector: C) -> C
+where
+    C: prometheus::core::Collector + Clone + 'static,
+{
+    if let Err(e) = registry().register(Box::new(collector.clone())) {
+        tracing::warn!("Failed to register metric: {}", e);
+    }
+    collector
+}
@@ -0,0 +1,65 @@
+//! PostgreSQL connection pool gauges.
+//!
+//! Exposed as `moneywise_db_pool_connections{state}` where `state` is `idle`
+//! or `in_use`; the pool size limit is `DATABASE_MAX_CONNECTIONS`.
+//!
+//! Read from the pool when metrics are gathered, so they are current at
+//! every scrape without a background task.
+
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    IntGaugeVec, Opts,
+};
+use sqlx::PgPool;
+
+use crate::metrics::{register, METRICS_NAMESPACE};
+
+/// Collector reporting the usage of one pool
+#[derive(Clone)]
+pub struct PoolCollector {
+    pool: PgPool,
+    connections: IntGaugeVec,
+}
+
+impl PoolCollector {
+    pub fn new(pool: PgPool) -> Self {
+        let connections = IntGaugeVec::new(
+            Opts::new(
+                "db_pool_connections",
+                "Open database connections by state",
+            )
+            .namespace(METRICS_NAMESPACE),
+            &["state"],
+        )
+        .expect("valid pool connection metric definition");
+
+        Self { pool, connections }
+    }
+
+    /// Copy the pool's current usage into the gauges
+    fn update(&self) {
+        let open = self.pool.size() as i64;
+        let idle = self.pool.num_idle() as i64;
+        self.connections.with_label_values(&["idle"]).set(idle);
+        self.connections
+            .with_label_values(&["in_use"])
+            .set((open - idle).max(0));
+    }
+}
+
+impl Collector for PoolCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.connections.desc()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        self.update();
+        self.connections.collect()
+    }
+}
+
+/// Report `pool` in the process-wide registry; call once per process
+pub fn register_pool(pool: PgPool) {
+    register(PoolCollector::new(pool));
+}
@@ -28,7 +28,7 @@ use uuid::Uuid;
 ///
 /// - Matches PostgreSQL schema types
 /// - Not exposed directly to API; use `BudgetApi`
-#[derive(Debug, FromRow)]
+#[derive(Debug, Clone, FromRow)]
 pub struct Budget {
     pub id: Uuid,
     pub month: i16, // smallint in PostgreSQL
@@ -42,6 +42,22 @@ pub struct Budget {
     pub updated_at: DateTime<Utc>, // timestamptz with default now()
 }
 
+/// A budget joined with its category and optional group.
+///
+/// - Input of `CategoryBudgetApi`, which adds remaining and percentage
+#[derive(Debug, Clone, FromRow)]
+pub struct CategoryBudgetRow {
+    pub id: Uuid,
+    pub category_name: String,
+    pub group_name: Option<String>, // NULL when the category has no group
+    pub category_color: String,
+    pub group_color: Option<String>,
+    pub planned: Decimal,
+    pub spent: Decimal,
+    pub carryover: Decimal,
+    pub currency: String, // Trimmed from character(3)
+}
+
 /// Budget overview with aggregated insights.
 ///
 /// Contains high-level summaries and category breakdowns.
@@ -85,6 +101,8 @@ pub struct BudgetInsight {
 //////////////////////////////////////////////////////////////////////
 // Separation of Database and External models
 // Trade-offs: More code but better maintainability and interface stability.
+// Decimals are (de)serialized as strings explicitly so the same models also
+// round-trip through binary cache codecs (MessagePack, bincode).
 //////////////////////////////////////////////////////////////////////
 
 /// External budget representation.
@@ -96,8 +114,11 @@ pub struct BudgetApi {
     pub month: i16, // smallint
     pub year: i32,
     pub category_id: String, // String for JSON compatibility
+    #[serde(with = "rust_decimal::serde::str")]
     pub planned: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
     pub spent: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
     pub carryover: Decimal,
     pub currency: String,
     pub created_at: DateTime<Utc>,
@@ -109,8 +130,11 @@ pub struct BudgetApi {
 /// Remaining = planned - spent + carryover
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BudgetOverviewApi {
+    #[serde(with = "rust_decimal::serde::str")]
     pub planned: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
     pub spent: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
     pub remaining: Decimal,
     pub currency: String,
 }
@@ -125,9 +149,58 @@ pub struct CategoryBudgetApi {
     pub group_name: Option<String>, // Nullable group
     pub category_color: String,
     pub group_color: Option<String>, // Nullable group color
+    #[serde(with = "rust_decimal::serde::str")]
     pub planned: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
     pub spent: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
     pub remaining: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
     pub percentage: Decimal,
     pub currency: String,
 }
+
+//////////////////////////////////////////////////////////////////////
+// Conversions from database to external models
+//////////////////////////////////////////////////////////////////////
+
+impl From<Budget> for BudgetApi {
+    fn from(budget: Budget) -> Self {
+        Self {
+            id: budget.id.to_string(),
+            month: budget.month,
+            year: budget.year,
+            category_id: budget.category_id.to_string(),
+            planned: budget.planned,
+            spent: budget.spent,
+            carryover: budget.carryover,
+            currency: budget.currency,
+            created_at: budget.created_at,
+            updated_at: budget.updated_at,
+        }
+    }
+}
+
+impl From<CategoryBudgetRow> for CategoryBudgetApi {
+    fn from(row: CategoryBudgetRow) -> Self {
+        // Percentage of budget used; safe when planned is zero
+        let percentage = if row.planned > Decimal::from(0) {
+            ((row.spent / row.planned) * Decimal::from(100)).round_dp(2)
+        } else {
+            Decimal::from(0)
+        };
+
+        Self {
+            id: row.id.to_string(),
+            category_name: row.category_name,
+            group_name: row.group_name,
+            category_color: row.category_color,
+            group_color: row.group_color,
+            planned: row.planned,
+            spent: row.spent,
+            remaining: row.planned - row.spent + row.carryover,
+            percentage,
+            currency: row.currency,
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! API key issuance and lookup for per-key rate limiting.
+//!
+//! Keys are random tokens handed out once by the admin API. Only their
+//! SHA-256 hash is persisted in the `api_keys` table, so a database leak
+//! does not expose usable keys.
+//!
+//! Lookups are remembered per store for `LOOKUP_CACHE_TTL`, unknown keys
+//! included, so repeated requests do not each query the database. A key
+//! revoked in the database therefore keeps working for up to that long.
+//! Lookups that do reach the database are limited per client IP
+//! (`LOOKUP_LIMIT_PER_IP`), so a flood of made-up keys is shed before it.
+
+use crate::rate_limiter::plans::PlanTier;
+use crate::rate_limiter::types::{ApiKeyIdentity, RateLimitError};
+use chrono::{DateTime, Utc};
+use lru::LruCache;
+use rand::RngCore;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Header carrying the API key on incoming requests
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Prefix of issued keys; makes them easy to spot in logs and secret scanners
+const API_KEY_PREFIX: &str = "mw_";
+
+/// Number of random bytes in an issued key
+const API_KEY_BYTES: usize = 32;
+
+/// How long a lookup result, found or not, is reused
+pub const LOOKUP_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Uncached key lookups allowed per client IP and window
+pub const LOOKUP_LIMIT_PER_IP: u32 = 30;
+
+/// Window of `LOOKUP_LIMIT_PER_IP`, in seconds
+pub const LOOKUP_WINDOW_SECONDS: u64 = 60;
+
+/// Lookup results remembered per store (least recently used evicted)
+const LOOKUP_CACHE_CAPACITY: usize = 10_000;
+
+/// Result of resolving one key hash
+#[derive(Clone, Copy)]
+struct CachedLookup {
+    identity: Option<ApiKeyIdentity>,
+    expires_at: Instant,
+}
+
+/// Newly issued API key. `key` is the only time the plaintext is available.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedApiKey {
+    pub id: String, // String for JSON compatibility
+    pub name: String,
+    pub plan: PlanTier,
+    pub key: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Postgres-backed store for hashed API keys
+#[derive(Clone)]
+pub struct ApiKeyStore {
+    pool: PgPool,
+    /// Recent lookups by key hash, shared by clones
+    lookups: Arc<Mutex<LruCache<String, CachedLookup>>>,
+    lookup_ttl: Duration,
+}
+
+impl ApiKeyStore {
+    /// Create a store on top of an existing connection pool
+    pub fn new(pool: PgPool) -> Self {
+        let capacity = NonZeroUsize::new(LOOKUP_CACHE_CAPACITY)
+            .expect("lookup cache capacity is non-zero");
+        Self {
+            pool,
+            lookups: Arc::new(Mutex::new(LruCache::new(capacity))),
+            lookup_ttl: LOOKUP_CACHE_TTL,
+        }
+    }
+
+    /// Reuse lookup results for `ttl` instead of `LOOKUP_CACHE_TTL`
+    pub fn with_lookup_ttl(mut self, ttl: Duration) -> Self {
+        self.lookup_ttl = ttl;
+        self
+    }
+
+    /// Issue a new key for `name` on the given plan.
+    ///
+    /// Returns the plaintext key; callers must hand it to the user
+    /// immediately since it cannot be recovered later.
+    pub async fn issue(
+        &self,
+        name: &str,
+        plan: PlanTier,
+    ) -> Result<IssuedApiKey, RateLimitError> {
+        let key = generate_api_key();
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO api_keys (name, key_hash, plan)
+            VALUES ($1, $2, $3)
+            RETURNING id, created_at
+            "#,
+        )
+        .bind(name)
+        .bind(hash_api_key(&key))
+        .bind(plan.as_str())
+        .fetch_one(&self.pool)
+        .await?;
+
+        tracing::info!("Issued API key '{}' on plan {}", name, plan);
+
+        Ok(IssuedApiKey {
+            id: row.try_get::<Uuid, _>("id")?.to_string(),
+            name: name.to_string(),
+            plan,
+            key,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+
+    /// The remembered result of resolving `key`: `Some(None)` for a key
+    /// known to be invalid, `None` when it has to be looked up
+    pub fn cached(&self, key: &str) -> Option<Option<ApiKeyIdentity>> {
+        let hash = hash_api_key(key);
+        let mut lookups = self.lookups.lock().unwrap();
+        match lookups.get(&hash) {
+            Some(lookup) if lookup.expires_at > Instant::now() => {
+                Some(lookup.identity)
+            }
+            Some(_) => {
+                lookups.pop(&hash);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Resolve a plaintext key to its identity, from the lookup cache when
+    /// possible.
+    ///
+    /// Returns `Ok(None)` for unknown or revoked keys. Failed lookups are
+    /// not remembered.
+    pub async fn authenticate(
+        &self,
+        key: &str,
+    ) -> Result<Option<ApiKeyIdentity>, RateLimitError> {
+        if let Some(identity) = self.cached(key) {
+            return Ok(identity);
+        }
+
+        let hash = hash_api_key(key);
+        let identity = self.lookup(&hash).await?;
+        self.lookups.lock().unwrap().put(
+            hash,
+            CachedLookup {
+                identity,
+                expires_at: Instant::now() + self.lookup_ttl,
+            },
+        );
+        Ok(identity)
+    }
+
+    /// Find the active key with hash `hash` in the database
+    async fn lookup(
+        &self,
+        hash: &str,
+    ) -> Result<Option<ApiKeyIdentity>, RateLimitError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, plan
+            FROM api_keys
+            WHERE key_hash = $1 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let plan = row
+            .try_get::<String, _>("plan")?
+            .parse::<PlanTier>()
+            .unwrap_or_else(|e| {
+                tracing::warn!("{}; falling back to free plan", e);
+                PlanTier::Free
+            });
+
+        Ok(Some(ApiKeyIdentity {
+            key_id: row.try_get("id")?,
+            plan,
+        }))
+    }
+}
+
+/// Hash a plaintext key for storage and lookup (hex-encoded SHA-256)
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Check the shape of a presented key before touching the database
+pub fn is_well_formed_api_key(key: &str) -> bool {
+    key.strip_prefix(API_KEY_PREFIX).is_some_and(|body| {
+        body.len() == API_KEY_BYTES * 2
+            && body.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// Generate a new random key: "mw_" followed by 64 hex characters
+fn generate_api_key() -> String {
+    let mut bytes = [0u8; API_KEY_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", API_KEY_PREFIX, hex::encode(bytes))
+}
@@ -0,0 +1,181 @@
+//! In-process rate-limit counters.
+//!
+//! Counters live in a fixed number of mutex-guarded shards so concurrent
+//! requests for different clients rarely contend. Expired counters are reset
+//! on access and purged by a periodic cleanup task.
+
+use crate::metrics::jobs::{job, record_job, JobOutcome};
+use crate::rate_limiter::backend::{CounterState, RateLimitBackend};
+use crate::rate_limiter::types::RateLimitError;
+use async_trait::async_trait;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Default number of shards
+pub const DEFAULT_SHARDS: usize = 16;
+
+/// Shortest interval between cleanup runs
+pub const MIN_CLEANUP_INTERVAL: Duration = Duration::from_secs(1);
+
+type Shard = Mutex<HashMap<String, Counter>>;
+
+#[derive(Debug, Clone, Copy)]
+struct Counter {
+    count: u32,
+    expires_at: Instant,
+}
+
+/// Sharded in-memory counters; limits apply per process
+#[derive(Clone)]
+pub struct InMemoryBackend {
+    shards: Arc<Vec<Shard>>,
+    hasher: RandomState,
+}
+
+impl Default for InMemoryBackend {
+    fn default() -> Self {
+        Self::new(DEFAULT_SHARDS)
+    }
+}
+
+impl InMemoryBackend {
+    /// Create a backend with `shard_count` shards (at least one)
+    pub fn new(shard_count: usize) -> Self {
+        let shards = (0..shard_count.max(1))
+            .map(|_| Mutex::new(HashMap::new()))
+            .collect();
+
+        Self {
+            shards: Arc::new(shards),
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Remove expired counters; returns how many were removed
+    pub fn purge_expired(&self) -> usize {
+        purge_shards(&self.shards, Instant::now())
+    }
+
+    /// Number of live (possibly expired but not yet purged) counters
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).len()).sum()
+    }
+
+    /// Whether no counters are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Spawn a task purging expired counters every `interval` (at least
+    /// `MIN_CLEANUP_INTERVAL`).
+    ///
+    /// The task stops once every handle to this backend has been dropped.
+    pub fn spawn_cleanup(&self, interval: Duration) -> JoinHandle<()> {
+        let shards: Weak<Vec<Shard>> = Arc::downgrade(&self.shards);
+        let interval = interval.max(MIN_CLEANUP_INTERVAL);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await; // First tick completes immediately
+            loop {
+                ticker.tick().await;
+                let Some(shards) = shards.upgrade() else {
+                    break;
+                };
+                let started = Instant::now();
+                let removed = purge_shards(&shards, started);
+                record_job(
+                    job::RATE_LIMIT_CLEANUP,
+                    JobOutcome::Success,
+                    started.elapsed(),
+                );
+                if removed > 0 {
+                    tracing::debug!(
+                        "Purged {} expired in-memory rate limit counters",
+                        removed
+                    );
+                }
+            }
+        })
+    }
+
+    fn shard(&self, key: &str) -> &Shard {
+        let index = self.hasher.hash_one(key) as usize % self.shards.len();
+        &self.shards[index]
+    }
+}
+
+#[async_trait]
+impl RateLimitBackend for InMemoryBackend {
+    async fn increment(
+        &self,
+        key: &str,
+        expiry_seconds: u64,
+    ) -> Result<u32, RateLimitError> {
+        let now = Instant::now();
+        let mut shard = lock(self.shard(key));
+
+        let counter = shard.entry(key.to_string()).or_insert(Counter {
+            count: 0,
+            expires_at: now,
+        });
+        if counter.expires_at <= now {
+            // New or expired window: start over
+            *counter = Counter {
+                count: 0,
+                expires_at: now + Duration::from_secs(expiry_seconds),
+            };
+        }
+        counter.count = counter.count.saturating_add(1);
+
+        Ok(counter.count)
+    }
+
+    async fn peek(
+        &self,
+        key: &str,
+    ) -> Result<Option<CounterState>, RateLimitError> {
+        let now = Instant::now();
+        let shard = lock(self.shard(key));
+
+        Ok(shard
+            .get(key)
+            .filter(|counter| counter.expires_at > now)
+            .map(|counter| CounterState {
+                count: counter.count,
+                ttl_seconds: Some((counter.expires_at - now).as_secs()),
+            }))
+    }
+
+    async fn reset(&self, key: &str) -> Result<bool, RateLimitError> {
+        Ok(lock(self.shard(key)).remove(key).is_some())
+    }
+
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+}
+
+fn purge_shards(shards: &[Shard], now: Instant) -> usize {
+    shards
+        .iter()
+        .map(|shard| {
+            let mut shard = lock(shard);
+            let before = shard.len();
+            shard.retain(|_, counter| counter.expires_at > now);
+            before - shard.len()
+        })
+        .sum()
+}
+
+/// Lock a shard, recovering from a poisoned mutex (counters stay usable)
+fn lock(shard: &Shard) -> std::sync::MutexGuard<'_, HashMap<String, Counter>> {
+    shard
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,87 @@
+//! Storage backends for rate-limit counters.
+//!
+//! - redis.rs: Shared counters in Redis for multi-node deployments
+//! - memory.rs: Sharded in-process counters for single-node and test setups
+//!
+//! The backend is selected with `RATE_LIMIT_BACKEND` (`redis` or `memory`).
+
+pub mod memory;
+pub mod redis;
+
+pub use memory::InMemoryBackend;
+pub use redis::RedisBackend;
+
+use crate::rate_limiter::types::RateLimitError;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Counter storage used by `RateLimitService`
+#[async_trait]
+pub trait RateLimitBackend: Send + Sync {
+    /// Atomically increment `key` and return the new count.
+    ///
+    /// The counter expires `expiry_seconds` after its first increment.
+    async fn increment(
+        &self,
+        key: &str,
+        expiry_seconds: u64,
+    ) -> Result<u32, RateLimitError>;
+
+    /// Read a counter without incrementing it; `None` if absent or expired
+    async fn peek(
+        &self,
+        key: &str,
+    ) -> Result<Option<CounterState>, RateLimitError>;
+
+    /// Delete a counter; returns whether it existed
+    async fn reset(&self, key: &str) -> Result<bool, RateLimitError>;
+
+    /// Check that the counter store answers; in-process stores always do
+    async fn ping(&self) -> Result<(), RateLimitError> {
+        Ok(())
+    }
+
+    /// Short backend name for logs
+    fn name(&self) -> &'static str;
+}
+
+/// Current value of a counter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CounterState {
+    pub count: u32,
+    /// Seconds until the counter expires; `None` if it has no expiry
+    pub ttl_seconds: Option<u64>,
+}
+
+/// Which backend to build from configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitBackendKind {
+    /// Shared counters in Redis (default)
+    Redis,
+    /// Per-process counters; limits are not shared between instances
+    Memory,
+}
+
+impl fmt::Display for RateLimitBackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Redis => write!(f, "redis"),
+            Self::Memory => write!(f, "memory"),
+        }
+    }
+}
+
+impl FromStr for RateLimitBackendKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "redis" => Ok(Self::Redis),
+            "memory" => Ok(Self::Memory),
+            other => Err(format!("Unknown rate limit backend '{}'", other)),
+        }
+    }
+}
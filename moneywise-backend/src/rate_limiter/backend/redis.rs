@@ -0,0 +1,149 @@
+//! Redis-backed rate-limit counters.
+//!
+//! Each counter is a single key, so any `RedisTopology` works unchanged.
+//!
+//! Connecting and every command are bounded by the configured timeout, so a
+//! Redis that stops answering surfaces as an error and the degradation
+//! policy applies, instead of stalling requests.
+
+use crate::rate_limiter::backend::{CounterState, RateLimitBackend};
+use crate::rate_limiter::types::RateLimitError;
+use crate::redis_topology::{RedisConnection, RedisTopology};
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tracing::{error, warn};
+
+/// Counters stored in Redis with `INCR` + `EXPIRE`, shared by all instances
+pub struct RedisBackend {
+    redis_url: String,
+    topology: RedisTopology,
+    /// Connected lazily; a failed attempt is retried on the next request
+    conn: OnceCell<RedisConnection>,
+    /// Limit for connecting and for each command
+    timeout: Duration,
+}
+
+impl RedisBackend {
+    /// Create a backend for `redis_url` without connecting yet; connecting
+    /// and each command give up after `timeout`
+    pub fn new(
+        redis_url: &str,
+        topology: RedisTopology,
+        timeout: Duration,
+    ) -> Result<Self, RateLimitError> {
+        topology.validate(redis_url).map_err(|e| {
+            error!("Invalid Redis configuration: {}", e);
+            e
+        })?;
+
+        Ok(Self {
+            redis_url: redis_url.to_string(),
+            topology,
+            conn: OnceCell::new(),
+            timeout,
+        })
+    }
+
+    async fn connection(&self) -> Result<RedisConnection, RateLimitError> {
+        let connecting = async {
+            let conn = self
+                .conn
+                .get_or_try_init(|| self.topology.connect(&self.redis_url))
+                .await?;
+            Ok(conn.clone())
+        };
+        self.bounded(connecting).await.map_err(|e| {
+            error!("Failed to connect to Redis: {}", e);
+            e
+        })
+    }
+
+    /// Run a Redis call, failing with a timeout error after `self.timeout`
+    async fn bounded<T, F>(&self, call: F) -> Result<T, RateLimitError>
+    where
+        F: Future<Output = redis::RedisResult<T>>,
+    {
+        match tokio::time::timeout(self.timeout, call).await {
+            Ok(result) => Ok(result?),
+            Err(_) => {
+                Err(RateLimitError::RedisError(redis::RedisError::from((
+                    redis::ErrorKind::IoError,
+                    "Redis call timed out",
+                    format!(
+                        "no response within {}ms",
+                        self.timeout.as_millis()
+                    ),
+                ))))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitBackend for RedisBackend {
+    async fn increment(
+        &self,
+        key: &str,
+        expiry_seconds: u64,
+    ) -> Result<u32, RateLimitError> {
+        let mut conn = self.connection().await?;
+        let count: u32 = self.bounded(conn.incr(key, 1)).await?;
+
+        // Set expiry for automatic cleanup (only on first increment)
+        if count == 1 {
+            if let Err(e) = self
+                .bounded(conn.expire::<&str, i64>(key, expiry_seconds as i64))
+                .await
+            {
+                // Log the error but don't fail the request - Redis will eventually clean up
+                warn!("Failed to set expiry for rate limit key {}: {}", key, e);
+            }
+        }
+
+        Ok(count)
+    }
+
+    async fn peek(
+        &self,
+        key: &str,
+    ) -> Result<Option<CounterState>, RateLimitError> {
+        let mut conn = self.connection().await?;
+        let count: Option<u32> = self.bounded(conn.get(key)).await?;
+        let Some(count) = count else {
+            return Ok(None);
+        };
+
+        // TTL returns -1 for keys without expiry and -2 for missing keys
+        let ttl: i64 = self.bounded(conn.ttl(key)).await?;
+        Ok(Some(CounterState {
+            count,
+            ttl_seconds: u64::try_from(ttl).ok(),
+        }))
+    }
+
+    async fn reset(&self, key: &str) -> Result<bool, RateLimitError> {
+        let mut conn = self.connection().await?;
+        let deleted: u32 = self.bounded(conn.del(key)).await?;
+        Ok(deleted > 0)
+    }
+
+    /// Connect (if needed) and ping Redis
+    async fn ping(&self) -> Result<(), RateLimitError> {
+        let mut conn = self.connection().await?;
+        let _: String = self
+            .bounded(redis::cmd("PING").query_async(&mut conn))
+            .await
+            .map_err(|e| {
+                error!("Redis ping failed: {}", e);
+                e
+            })?;
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+}
@@ -1,14 +1,85 @@
 //! Rate limiting configuration
 
-use crate::connections::parse_redis_url_from_env;
+use crate::connections::{parse_env_with_default, parse_redis_url_from_env};
+use crate::rate_limiter::backend::{memory, RateLimitBackendKind};
+use crate::rate_limiter::types::{DegradationPolicy, TransactionType};
+use crate::redis_topology::RedisTopology;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Rate limiting configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateLimitConfig {
     pub redis_url: String,
-    /// Graceful degradation when Redis is unavailable
-    pub graceful_degradation: bool,
+    /// Standalone, Sentinel or Cluster deployment behind `redis_url`
+    pub redis_topology: RedisTopology,
+    /// Behavior per transaction type when the backend is unavailable
+    pub degradation: DegradationPolicies,
+    /// Where counters are stored (`RATE_LIMIT_BACKEND`: redis or memory)
+    pub backend: RateLimitBackendKind,
+    /// Number of shards for the in-memory backend
+    pub memory_shards: usize,
+    /// Limit for connecting to Redis and for each Redis command
+    /// (`REDIS_CONNECTION_TIMEOUT_SECS`, shared with the cache)
+    pub connection_timeout: Duration,
+    /// How often in-memory counters (backend and fallback) are purged;
+    /// raised to `memory::MIN_CLEANUP_INTERVAL` if shorter
+    pub memory_cleanup_interval: Duration,
+}
+
+/// Degradation policy for each transaction type.
+///
+/// Defaults keep writes protected by the local fallback limiter and let
+/// reads through, so a Redis outage never disables write protection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DegradationPolicies {
+    pub budget_modification: DegradationPolicy,
+    pub budget_read: DegradationPolicy,
+    pub budget_overview: DegradationPolicy,
+}
+
+impl DegradationPolicies {
+    /// Same policy for every transaction type
+    pub fn uniform(policy: DegradationPolicy) -> Self {
+        Self {
+            budget_modification: policy,
+            budget_read: policy,
+            budget_overview: policy,
+        }
+    }
+
+    /// Policy applied to a transaction type
+    pub fn for_type(
+        &self,
+        transaction_type: TransactionType,
+    ) -> DegradationPolicy {
+        match transaction_type {
+            TransactionType::BudgetModification => self.budget_modification,
+            TransactionType::BudgetRead => self.budget_read,
+            TransactionType::BudgetOverview => self.budget_overview,
+        }
+    }
+}
+
+impl Default for DegradationPolicies {
+    /// Read from `RATE_LIMIT_DEGRADATION_{MODIFICATION,READ,OVERVIEW}`
+    /// (`fail_open`, `fail_closed` or `local_fallback`).
+    fn default() -> Self {
+        Self {
+            budget_modification: parse_env_with_default(
+                "RATE_LIMIT_DEGRADATION_MODIFICATION",
+                DegradationPolicy::LocalFallback,
+            ),
+            budget_read: parse_env_with_default(
+                "RATE_LIMIT_DEGRADATION_READ",
+                DegradationPolicy::FailOpen,
+            ),
+            budget_overview: parse_env_with_default(
+                "RATE_LIMIT_DEGRADATION_OVERVIEW",
+                DegradationPolicy::FailOpen,
+            ),
+        }
+    }
 }
 
 impl Default for RateLimitConfig {
@@ -20,9 +91,46 @@ impl Default for RateLimitConfig {
     /// that cannot be parsed as the expected types. This is intentional for
     /// configuration errors that should be caught at startup.
     fn default() -> Self {
+        let backend = parse_env_with_default(
+            "RATE_LIMIT_BACKEND",
+            RateLimitBackendKind::Redis,
+        );
+        let memory_shards = parse_env_with_default(
+            "RATE_LIMIT_MEMORY_SHARDS",
+            memory::DEFAULT_SHARDS,
+        );
+        let memory_cleanup_interval =
+            parse_env_with_default("RATE_LIMIT_CLEANUP_INTERVAL_SECS", 60);
+        let connection_timeout =
+            parse_env_with_default("REDIS_CONNECTION_TIMEOUT_SECS", 5);
+
+        Self {
+            redis_url: parse_redis_url_from_env("REDIS_URL"),
+            redis_topology: RedisTopology::from_env(),
+            degradation: DegradationPolicies::default(),
+            backend,
+            memory_shards,
+            connection_timeout: Duration::from_secs(connection_timeout),
+            memory_cleanup_interval: Duration::from_secs(
+                memory_cleanup_interval,
+            ),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// In-memory configuration for tests and single-node deployments
+    pub fn in_memory() -> Self {
         Self {
             redis_url: parse_redis_url_from_env("REDIS_URL"),
-            graceful_degradation: true,
+            redis_topology: RedisTopology::from_env(),
+            degradation: DegradationPolicies::uniform(
+                DegradationPolicy::FailOpen,
+            ),
+            backend: RateLimitBackendKind::Memory,
+            memory_shards: memory::DEFAULT_SHARDS,
+            connection_timeout: Duration::from_secs(5),
+            memory_cleanup_interval: Duration::from_secs(60),
         }
     }
 }
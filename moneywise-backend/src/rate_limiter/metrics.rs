@@ -0,0 +1,74 @@
+//! Rate limiter decision counters.
+//!
+//! Exposed as `moneywise_rate_limit_decisions_total{decision, transaction_type}`
+//! where `decision` is `allowed`, `denied` (over a limit, including the
+//! local fallback's), `degraded` (backend failure, request let through) or
+//! `unavailable` (backend failure, rejected with 503 by a fail-closed
+//! policy).
+
+use crate::metrics::{register, METRICS_NAMESPACE};
+use crate::rate_limiter::types::TransactionType;
+use prometheus::{IntCounterVec, Opts};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Outcome of a rate limit check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Allowed,
+    Denied,
+    /// Backend unavailable; the request was let through
+    Degraded,
+    /// Backend unavailable; the request was rejected (fail closed)
+    Unavailable,
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Allowed => write!(f, "allowed"),
+            Self::Denied => write!(f, "denied"),
+            Self::Degraded => write!(f, "degraded"),
+            Self::Unavailable => write!(f, "unavailable"),
+        }
+    }
+}
+
+fn decisions() -> &'static IntCounterVec {
+    static DECISIONS: OnceLock<IntCounterVec> = OnceLock::new();
+    DECISIONS.get_or_init(|| {
+        let counter = IntCounterVec::new(
+            Opts::new(
+                "rate_limit_decisions_total",
+                "Rate limit decisions by outcome and transaction type",
+            )
+            .namespace(METRICS_NAMESPACE),
+            &["decision", "transaction_type"],
+        )
+        .expect("valid rate limit metric definition");
+        register(counter)
+    })
+}
+
+/// Count one decision
+pub fn record_decision(decision: Decision, transaction_type: TransactionType) {
+    decisions()
+        .with_label_values(&[
+            &decision.to_string(),
+            &transaction_type.to_string(),
+        ])
+        .inc();
+}
+
+/// Current count for a decision and transaction type
+pub fn decision_count(
+    decision: Decision,
+    transaction_type: TransactionType,
+) -> u64 {
+    decisions()
+        .with_label_values(&[
+            &decision.to_string(),
+            &transaction_type.to_string(),
+        ])
+        .get()
+}
@@ -1,18 +1,37 @@
 //! Axum middleware for rate limiting
 
+use crate::rate_limiter::api_keys::{is_well_formed_api_key, API_KEY_HEADER};
+use crate::rate_limiter::routes::{RateLimitClass, RouteTable};
 use crate::rate_limiter::service::RateLimitService;
-use crate::rate_limiter::types::{RateLimitKey, TransactionType};
+use crate::rate_limiter::types::{DegradationPolicy, RateLimitKey};
+use crate::server::request_id::insert_request_id;
 use axum::{
-    extract::State, http::StatusCode, middleware::Next, response::IntoResponse,
+    extract::{MatchedPath, State},
+    http::StatusCode,
+    middleware::Next,
+    response::IntoResponse,
     Json,
 };
 use serde_json::json;
 use std::sync::Arc;
 
-/// Extract rate limit information from request
+/// Extract rate limit information from request.
+///
+/// The transaction type comes from the class declared for the matched route
+/// in `routes`. Returns `None` for routes declared as exempt.
 pub fn extract_rate_limit_info(
     req: &axum::http::Request<axum::body::Body>,
-) -> RateLimitKey {
+    routes: &RouteTable,
+) -> Option<RateLimitKey> {
+    let matched_path = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str());
+    let transaction_type = match routes.classify(req.method(), matched_path) {
+        RateLimitClass::Limited(transaction_type) => transaction_type,
+        RateLimitClass::Exempt => return None,
+    };
+
     // Get IP address from headers (in production, this should come from a reverse proxy)
     let ip = req
         .headers()
@@ -29,47 +48,25 @@ pub fn extract_rate_limit_info(
         .and_then(|h| h.to_str().ok())
         .and_then(|s| validate_device_id(s).then_some(s.to_string()));
 
-    // Determine transaction type from path and HTTP method
-    let path = req.uri().path();
-    let method = req.method().as_str();
-
-    // Match API endpoints with appropriate rate limiting
-    let transaction_type = if path.starts_with("/api/budgets") {
-        match (path, method) {
-            // Budget overview endpoint - highest rate limit for frequent dashboard updates
-            ("/api/budgets/overview", "GET") => TransactionType::BudgetOverview,
-            // Budget read operations - moderate rate limit for data fetching
-            ("/api/budgets", "GET") => TransactionType::BudgetRead,
-            // Individual budget read operations
-            (path, "GET") if path.starts_with("/api/budgets/") && path.len() > "/api/budgets/".len() => {
-                TransactionType::BudgetRead
-            },
-            // Budget modification operations - strictest rate limit for write operations
-            ("/api/budgets", "POST") => TransactionType::BudgetModification,
-            ("/api/budgets", "PUT") => TransactionType::BudgetModification,
-            // Individual budget modification operations
-            (path, "PUT") if path.starts_with("/api/budgets/") && path.len() > "/api/budgets/".len() => {
-                TransactionType::BudgetModification
-            },
-            // Default to budget read for any other budget endpoints
-            _ => TransactionType::BudgetRead,
-        }
-    } else {
-        // For non-budget endpoints, use a default rate limit
-        // TODO: Add other transaction types as needed
-        // This prevents the warning and provides reasonable rate limiting
-        TransactionType::BudgetRead
-    };
+    Some(RateLimitKey::new(ip, device_id, transaction_type))
+}
 
-    RateLimitKey::new(ip, device_id, transaction_type)
+/// Extract the API key header, if present
+pub fn extract_api_key(
+    req: &axum::http::Request<axum::body::Body>,
+) -> Option<String> {
+    req.headers()
+        .get(API_KEY_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
 }
 
 /// Validates device ID format and length
-fn validate_device_id(device_id: &str) -> bool {
+pub fn validate_device_id(device_id: &str) -> bool {
     // Device ID should be 8-64 characters, alphanumeric with hyphens/underscores
     let len = device_id.len();
-    len >= 8
-        && len <= 64
+    (8..=64).contains(&len)
         && device_id
             .chars()
             .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
@@ -81,22 +78,75 @@ pub async fn rate_limit_middleware(
     req: axum::http::Request<axum::body::Body>,
     next: Next<axum::body::Body>,
 ) -> impl IntoResponse {
-    // Extract rate limit information
-    let rate_limit_key = extract_rate_limit_info(&req);
+    // Extract rate limit information; exempt routes skip limiting entirely
+    let Some(mut rate_limit_key) =
+        extract_rate_limit_info(&req, rate_limiter.routes())
+    else {
+        return next.run(req).await;
+    };
+
+    // Switch to per-key limits when a valid API key is presented
+    if let Some(api_key) = extract_api_key(&req) {
+        if !is_well_formed_api_key(&api_key) {
+            return create_invalid_api_key_error().into_response();
+        }
+        match rate_limiter.cached_api_key(&api_key) {
+            Some(Some(identity)) => {
+                rate_limit_key = rate_limit_key.with_api_key(identity);
+            }
+            Some(None) => {
+                return create_invalid_api_key_error().into_response();
+            }
+            None => {
+                // Looking the key up costs a database query: a per-IP lookup
+                // budget sheds floods of made-up keys before the database,
+                // without charging key holders to the shared IP/device limit
+                if let Ok(result) =
+                    rate_limiter.check_api_key_lookup(&rate_limit_key).await
+                {
+                    if !result.allowed {
+                        return rejection_response(&result);
+                    }
+                }
+
+                match rate_limiter.authenticate_api_key(&api_key).await {
+                    Ok(Some(identity)) => {
+                        rate_limit_key = rate_limit_key.with_api_key(identity);
+                    }
+                    Ok(None) => {
+                        // Invalid keys count against the IP/device limit
+                        if let Ok(result) =
+                            rate_limiter.check_and_record(rate_limit_key).await
+                        {
+                            if !result.allowed {
+                                return rejection_response(&result);
+                            }
+                        }
+                        return create_invalid_api_key_error().into_response();
+                    }
+                    Err(e) => {
+                        // Fall back to IP/device limits rather than rejecting
+                        // the request
+                        tracing::warn!("API key lookup failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
 
     // Check rate limit
-    match rate_limiter.check_and_record(rate_limit_key).await {
-        Ok(result) => {
-            if result.allowed {
-                // Process request and add rate limit headers
-                let mut res = next.run(req).await;
-                add_rate_limit_headers(&mut res, &result);
-                res
-            } else {
-                // Return rate limit error
-                create_rate_limit_error(&result).into_response()
+    let outcome = rate_limiter.check_and_record(rate_limit_key).await;
+    match outcome {
+        Ok(result) if result.allowed => {
+            // Process request and add rate limit headers
+            let mut res = next.run(req).await;
+            add_rate_limit_headers(&mut res, &result);
+            if result.degradation.is_some() {
+                add_error_headers(&mut res);
             }
+            res
         }
+        Ok(result) => rejection_response(&result),
         Err(e) => {
             // Log error but allow request to proceed (graceful degradation)
             tracing::warn!("Rate limit check failed: {}", e);
@@ -107,6 +157,31 @@ pub async fn rate_limit_middleware(
     }
 }
 
+/// Response for a request the limiter did not allow
+fn rejection_response(
+    result: &crate::rate_limiter::types::RateLimitResult,
+) -> axum::response::Response {
+    if result.degradation == Some(DegradationPolicy::FailClosed) {
+        // Backend down and this transaction type fails closed
+        let mut res = create_unavailable_error(result).into_response();
+        add_error_headers(&mut res);
+        if let Some(retry_after) = result.retry_after {
+            if let Ok(retry_header) = retry_after.to_string().parse() {
+                res.headers_mut().insert("Retry-After", retry_header);
+            }
+        }
+        res
+    } else {
+        // Return rate limit error
+        let mut res = create_rate_limit_error(result).into_response();
+        add_rate_limit_headers(&mut res, result);
+        if result.degradation.is_some() {
+            add_error_headers(&mut res);
+        }
+        res
+    }
+}
+
 /// Add rate limit headers to response
 fn add_rate_limit_headers(
     res: &mut axum::response::Response,
@@ -115,8 +190,7 @@ fn add_rate_limit_headers(
     let headers = res.headers_mut();
 
     // Safely insert headers with proper error handling
-    if let Ok(limit_header) = result.limit_type.get_limit().to_string().parse()
-    {
+    if let Ok(limit_header) = result.limit.to_string().parse() {
         headers.insert("X-RateLimit-Limit", limit_header);
     }
 
@@ -134,6 +208,26 @@ fn add_rate_limit_headers(
             headers.insert("Retry-After", retry_header);
         }
     }
+
+    if let Some(plan) = result.plan {
+        if let Ok(plan_header) = plan.as_str().parse() {
+            headers.insert("X-RateLimit-Plan", plan_header);
+        }
+    }
+
+    if let Some(daily) = &result.daily {
+        if let Ok(daily_limit_header) = daily.limit.to_string().parse() {
+            headers.insert("X-RateLimit-Daily-Limit", daily_limit_header);
+        }
+        if let Ok(daily_remaining_header) = daily.remaining.to_string().parse()
+        {
+            headers
+                .insert("X-RateLimit-Daily-Remaining", daily_remaining_header);
+        }
+        if let Ok(daily_reset_header) = daily.reset_time.to_string().parse() {
+            headers.insert("X-RateLimit-Daily-Reset", daily_reset_header);
+        }
+    }
 }
 
 /// Add error headers when rate limiting fails
@@ -148,13 +242,53 @@ fn add_error_headers(res: &mut axum::response::Response) {
 fn create_rate_limit_error(
     result: &crate::rate_limiter::types::RateLimitResult,
 ) -> impl IntoResponse {
-    let error_body = json!({
+    let daily_exhausted =
+        result.daily.as_ref().is_some_and(|d| d.remaining == 0);
+    let message = if daily_exhausted {
+        "Daily request quota exhausted".to_string()
+    } else {
+        format!("Too many requests for {:?}", result.limit_type)
+    };
+
+    let mut error_body = json!({
         "error": "Rate limit exceeded",
-        "message": format!("Too many requests for {:?}", result.limit_type),
+        "message": message,
         "retry_after": result.retry_after,
         "reset_at": result.reset_time,
-        "limit_type": result.limit_type
+        "limit_type": result.limit_type,
+        "plan": result.plan
     });
+    insert_request_id(&mut error_body);
 
     (StatusCode::TOO_MANY_REQUESTS, Json(error_body))
 }
+
+/// Create error response when rate limiting is unavailable and the
+/// transaction type fails closed
+fn create_unavailable_error(
+    result: &crate::rate_limiter::types::RateLimitResult,
+) -> impl IntoResponse {
+    let mut error_body = json!({
+        "error": "Rate limiting unavailable",
+        "message": format!(
+            "{:?} requests are temporarily disabled; please retry shortly",
+            result.limit_type
+        ),
+        "retry_after": result.retry_after,
+        "limit_type": result.limit_type
+    });
+    insert_request_id(&mut error_body);
+
+    (StatusCode::SERVICE_UNAVAILABLE, Json(error_body))
+}
+
+/// Create error response for an unknown, revoked or malformed API key
+fn create_invalid_api_key_error() -> impl IntoResponse {
+    let mut error_body = json!({
+        "error": "Invalid API key",
+        "status": StatusCode::UNAUTHORIZED.as_u16()
+    });
+    insert_request_id(&mut error_body);
+
+    (StatusCode::UNAUTHORIZED, Json(error_body))
+}
@@ -1,8 +1,10 @@
 //! Rate Limiting Service for MoneyWise Backend
 //!
-//! Provides server-side rate limiting using Redis with:
+//! Provides server-side rate limiting (Redis or in-memory counters) with:
 //! - Budget operations limit (30/min)
 //! - IP and device-based tracking
+//! - Per-route classes declared next to each route (`ClassifiedRouter`)
+//! - Optional per-API-key tracking with plan tiers and a daily quota
 //! - Graceful degradation when Redis is unavailable
 //!
 //! TODO: Add rate limiting for other endpoint types:
@@ -10,10 +12,19 @@
 //! - Reporting endpoints (analytics, exports)
 //! - Settings endpoints (configuration, preferences)
 
+pub mod api_keys;
+pub mod backend;
 pub mod config;
+pub mod metrics;
 pub mod middleware;
+pub mod plans;
+pub mod routes;
 pub mod service;
 pub mod types;
 
+pub use api_keys::ApiKeyStore;
+pub use backend::{InMemoryBackend, RateLimitBackend, RedisBackend};
 pub use config::RateLimitConfig;
+pub use plans::PlanTier;
+pub use routes::{ClassifiedRouter, RateLimitClass, RouteTable};
 pub use service::RateLimitService;
@@ -0,0 +1,72 @@
+//! Plan tiers for API-key based rate limiting.
+//!
+//! Requests without an API key keep the shared limits from
+//! `config/rate-limits.json`. Requests carrying a valid key are limited per
+//! key using the limits of the key's plan, plus a daily quota that spans all
+//! transaction types.
+
+use crate::rate_limiter::types::TransactionType;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Rate-limit plan attached to an API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanTier {
+    /// Default plan: same per-minute limits as anonymous traffic
+    Free,
+    /// Household plan: several devices sharing one key
+    Family,
+}
+
+impl PlanTier {
+    /// Per-window request limit for a transaction type.
+    ///
+    /// The window length itself is shared with anonymous traffic
+    /// (`TransactionType::get_window_seconds`).
+    pub fn limit_for(&self, transaction_type: TransactionType) -> u32 {
+        match self {
+            Self::Free => transaction_type.get_limit(),
+            Self::Family => match transaction_type {
+                TransactionType::BudgetModification => 90,
+                TransactionType::BudgetRead => 300,
+                TransactionType::BudgetOverview => 600,
+            },
+        }
+    }
+
+    /// Maximum number of requests per UTC day across all transaction types
+    pub fn daily_quota(&self) -> u32 {
+        match self {
+            Self::Free => 5_000,
+            Self::Family => 20_000,
+        }
+    }
+
+    /// Stable string form used in the database and response headers
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Free => "free",
+            Self::Family => "family",
+        }
+    }
+}
+
+impl fmt::Display for PlanTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PlanTier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "free" => Ok(Self::Free),
+            "family" => Ok(Self::Family),
+            other => Err(format!("Unknown plan tier '{}'", other)),
+        }
+    }
+}
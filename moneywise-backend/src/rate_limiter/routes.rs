@@ -0,0 +1,322 @@
+//! Route-table-driven rate-limit classification.
+//!
+//! Every endpoint declares its `TransactionType` where it is routed, via
+//! `ClassifiedRouter`. The resulting `RouteTable` is keyed by HTTP method and
+//! the matched route pattern (e.g. `GET /api/budgets/:id`), so the middleware
+//! no longer guesses classes from raw request paths.
+//!
+//! Routes added through the unclassified escape hatch are reported at startup
+//! and limited with the strictest class until they opt in.
+
+use crate::rate_limiter::types::TransactionType;
+use axum::{
+    handler::Handler,
+    http::Method,
+    routing::{on, MethodFilter, MethodRouter},
+    Router,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Class applied to matched routes that never declared one.
+/// Fails safe by using the strictest limit.
+pub const UNCLASSIFIED_ROUTE_CLASS: TransactionType =
+    TransactionType::BudgetModification;
+
+/// Class applied to requests that matched no route (404 fallback)
+pub const UNMATCHED_ROUTE_CLASS: TransactionType = TransactionType::BudgetRead;
+
+/// Rate-limit class declared for a route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitClass {
+    /// Count requests against the given transaction type
+    Limited(TransactionType),
+    /// Never rate limited (e.g. liveness probes)
+    Exempt,
+}
+
+impl From<TransactionType> for RateLimitClass {
+    fn from(transaction_type: TransactionType) -> Self {
+        Self::Limited(transaction_type)
+    }
+}
+
+impl fmt::Display for RateLimitClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Limited(transaction_type) => {
+                write!(f, "{}", transaction_type)
+            }
+            Self::Exempt => write!(f, "exempt"),
+        }
+    }
+}
+
+/// Rate-limit classes of all routes, keyed by method and route pattern
+#[derive(Debug, Clone, Default)]
+pub struct RouteTable {
+    classes: BTreeMap<(String, String), RateLimitClass>,
+    /// Route patterns added without a class
+    unclassified: BTreeSet<String>,
+}
+
+impl RouteTable {
+    /// Look up the class of a matched route.
+    ///
+    /// `matched_path` is the route pattern (axum's `MatchedPath`), or `None`
+    /// when the request did not match any route. HEAD requests are served by
+    /// GET handlers, so they take the GET class unless HEAD has its own.
+    /// Unclassified routes are reported once by `report`, not per request.
+    pub fn classify(
+        &self,
+        method: &Method,
+        matched_path: Option<&str>,
+    ) -> RateLimitClass {
+        let Some(path) = matched_path else {
+            return RateLimitClass::Limited(UNMATCHED_ROUTE_CLASS);
+        };
+
+        self.lookup(method, path)
+            .or_else(|| {
+                (method == Method::HEAD)
+                    .then(|| self.lookup(&Method::GET, path))
+                    .flatten()
+            })
+            .unwrap_or(RateLimitClass::Limited(UNCLASSIFIED_ROUTE_CLASS))
+    }
+
+    /// Route patterns that were added without declaring a class
+    pub fn unclassified_routes(&self) -> Vec<String> {
+        self.unclassified.iter().cloned().collect()
+    }
+
+    /// Log the route table and report unclassified routes.
+    ///
+    /// Intended to run once at startup. Returns the unclassified routes so
+    /// callers can decide whether to refuse to start.
+    pub fn report(&self) -> Vec<String> {
+        for ((method, path), class) in &self.classes {
+            tracing::debug!(
+                "Rate-limit class {} for {} {}",
+                class,
+                method,
+                path
+            );
+        }
+
+        let unclassified = self.unclassified_routes();
+        for path in &unclassified {
+            tracing::error!(
+                "Route {} has no rate-limit class; it will be limited as {}",
+                path,
+                UNCLASSIFIED_ROUTE_CLASS
+            );
+        }
+        tracing::info!(
+            "Rate-limit route table: {} classified, {} unclassified",
+            self.classes.len(),
+            unclassified.len()
+        );
+        unclassified
+    }
+
+    /// Number of classified (method, route) pairs
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Whether no routes have been classified
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    fn lookup(&self, method: &Method, path: &str) -> Option<RateLimitClass> {
+        self.classes
+            .get(&(method.as_str().to_string(), path.to_string()))
+            .copied()
+    }
+
+    fn insert(&mut self, method: &Method, path: &str, class: RateLimitClass) {
+        self.classes
+            .insert((method.as_str().to_string(), path.to_string()), class);
+    }
+
+    fn extend_with_prefix(&mut self, prefix: &str, other: RouteTable) {
+        for ((method, path), class) in other.classes {
+            self.classes
+                .insert((method, join_paths(prefix, &path)), class);
+        }
+        for path in other.unclassified {
+            self.unclassified.insert(join_paths(prefix, &path));
+        }
+    }
+}
+
+/// Router wrapper that records a rate-limit class for every route it adds.
+///
+/// # Examples
+///
+/// ```ignore
+/// ClassifiedRouter::new()
+///     .get("/", get_budgets, TransactionType::BudgetRead)
+///     .post("/", create_budget, TransactionType::BudgetModification)
+/// ```
+pub struct ClassifiedRouter<S = ()> {
+    router: Router<S>,
+    table: RouteTable,
+}
+
+impl<S> Default for ClassifiedRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> ClassifiedRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    /// Create an empty router
+    pub fn new() -> Self {
+        Self {
+            router: Router::new(),
+            table: RouteTable::default(),
+        }
+    }
+
+    /// Add a GET route with its rate-limit class
+    pub fn get<H, T>(
+        self,
+        path: &str,
+        handler: H,
+        class: impl Into<RateLimitClass>,
+    ) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.on(Method::GET, path, handler, class)
+    }
+
+    /// Add a POST route with its rate-limit class
+    pub fn post<H, T>(
+        self,
+        path: &str,
+        handler: H,
+        class: impl Into<RateLimitClass>,
+    ) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.on(Method::POST, path, handler, class)
+    }
+
+    /// Add a PUT route with its rate-limit class
+    pub fn put<H, T>(
+        self,
+        path: &str,
+        handler: H,
+        class: impl Into<RateLimitClass>,
+    ) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.on(Method::PUT, path, handler, class)
+    }
+
+    /// Add a DELETE route with its rate-limit class
+    pub fn delete<H, T>(
+        self,
+        path: &str,
+        handler: H,
+        class: impl Into<RateLimitClass>,
+    ) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.on(Method::DELETE, path, handler, class)
+    }
+
+    /// Add a route for an arbitrary method with its rate-limit class
+    pub fn on<H, T>(
+        mut self,
+        method: Method,
+        path: &str,
+        handler: H,
+        class: impl Into<RateLimitClass>,
+    ) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        let filter = MethodFilter::try_from(method.clone())
+            .expect("unsupported HTTP method for route");
+        self.table.insert(&method, path, class.into());
+        self.router = self.router.route(path, on(filter, handler));
+        self
+    }
+
+    /// Add a pre-built method router without a class.
+    ///
+    /// Escape hatch for routers built elsewhere; the route is reported by
+    /// `RouteTable::report` and limited as `UNCLASSIFIED_ROUTE_CLASS`.
+    pub fn route_unclassified(
+        mut self,
+        path: &str,
+        method_router: MethodRouter<S>,
+    ) -> Self {
+        self.table.unclassified.insert(path.to_string());
+        self.router = self.router.route(path, method_router);
+        self
+    }
+
+    /// Nest another classified router under `prefix`
+    pub fn nest(mut self, prefix: &str, other: ClassifiedRouter<S>) -> Self {
+        self.table.extend_with_prefix(prefix, other.table);
+        self.router = self.router.nest(prefix, other.router);
+        self
+    }
+
+    /// Merge another classified router at the same level
+    pub fn merge(mut self, other: ClassifiedRouter<S>) -> Self {
+        self.table.extend_with_prefix("", other.table);
+        self.router = self.router.merge(other.router);
+        self
+    }
+
+    /// Apply a transformation to the underlying router (e.g. `route_layer`)
+    pub fn map_router(
+        mut self,
+        f: impl FnOnce(Router<S>) -> Router<S>,
+    ) -> Self {
+        self.router = f(self.router);
+        self
+    }
+
+    /// Split into the axum router and its rate-limit route table
+    pub fn into_parts(self) -> (Router<S>, RouteTable) {
+        (self.router, self.table)
+    }
+}
+
+/// Join a nest prefix and a route path the way axum does:
+/// `("/budgets", "/")` becomes `"/budgets"`, `("/budgets", "/:id")`
+/// becomes `"/budgets/:id"`.
+fn join_paths(prefix: &str, path: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    if path == "/" || path.is_empty() {
+        if prefix.is_empty() {
+            "/".to_string()
+        } else {
+            prefix.to_string()
+        }
+    } else {
+        format!("{}{}", prefix, path)
+    }
+}
@@ -1,59 +1,166 @@
-//! Rate limiting service implementation using Redis
+//! Rate limiting service implementation
 
+use crate::rate_limiter::api_keys::{
+    ApiKeyStore, LOOKUP_LIMIT_PER_IP, LOOKUP_WINDOW_SECONDS,
+};
+use crate::rate_limiter::backend::{
+    InMemoryBackend, RateLimitBackend, RateLimitBackendKind, RedisBackend,
+};
 use crate::rate_limiter::config::RateLimitConfig;
+use crate::rate_limiter::metrics::{record_decision, Decision};
+use crate::rate_limiter::routes::RouteTable;
 use crate::rate_limiter::types::{
-    RateLimitError, RateLimitKey, RateLimitResult,
+    ApiKeyIdentity, DailyQuota, DegradationPolicy, RateLimitError,
+    RateLimitKey, RateLimitResult, RateLimitStatus, TransactionType,
 };
-use redis::{AsyncCommands, Client};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{error, warn};
 
 /// Additional buffer time for Redis key expiry to ensure it outlives the rate limit window.
 /// This helps prevent race conditions where a key might expire prematurely.
-const REDIS_EXPIRY_BUFFER_SECONDS: i64 = 60;
+const REDIS_EXPIRY_BUFFER_SECONDS: u64 = 60;
+
+/// Length of the daily quota window (one UTC day)
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Retry-After sent when a fail-closed transaction type is rejected
+const FAIL_CLOSED_RETRY_AFTER_SECONDS: u64 = 5;
 
-/// Rate limiting service using Redis for distributed rate limiting
+/// Outcome of incrementing a counter, given the degradation policy
+enum Counted {
+    /// Counted by the configured backend
+    Backend(u32),
+    /// Backend unavailable; counted by the local fallback limiter
+    Local(u32),
+    /// Backend unavailable; allow without counting
+    FailOpen,
+    /// Backend unavailable; reject
+    FailClosed,
+}
+
+/// Rate limiting service on top of a pluggable counter backend
 pub struct RateLimitService {
-    client: Client,
+    backend: Arc<dyn RateLimitBackend>,
+    /// Per-process counters used while the backend is unavailable
+    fallback: InMemoryBackend,
     config: RateLimitConfig,
+    /// Optional API key lookup; without it every request is limited by IP/device
+    api_keys: Option<ApiKeyStore>,
+    /// Rate-limit classes declared by the routers
+    routes: RouteTable,
 }
 
 impl RateLimitService {
-    /// Helper function to establish and test Redis connection
-    async fn test_redis_connection(
-        client: &Client,
-    ) -> Result<redis::aio::ConnectionManager, RateLimitError> {
-        let mut conn = redis::aio::ConnectionManager::new(client.clone())
-            .await
-            .map_err(|e| {
-                error!("Failed to connect to Redis: {}", e);
-                e
-            })?;
+    /// Create a new rate limiting service using the configured backend.
+    ///
+    /// An unreachable Redis at startup is not fatal: requests are handled by
+    /// the per-type degradation policies until it comes back.
+    pub async fn new(config: RateLimitConfig) -> Result<Self, RateLimitError> {
+        let backend: Arc<dyn RateLimitBackend> = match config.backend {
+            RateLimitBackendKind::Redis => {
+                let backend = RedisBackend::new(
+                    &config.redis_url,
+                    config.redis_topology.clone(),
+                    config.connection_timeout,
+                )?;
+                if let Err(e) = backend.ping().await {
+                    warn!(
+                        "Redis unavailable at startup, rate limiting degraded until it recovers: {}",
+                        e
+                    );
+                }
+                Arc::new(backend)
+            }
+            RateLimitBackendKind::Memory => {
+                let backend = InMemoryBackend::new(config.memory_shards);
+                backend.spawn_cleanup(config.memory_cleanup_interval);
+                Arc::new(backend)
+            }
+        };
 
-        // Ping Redis to verify connection
-        let _: String = redis::cmd("PING")
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| {
-                error!("Redis ping failed: {}", e);
-                e
-            })?;
-        Ok(conn)
+        tracing::info!(
+            "Rate limiting service initialized with {} backend",
+            backend.name()
+        );
+
+        let service = Self::with_backend(config, backend);
+        service
+            .fallback
+            .spawn_cleanup(service.config.memory_cleanup_interval);
+        Ok(service)
     }
 
-    /// Create a new rate limiting service
-    pub async fn new(config: RateLimitConfig) -> Result<Self, RateLimitError> {
-        let client = Client::open(config.redis_url.clone()).map_err(|e| {
-            error!("Failed to create Redis client: {}", e);
-            e
-        })?;
+    /// Create a service on top of an existing backend
+    pub fn with_backend(
+        config: RateLimitConfig,
+        backend: Arc<dyn RateLimitBackend>,
+    ) -> Self {
+        Self {
+            backend,
+            fallback: InMemoryBackend::new(config.memory_shards),
+            config,
+            api_keys: None,
+            routes: RouteTable::default(),
+        }
+    }
+
+    /// Enable per-API-key limits backed by the given key store
+    pub fn with_api_keys(mut self, api_keys: ApiKeyStore) -> Self {
+        self.api_keys = Some(api_keys);
+        self
+    }
 
-        // Test the connection
-        let _conn = Self::test_redis_connection(&client).await?;
+    /// Classify requests using the route table built alongside the routers
+    pub fn with_route_table(mut self, routes: RouteTable) -> Self {
+        self.routes = routes;
+        self
+    }
 
-        tracing::info!("Rate limiting service initialized with Redis");
+    /// Route table used to classify incoming requests
+    pub fn routes(&self) -> &RouteTable {
+        &self.routes
+    }
 
-        Ok(Self { client, config })
+    /// Name of the counter backend
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    /// Check that the counter backend answers
+    pub async fn ping(&self) -> Result<(), RateLimitError> {
+        self.backend.ping().await
+    }
+
+    /// Whether some transaction type is rejected while the backend is down
+    pub fn fails_closed(&self) -> bool {
+        TransactionType::ALL.iter().any(|&t| {
+            self.config.degradation.for_type(t) == DegradationPolicy::FailClosed
+        })
+    }
+
+    /// The remembered result of resolving `key` without touching the
+    /// database: `Some(None)` for a key known to be invalid (always, when
+    /// API keys are not enabled), `None` when it has to be looked up
+    pub fn cached_api_key(&self, key: &str) -> Option<Option<ApiKeyIdentity>> {
+        match &self.api_keys {
+            Some(store) => store.cached(key),
+            None => Some(None),
+        }
+    }
+
+    /// Resolve a presented API key.
+    ///
+    /// Returns `Ok(None)` when the key is unknown or revoked, or when API keys
+    /// are not enabled on this service.
+    pub async fn authenticate_api_key(
+        &self,
+        key: &str,
+    ) -> Result<Option<ApiKeyIdentity>, RateLimitError> {
+        match &self.api_keys {
+            Some(store) => store.authenticate(key).await,
+            None => Ok(None),
+        }
     }
 
     /// Check if a request is allowed and record it if permitted
@@ -62,90 +169,356 @@ impl RateLimitService {
         key: RateLimitKey,
     ) -> Result<RateLimitResult, RateLimitError> {
         let tx_type = key.transaction_type;
-        let main_limit = tx_type.get_limit();
+        let outcome = self.evaluate(key).await;
+        record_decision(Self::decision(&outcome), tx_type);
+
+        outcome
+    }
+
+    /// Count an uncached API key lookup against the client IP's lookup
+    /// budget (`LOOKUP_LIMIT_PER_IP`).
+    ///
+    /// The budget is separate from the IP/device request limit, so key
+    /// holders behind a busy NAT are not cut off by the NAT's traffic while
+    /// their key is looked up. Only rejections are recorded as decisions;
+    /// an allowed lookup is recorded with the request's own check.
+    pub async fn check_api_key_lookup(
+        &self,
+        key: &RateLimitKey,
+    ) -> Result<RateLimitResult, RateLimitError> {
+        let tx_type = key.transaction_type;
+        let now = Self::now()?;
+        let lookup_key = key.to_lookup_redis_key();
+
+        let count = match self
+            .count(
+                &lookup_key,
+                LOOKUP_WINDOW_SECONDS + REDIS_EXPIRY_BUFFER_SECONDS,
+                tx_type,
+            )
+            .await
+        {
+            Counted::Backend(count) | Counted::Local(count) => count,
+            Counted::FailOpen => 0,
+            Counted::FailClosed => {
+                let outcome = Ok(RateLimitResult::unavailable(
+                    now,
+                    FAIL_CLOSED_RETRY_AFTER_SECONDS,
+                    tx_type,
+                ));
+                record_decision(Self::decision(&outcome), tx_type);
+                return outcome;
+            }
+        };
+
+        let mut result = if count > LOOKUP_LIMIT_PER_IP {
+            let result = RateLimitResult::rate_limited(
+                now + LOOKUP_WINDOW_SECONDS,
+                LOOKUP_WINDOW_SECONDS,
+                tx_type,
+            );
+            record_decision(Decision::Denied, tx_type);
+            result
+        } else {
+            RateLimitResult::allowed(
+                LOOKUP_LIMIT_PER_IP - count,
+                now + LOOKUP_WINDOW_SECONDS,
+                tx_type,
+            )
+        };
+        result.limit = LOOKUP_LIMIT_PER_IP;
+        Ok(result)
+    }
+
+    /// Decision metric label for the outcome of a check
+    fn decision(outcome: &Result<RateLimitResult, RateLimitError>) -> Decision {
+        match outcome {
+            Ok(result) if result.allowed => match result.degradation {
+                Some(_) => Decision::Degraded,
+                None => Decision::Allowed,
+            },
+            Ok(result) => match result.degradation {
+                Some(DegradationPolicy::FailClosed) => Decision::Unavailable,
+                _ => Decision::Denied,
+            },
+            // The middleware lets the request through
+            Err(_) => Decision::Degraded,
+        }
+    }
 
-        let now = SystemTime::now()
+    /// Current Unix time in seconds
+    fn now() -> Result<u64, RateLimitError> {
+        SystemTime::now()
             .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
             .map_err(|e| {
                 error!("Failed to get current time: {}", e);
                 RateLimitError::RedisError(redis::RedisError::from((
                     redis::ErrorKind::IoError,
                     "SystemTime error",
                 )))
-            })?
-            .as_secs();
-        let window_seconds = tx_type.get_window_seconds();
+            })
+    }
 
-        let mut conn = match Self::test_redis_connection(&self.client).await {
-            Ok(c) => c,
-            Err(e) => {
-                if self.config.graceful_degradation {
-                    warn!("Redis connection failed, allowing request: {}", e);
-                    return Ok(RateLimitResult::allowed(
-                        main_limit - 1, // Assume one request used
-                        now + window_seconds,
-                        tx_type,
-                    ));
-                }
-                return Err(e);
+    /// Current counters for an IP/device pair, one per transaction type
+    pub async fn inspect(
+        &self,
+        ip: &str,
+        device_id: Option<&str>,
+    ) -> Result<Vec<RateLimitStatus>, RateLimitError> {
+        let mut statuses = Vec::with_capacity(TransactionType::ALL.len());
+
+        for tx_type in TransactionType::ALL {
+            let key = Self::admin_key(ip, device_id, tx_type);
+            let redis_key = key.to_redis_key();
+            let state = self.backend.peek(&redis_key).await?;
+            let count = state.map_or(0, |s| s.count);
+
+            statuses.push(RateLimitStatus {
+                transaction_type: tx_type,
+                key: redis_key,
+                limit: key.limit(),
+                count,
+                remaining: key.limit().saturating_sub(count),
+                reset_in_seconds: state.and_then(|s| s.ttl_seconds),
+            });
+        }
+
+        Ok(statuses)
+    }
+
+    /// Reset all counters for an IP/device pair; returns how many existed
+    pub async fn reset(
+        &self,
+        ip: &str,
+        device_id: Option<&str>,
+    ) -> Result<u32, RateLimitError> {
+        let mut deleted = 0;
+        for tx_type in TransactionType::ALL {
+            let key = Self::admin_key(ip, device_id, tx_type).to_redis_key();
+            if self.backend.reset(&key).await? {
+                deleted += 1;
             }
-        };
+        }
 
-        let main_key = key.to_redis_key();
+        tracing::info!(
+            "Reset {} rate limit counters for {} / {}",
+            deleted,
+            ip,
+            device_id.unwrap_or("unknown")
+        );
+        Ok(deleted)
+    }
+
+    fn admin_key(
+        ip: &str,
+        device_id: Option<&str>,
+        tx_type: TransactionType,
+    ) -> RateLimitKey {
+        RateLimitKey::new(
+            ip.to_string(),
+            device_id.map(str::to_string),
+            tx_type,
+        )
+    }
+
+    /// Run the rate limit check against the backend
+    async fn evaluate(
+        &self,
+        key: RateLimitKey,
+    ) -> Result<RateLimitResult, RateLimitError> {
+        let tx_type = key.transaction_type;
+        let main_limit = key.limit();
+
+        let now = Self::now()?;
         let window_seconds = tx_type.get_window_seconds();
 
+        let main_key = key.to_redis_key();
+
         // Atomically increment and get the new count
-        let current_count: u32 = match conn.incr(&main_key, 1).await {
-            Ok(count) => count,
-            Err(e) => {
-                error!(
-                    "Failed to increment rate limit counter for key {}: {}",
-                    main_key, e
-                );
-                if self.config.graceful_degradation {
-                    warn!("Allowing request due to graceful degradation mode");
-                    // Return allowed result with conservative remaining count
-                    return Ok(RateLimitResult::allowed(
+        let (current_count, degradation) = match self
+            .count(
+                &main_key,
+                window_seconds + REDIS_EXPIRY_BUFFER_SECONDS,
+                tx_type,
+            )
+            .await
+        {
+            Counted::Backend(count) => (count, None),
+            Counted::Local(count) => {
+                (count, Some(DegradationPolicy::LocalFallback))
+            }
+            Counted::FailOpen => {
+                // Return allowed result with conservative remaining count
+                return Ok(Self::with_identity(
+                    RateLimitResult::allowed(
                         main_limit - 1, // Assume one request used
                         now + window_seconds,
                         tx_type,
-                    ));
-                }
-                return Err(RateLimitError::RedisError(e));
+                    )
+                    .degraded(DegradationPolicy::FailOpen),
+                    &key,
+                ));
+            }
+            Counted::FailClosed => {
+                return Ok(Self::with_identity(
+                    RateLimitResult::unavailable(
+                        now,
+                        FAIL_CLOSED_RETRY_AFTER_SECONDS,
+                        tx_type,
+                    ),
+                    &key,
+                ));
             }
         };
 
-        // Set expiry for automatic cleanup (only on first increment)
-        if current_count == 1 {
-            if let Err(e) = conn
-                .expire::<&str, i64>(
-                    &main_key,
-                    window_seconds as i64 + REDIS_EXPIRY_BUFFER_SECONDS,
-                )
-                .await
-            {
-                // Log the error but don't fail the request - Redis will eventually clean up
-                warn!(
-                    "Failed to set expiry for rate limit key {}: {}",
-                    main_key, e
-                );
-            }
+        // A limit of N allows N requests per window, like the daily quota
+        if current_count > main_limit {
+            // Request exceeded limit, return rate limited result
+            let mut result = Self::with_identity(
+                RateLimitResult::rate_limited(
+                    now + window_seconds,
+                    window_seconds,
+                    tx_type,
+                ),
+                &key,
+            );
+            result.degradation = degradation;
+            return Ok(result);
         }
 
-        if current_count >= main_limit {
-            // Request exceeded limit, return rate limited result
-            Ok(RateLimitResult::rate_limited(
-                now + window_seconds,
-                window_seconds,
-                tx_type,
-            ))
-        } else {
-            // Request allowed, return success result
-            Ok(RateLimitResult::allowed(
+        let mut result = Self::with_identity(
+            RateLimitResult::allowed(
                 main_limit - current_count,
                 now + window_seconds,
                 tx_type,
-            ))
+            ),
+            &key,
+        );
+        result.degradation = degradation;
+
+        // API keys also count against a daily quota shared by all transaction types
+        match key.api_key {
+            Some(identity) => {
+                self.check_daily_quota(&key, identity, now, result).await
+            }
+            None => Ok(result),
+        }
+    }
+
+    /// Record a request against the key's daily quota and fold the outcome
+    /// into the per-window result.
+    async fn check_daily_quota(
+        &self,
+        key: &RateLimitKey,
+        identity: ApiKeyIdentity,
+        now: u64,
+        mut result: RateLimitResult,
+    ) -> Result<RateLimitResult, RateLimitError> {
+        let day = now / SECONDS_PER_DAY;
+        let reset_time = (day + 1) * SECONDS_PER_DAY;
+        let daily_limit = identity.plan.daily_quota();
+        let daily_key = match key.to_daily_redis_key(day) {
+            Some(daily_key) => daily_key,
+            None => return Ok(result),
+        };
+
+        let daily_count = match self
+            .count(
+                &daily_key,
+                SECONDS_PER_DAY + REDIS_EXPIRY_BUFFER_SECONDS,
+                key.transaction_type,
+            )
+            .await
+        {
+            Counted::Backend(count) => count,
+            Counted::Local(count) => {
+                result.degradation = Some(DegradationPolicy::LocalFallback);
+                count
+            }
+            // Keep the per-window decision; skip the daily quota
+            Counted::FailOpen => {
+                return Ok(result.degraded(DegradationPolicy::FailOpen));
+            }
+            Counted::FailClosed => {
+                return Ok(Self::with_identity(
+                    RateLimitResult::unavailable(
+                        now,
+                        FAIL_CLOSED_RETRY_AFTER_SECONDS,
+                        key.transaction_type,
+                    ),
+                    key,
+                ));
+            }
+        };
+
+        if daily_count > daily_limit {
+            let quota = DailyQuota {
+                limit: daily_limit,
+                remaining: 0,
+                reset_time,
+            };
+            let mut denied = Self::with_identity(
+                RateLimitResult::rate_limited(
+                    reset_time,
+                    reset_time - now,
+                    key.transaction_type,
+                ),
+                key,
+            )
+            .with_daily_quota(quota);
+            denied.degradation = result.degradation;
+            return Ok(denied);
+        }
+
+        Ok(result.with_daily_quota(DailyQuota {
+            limit: daily_limit,
+            remaining: daily_limit - daily_count,
+            reset_time,
+        }))
+    }
+
+    /// Increment a counter in the backend, applying the transaction type's
+    /// degradation policy when the backend fails
+    async fn count(
+        &self,
+        counter_key: &str,
+        expiry_seconds: u64,
+        tx_type: TransactionType,
+    ) -> Counted {
+        let error =
+            match self.backend.increment(counter_key, expiry_seconds).await {
+                Ok(count) => return Counted::Backend(count),
+                Err(e) => e,
+            };
+
+        let policy = self.config.degradation.for_type(tx_type);
+        warn!(
+            "Failed to increment rate limit counter for key {}: {}; applying {} policy",
+            counter_key, error, policy
+        );
+
+        match policy {
+            DegradationPolicy::FailOpen => Counted::FailOpen,
+            DegradationPolicy::FailClosed => Counted::FailClosed,
+            DegradationPolicy::LocalFallback => {
+                match self.fallback.increment(counter_key, expiry_seconds).await
+                {
+                    Ok(count) => Counted::Local(count),
+                    Err(_) => Counted::FailOpen,
+                }
+            }
+        }
+    }
+
+    /// Attach plan information from the key to a result
+    fn with_identity(
+        result: RateLimitResult,
+        key: &RateLimitKey,
+    ) -> RateLimitResult {
+        match key.api_key {
+            Some(identity) => result.with_plan(identity.plan, key.limit()),
+            None => result,
         }
     }
 }
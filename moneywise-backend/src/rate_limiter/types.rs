@@ -1,7 +1,10 @@
 //! Rate limiting types and enums
 
+use crate::rate_limiter::plans::PlanTier;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
 
 // Import generated rate limit configuration
 mod generated {
@@ -10,6 +13,7 @@ mod generated {
 
 /// Transaction type for budget operations with specific rate limits
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[allow(clippy::enum_variant_names)]
 pub enum TransactionType {
     /// Budget modification operations (create, update, delete)
     BudgetModification,
@@ -24,6 +28,13 @@ pub enum TransactionType {
 }
 
 impl TransactionType {
+    /// Every transaction type, in order of increasing limit
+    pub const ALL: [TransactionType; 3] = [
+        Self::BudgetModification,
+        Self::BudgetRead,
+        Self::BudgetOverview,
+    ];
+
     /// Get the rate limit for budget operations
     pub fn get_limit(&self) -> u32 {
         match self {
@@ -60,12 +71,21 @@ impl fmt::Display for TransactionType {
     }
 }
 
+/// Authenticated API key attached to a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ApiKeyIdentity {
+    pub key_id: Uuid,
+    pub plan: PlanTier,
+}
+
 /// Rate limit key components for Redis storage
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RateLimitKey {
     pub ip_address: String,
     pub device_id: Option<String>,
     pub transaction_type: TransactionType,
+    /// When set, limits are tracked per key instead of per IP/device
+    pub api_key: Option<ApiKeyIdentity>,
 }
 
 impl RateLimitKey {
@@ -79,6 +99,21 @@ impl RateLimitKey {
             ip_address,
             device_id,
             transaction_type,
+            api_key: None,
+        }
+    }
+
+    /// Switch this key to per-API-key tracking
+    pub fn with_api_key(mut self, identity: ApiKeyIdentity) -> Self {
+        self.api_key = Some(identity);
+        self
+    }
+
+    /// Request limit for the current window, honoring the API key's plan
+    pub fn limit(&self) -> u32 {
+        match self.api_key {
+            Some(identity) => identity.plan.limit_for(self.transaction_type),
+            None => self.transaction_type.get_limit(),
         }
     }
 
@@ -86,13 +121,82 @@ impl RateLimitKey {
     ///
     /// Uses Display trait for stable string representation.
     /// Example: "rate_limit:192.168.1.1:device123:budget_modification"
+    /// or, with an API key: "rate_limit:key:{key_id}:budget_modification"
     pub fn to_redis_key(&self) -> String {
+        if let Some(identity) = &self.api_key {
+            return format!(
+                "rate_limit:key:{}:{}",
+                identity.key_id, self.transaction_type
+            );
+        }
         let device_part = self.device_id.as_deref().unwrap_or("unknown");
         format!(
             "rate_limit:{}:{}:{}",
             self.ip_address, device_part, self.transaction_type
         )
     }
+
+    /// Convert to Redis key for the API key lookups made for this IP.
+    ///
+    /// Shared by every device and transaction type behind the IP.
+    /// Example: "rate_limit:lookup:192.168.1.1"
+    pub fn to_lookup_redis_key(&self) -> String {
+        format!("rate_limit:lookup:{}", self.ip_address)
+    }
+
+    /// Convert to Redis key for the daily quota of an API key.
+    ///
+    /// `day` is the number of days since the Unix epoch (UTC), so the key
+    /// rolls over at midnight UTC.
+    /// Example: "rate_limit:daily:{key_id}:20347"
+    pub fn to_daily_redis_key(&self, day: u64) -> Option<String> {
+        self.api_key.map(|identity| {
+            format!("rate_limit:daily:{}:{}", identity.key_id, day)
+        })
+    }
+}
+
+/// What to do with a request when the counter backend is unavailable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DegradationPolicy {
+    /// Allow the request without counting it
+    FailOpen,
+    /// Reject the request with 503 Service Unavailable
+    FailClosed,
+    /// Count the request in a per-process fallback limiter (approximate)
+    LocalFallback,
+}
+
+impl fmt::Display for DegradationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FailOpen => write!(f, "fail_open"),
+            Self::FailClosed => write!(f, "fail_closed"),
+            Self::LocalFallback => write!(f, "local_fallback"),
+        }
+    }
+}
+
+impl FromStr for DegradationPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fail_open" | "open" => Ok(Self::FailOpen),
+            "fail_closed" | "closed" => Ok(Self::FailClosed),
+            "local_fallback" | "local" => Ok(Self::LocalFallback),
+            other => Err(format!("Unknown degradation policy '{}'", other)),
+        }
+    }
+}
+
+/// Daily quota state reported alongside the per-window result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyQuota {
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset_time: u64, // Unix timestamp (next midnight UTC)
 }
 
 /// Rate limit check result
@@ -103,6 +207,12 @@ pub struct RateLimitResult {
     pub reset_time: u64,          // Unix timestamp
     pub retry_after: Option<u64>, // Seconds to wait
     pub limit_type: TransactionType,
+    /// Effective per-window limit (plan-specific when an API key is used)
+    pub limit: u32,
+    pub plan: Option<PlanTier>,
+    pub daily: Option<DailyQuota>,
+    /// Policy applied because the backend was unavailable, if any
+    pub degradation: Option<DegradationPolicy>,
 }
 
 impl RateLimitResult {
@@ -118,6 +228,10 @@ impl RateLimitResult {
             reset_time,
             retry_after: None,
             limit_type,
+            limit: limit_type.get_limit(),
+            plan: None,
+            daily: None,
+            degradation: None,
         }
     }
 
@@ -133,8 +247,54 @@ impl RateLimitResult {
             reset_time,
             retry_after: Some(retry_after),
             limit_type,
+            limit: limit_type.get_limit(),
+            plan: None,
+            daily: None,
+            degradation: None,
         }
     }
+
+    /// Attach the plan and its per-window limit
+    pub fn with_plan(mut self, plan: PlanTier, limit: u32) -> Self {
+        self.plan = Some(plan);
+        self.limit = limit;
+        self
+    }
+
+    /// Create a result for a request rejected because the backend is down
+    /// and the transaction type fails closed
+    pub fn unavailable(
+        now: u64,
+        retry_after: u64,
+        limit_type: TransactionType,
+    ) -> Self {
+        Self::rate_limited(now + retry_after, retry_after, limit_type)
+            .degraded(DegradationPolicy::FailClosed)
+    }
+
+    /// Record that the backend was unavailable and `policy` was applied
+    pub fn degraded(mut self, policy: DegradationPolicy) -> Self {
+        self.degradation = Some(policy);
+        self
+    }
+
+    /// Attach daily quota state
+    pub fn with_daily_quota(mut self, daily: DailyQuota) -> Self {
+        self.daily = Some(daily);
+        self
+    }
+}
+
+/// Current counter for one transaction type, as shown by the admin API
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitStatus {
+    pub transaction_type: TransactionType,
+    pub key: String,
+    pub limit: u32,
+    pub count: u32,
+    pub remaining: u32,
+    /// Seconds until the counter resets; `None` when no counter exists
+    pub reset_in_seconds: Option<u64>,
 }
 
 /// Rate limit error types
@@ -142,4 +302,6 @@ impl RateLimitResult {
 pub enum RateLimitError {
     #[error("Redis connection failed: {0}")]
     RedisError(#[from] redis::RedisError),
+    #[error("API key lookup failed: {0}")]
+    DatabaseError(#[from] sqlx::Error),
 }
@@ -0,0 +1,490 @@
+//! Redis deployment topologies shared by the cache and the rate limiter.
+//!
+//! - Standalone: a single server at `REDIS_URL` (default)
+//! - Sentinel: the primary of `REDIS_SENTINEL_MASTER` is discovered through
+//!   the sentinels in `REDIS_SENTINEL_URLS`, and rediscovered after a
+//!   failover (connection loss or a `READONLY` reply from a demoted primary)
+//! - Cluster: slots are discovered from the seed nodes in
+//!   `REDIS_CLUSTER_URLS`; `MOVED`/`ASK` redirections are followed by the
+//!   client
+//!
+//! The mode is selected with `REDIS_MODE`. Address lists fall back to
+//! `REDIS_URL`, whose credentials and database are also used for the
+//! primary found through Sentinel.
+//!
+//! In Cluster mode a command (or Lua script) may only touch keys of one
+//! hash slot; keys that are written together share a `{hash tag}`.
+
+use redis::aio::{ConnectionLike, ConnectionManager, PubSub};
+use redis::cluster::ClusterClient;
+use redis::cluster_async::ClusterConnection;
+use redis::cluster_routing::{RoutingInfo, SingleNodeRoutingInfo};
+use redis::sentinel::{
+    SentinelClient, SentinelNodeConnectionInfo, SentinelServerType,
+};
+use redis::{
+    Client, Cmd, ErrorKind, FromRedisValue, IntoConnectionInfo, Pipeline,
+    RedisError, RedisFuture, RedisResult, Value,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
+
+use crate::connections::parse_env_with_default;
+
+/// Master name used when `REDIS_SENTINEL_MASTER` is not set
+pub const DEFAULT_SENTINEL_MASTER: &str = "mymaster";
+
+/// Number of hash slots in a Redis Cluster
+pub const CLUSTER_SLOTS: u16 = 16384;
+
+/// `REDIS_MODE` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedisMode {
+    Standalone,
+    Sentinel,
+    Cluster,
+}
+
+impl fmt::Display for RedisMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Standalone => write!(f, "standalone"),
+            Self::Sentinel => write!(f, "sentinel"),
+            Self::Cluster => write!(f, "cluster"),
+        }
+    }
+}
+
+impl FromStr for RedisMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "standalone" => Ok(Self::Standalone),
+            "sentinel" => Ok(Self::Sentinel),
+            "cluster" => Ok(Self::Cluster),
+            other => Err(format!("Unknown Redis mode '{}'", other)),
+        }
+    }
+}
+
+/// How to reach Redis, in addition to `REDIS_URL`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum RedisTopology {
+    /// Connect to `REDIS_URL` directly
+    Standalone,
+    /// Ask `sentinels` for the current primary of `master_name`
+    Sentinel {
+        sentinels: Vec<String>,
+        master_name: String,
+    },
+    /// Discover the cluster from the `nodes` seed list
+    Cluster { nodes: Vec<String> },
+}
+
+impl Default for RedisTopology {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+impl RedisTopology {
+    /// Read `REDIS_MODE`, `REDIS_SENTINEL_URLS`, `REDIS_SENTINEL_MASTER` and
+    /// `REDIS_CLUSTER_URLS`; invalid values fall back to standalone.
+    pub fn from_env() -> Self {
+        match parse_env_with_default("REDIS_MODE", RedisMode::Standalone) {
+            RedisMode::Standalone => Self::Standalone,
+            RedisMode::Sentinel => Self::Sentinel {
+                sentinels: url_list_from_env("REDIS_SENTINEL_URLS"),
+                master_name: parse_env_with_default(
+                    "REDIS_SENTINEL_MASTER",
+                    DEFAULT_SENTINEL_MASTER.to_string(),
+                ),
+            },
+            RedisMode::Cluster => Self::Cluster {
+                nodes: url_list_from_env("REDIS_CLUSTER_URLS"),
+            },
+        }
+    }
+
+    /// Mode of this topology
+    pub fn mode(&self) -> RedisMode {
+        match self {
+            Self::Standalone => RedisMode::Standalone,
+            Self::Sentinel { .. } => RedisMode::Sentinel,
+            Self::Cluster { .. } => RedisMode::Cluster,
+        }
+    }
+
+    /// Check the configured addresses without connecting
+    pub fn validate(&self, redis_url: &str) -> RedisResult<()> {
+        match self {
+            Self::Standalone => Client::open(redis_url).map(|_| ()),
+            Self::Sentinel { master_name, .. } => {
+                self.sentinel_client(redis_url, master_name).map(|_| ())
+            }
+            Self::Cluster { .. } => self.cluster_client(redis_url).map(|_| ()),
+        }
+    }
+
+    /// Open a connection that follows this topology
+    pub async fn connect(
+        &self,
+        redis_url: &str,
+    ) -> RedisResult<RedisConnection> {
+        let connection = match self {
+            Self::Standalone => {
+                let client = Client::open(redis_url)?;
+                RedisConnection::Standalone(
+                    ConnectionManager::new(client).await?,
+                )
+            }
+            Self::Sentinel { master_name, .. } => {
+                let mut sentinel =
+                    self.sentinel_client(redis_url, master_name)?;
+                let primary = primary_connection(&mut sentinel).await?;
+                RedisConnection::Sentinel(SentinelConnection {
+                    sentinel: Arc::new(tokio::sync::Mutex::new(sentinel)),
+                    primary: Arc::new(RwLock::new(primary)),
+                    generation: Arc::new(AtomicU64::new(0)),
+                })
+            }
+            Self::Cluster { .. } => RedisConnection::Cluster(
+                self.cluster_client(redis_url)?
+                    .get_async_connection()
+                    .await?,
+            ),
+        };
+        info!("Connected to Redis ({})", self.mode());
+        Ok(connection)
+    }
+
+    /// Open a pub/sub connection.
+    ///
+    /// Sentinel subscribes on the current primary; Cluster subscribes on the
+    /// first reachable seed node, since `PUBLISH` reaches every node.
+    pub async fn pubsub(&self, redis_url: &str) -> RedisResult<PubSub> {
+        match self {
+            Self::Standalone => {
+                Client::open(redis_url)?.get_async_pubsub().await
+            }
+            Self::Sentinel { master_name, .. } => {
+                self.sentinel_client(redis_url, master_name)?
+                    .async_get_client()
+                    .await?
+                    .get_async_pubsub()
+                    .await
+            }
+            Self::Cluster { nodes } => {
+                let mut last_error = None;
+                for node in addresses(nodes, redis_url) {
+                    match Client::open(node)?.get_async_pubsub().await {
+                        Ok(pubsub) => return Ok(pubsub),
+                        Err(e) => last_error = Some(e),
+                    }
+                }
+                Err(last_error.unwrap_or_else(|| {
+                    (ErrorKind::ClientError, "No cluster nodes configured")
+                        .into()
+                }))
+            }
+        }
+    }
+
+    fn sentinel_client(
+        &self,
+        redis_url: &str,
+        master_name: &str,
+    ) -> RedisResult<SentinelClient> {
+        let Self::Sentinel { sentinels, .. } = self else {
+            unreachable!("sentinel_client called on a non-sentinel topology")
+        };
+        // Credentials and database of REDIS_URL apply to the primary
+        let primary = redis_url.into_connection_info()?;
+        SentinelClient::build(
+            addresses(sentinels, redis_url),
+            master_name.to_string(),
+            Some(SentinelNodeConnectionInfo {
+                tls_mode: None,
+                redis_connection_info: Some(primary.redis),
+            }),
+            SentinelServerType::Master,
+        )
+    }
+
+    fn cluster_client(&self, redis_url: &str) -> RedisResult<ClusterClient> {
+        let Self::Cluster { nodes } = self else {
+            unreachable!("cluster_client called on a non-cluster topology")
+        };
+        ClusterClient::new(addresses(nodes, redis_url))
+    }
+}
+
+/// Comma-separated URLs from `var`, empty when unset
+fn url_list_from_env(var: &str) -> Vec<String> {
+    std::env::var(var)
+        .map(|value| parse_url_list(&value))
+        .unwrap_or_default()
+}
+
+/// Split a comma-separated URL list, ignoring blanks
+pub fn parse_url_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `urls`, or `redis_url` alone when the list is empty
+fn addresses<'a>(urls: &'a [String], redis_url: &'a str) -> Vec<&'a str> {
+    if urls.is_empty() {
+        vec![redis_url]
+    } else {
+        urls.iter().map(String::as_str).collect()
+    }
+}
+
+async fn primary_connection(
+    sentinel: &mut SentinelClient,
+) -> RedisResult<ConnectionManager> {
+    let client = sentinel.async_get_client().await?;
+    ConnectionManager::new(client).await
+}
+
+/// Address of one cluster node
+pub type NodeAddress = (String, u16);
+
+/// Connection to Redis in any topology; cheap to clone
+#[derive(Clone)]
+pub enum RedisConnection {
+    Standalone(ConnectionManager),
+    Sentinel(SentinelConnection),
+    Cluster(ClusterConnection),
+}
+
+impl RedisConnection {
+    /// Nodes to run node-local commands such as `SCAN` on: every cluster
+    /// primary, or `None` for the single server of the other topologies
+    pub async fn scan_targets(&self) -> RedisResult<Vec<Option<NodeAddress>>> {
+        let Self::Cluster(conn) = self else {
+            return Ok(vec![None]);
+        };
+        let nodes: String = String::from_redis_value(
+            &conn
+                .clone()
+                .route_command(
+                    redis::cmd("CLUSTER").arg("NODES"),
+                    RoutingInfo::SingleNode(SingleNodeRoutingInfo::Random),
+                )
+                .await?,
+        )?;
+        Ok(parse_cluster_primaries(&nodes)
+            .into_iter()
+            .map(Some)
+            .collect())
+    }
+
+    /// Run `cmd` on `node` (from `scan_targets`), or through the normal
+    /// routing when `node` is `None`
+    pub async fn query_node<T: FromRedisValue>(
+        &self,
+        node: Option<&NodeAddress>,
+        cmd: &Cmd,
+    ) -> RedisResult<T> {
+        match (self, node) {
+            (Self::Cluster(conn), Some((host, port))) => {
+                let value = conn
+                    .clone()
+                    .route_command(
+                        cmd,
+                        RoutingInfo::SingleNode(
+                            SingleNodeRoutingInfo::ByAddress {
+                                host: host.clone(),
+                                port: *port,
+                            },
+                        ),
+                    )
+                    .await?;
+                T::from_redis_value(&value)
+            }
+            _ => cmd.query_async(&mut self.clone()).await,
+        }
+    }
+}
+
+impl ConnectionLike for RedisConnection {
+    fn req_packed_command<'a>(
+        &'a mut self,
+        cmd: &'a Cmd,
+    ) -> RedisFuture<'a, Value> {
+        match self {
+            Self::Standalone(conn) => conn.req_packed_command(cmd),
+            Self::Cluster(conn) => conn.req_packed_command(cmd),
+            Self::Sentinel(conn) => Box::pin(async move {
+                let (generation, mut primary) = conn.primary();
+                let result = primary.req_packed_command(cmd).await;
+                conn.check(generation, result).await
+            }),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            Self::Standalone(conn) => {
+                conn.req_packed_commands(cmd, offset, count)
+            }
+            Self::Cluster(conn) => conn.req_packed_commands(cmd, offset, count),
+            Self::Sentinel(conn) => Box::pin(async move {
+                let (generation, mut primary) = conn.primary();
+                let result =
+                    primary.req_packed_commands(cmd, offset, count).await;
+                conn.check(generation, result).await
+            }),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            Self::Standalone(conn) => conn.get_db(),
+            Self::Cluster(conn) => conn.get_db(),
+            Self::Sentinel(conn) => conn.primary().1.get_db(),
+        }
+    }
+}
+
+/// Connection to the primary discovered through Sentinel
+#[derive(Clone)]
+pub struct SentinelConnection {
+    sentinel: Arc<tokio::sync::Mutex<SentinelClient>>,
+    primary: Arc<RwLock<ConnectionManager>>,
+    /// Bumped on every rediscovery, so concurrent failures rediscover once
+    generation: Arc<AtomicU64>,
+}
+
+impl SentinelConnection {
+    fn primary(&self) -> (u64, ConnectionManager) {
+        let primary = self.primary.read().unwrap_or_else(|e| e.into_inner());
+        (self.generation.load(Ordering::Acquire), primary.clone())
+    }
+
+    /// Rediscover the primary if `result` suggests a failover; the error
+    /// itself is returned for the caller's retry logic
+    async fn check<T>(
+        &self,
+        generation: u64,
+        result: RedisResult<T>,
+    ) -> RedisResult<T> {
+        if let Err(e) = &result {
+            if is_failover_error(e) {
+                self.rediscover(generation).await;
+            }
+        }
+        result
+    }
+
+    async fn rediscover(&self, generation: u64) {
+        let mut sentinel = self.sentinel.lock().await;
+        if self.generation.load(Ordering::Acquire) != generation {
+            return; // Another request already switched primaries
+        }
+        match primary_connection(&mut sentinel).await {
+            Ok(primary) => {
+                *self.primary.write().unwrap_or_else(|e| e.into_inner()) =
+                    primary;
+                self.generation.fetch_add(1, Ordering::AcqRel);
+                info!("Reconnected to the Redis primary reported by Sentinel");
+            }
+            Err(e) => warn!("Failed to rediscover the Redis primary: {}", e),
+        }
+    }
+}
+
+/// Errors after which the Sentinel primary may have changed
+fn is_failover_error(error: &RedisError) -> bool {
+    error.is_io_error()
+        || error.is_connection_dropped()
+        || error.is_connection_refusal()
+        || error.kind() == ErrorKind::ReadOnly
+}
+
+/// Addresses of the primaries serving slots, from `CLUSTER NODES` output
+///
+/// Each line is `<id> <ip:port@cport[,hostname]> <flags> <master> ...
+/// <slot ranges>`; failed primaries and primaries without slots are skipped.
+pub fn parse_cluster_primaries(nodes: &str) -> Vec<NodeAddress> {
+    nodes
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let flags = fields.get(2)?;
+            let is_serving = flags.split(',').any(|f| f == "master")
+                && !flags.split(',').any(|f| f.starts_with("fail"))
+                && fields.len() > 8;
+            if !is_serving {
+                return None;
+            }
+            let address = fields[1].split(['@', ',']).next()?;
+            let (host, port) = address.rsplit_once(':')?;
+            if host.is_empty() {
+                return None;
+            }
+            Some((host.to_string(), port.parse().ok()?))
+        })
+        .collect()
+}
+
+/// Cluster hash slot of `key`: CRC16 of its `{hash tag}` if it has a
+/// non-empty one, else of the whole key
+pub fn hash_slot(key: &str) -> u16 {
+    let bytes = key.as_bytes();
+    let hashed = bytes
+        .iter()
+        .position(|&b| b == b'{')
+        .and_then(|open| {
+            let rest = &bytes[open + 1..];
+            let close = rest.iter().position(|&b| b == b'}')?;
+            (close > 0).then(|| &rest[..close])
+        })
+        .unwrap_or(bytes);
+    crc16(hashed) % CLUSTER_SLOTS
+}
+
+/// Split `keys` into groups of one hash slot each, ordered by slot, so
+/// multi-key commands such as `DEL` can run on a cluster
+pub fn group_by_slot<'a>(keys: &[&'a str]) -> Vec<Vec<&'a str>> {
+    let mut groups: BTreeMap<u16, Vec<&'a str>> = BTreeMap::new();
+    for &key in keys {
+        groups.entry(hash_slot(key)).or_default().push(key);
+    }
+    groups.into_values().collect()
+}
+
+/// CRC16-CCITT (XMODEM), as used by Redis Cluster
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
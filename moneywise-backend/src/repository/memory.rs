@@ -0,0 +1,260 @@
+//! In-process budget repository.
+//!
+//! Keeps category groups, categories and budgets in mutex-guarded maps and
+//! answers queries the same way as the SQL in `postgres.rs`, including the
+//! unique (year, month, category) and category foreign key constraints.
+//! Data lives as long as the repository; there is no persistence.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, Result},
+    models::{Budget, BudgetOverviewApi, CategoryBudgetApi, CategoryBudgetRow},
+    repository::{
+        empty_overview, overview_from_totals, BudgetRepository, NewBudget,
+        DUPLICATE_BUDGET, UNKNOWN_CATEGORY,
+    },
+};
+
+/// Sort key of categories without a group (as in the SQL query)
+const UNGROUPED_SORT_ORDER: i32 = 999;
+
+#[derive(Debug, Clone)]
+struct CategoryGroup {
+    name: String,
+    color: String,
+    sort_order: Option<i32>,
+}
+
+#[derive(Debug, Clone)]
+struct Category {
+    name: String,
+    color: String,
+    group_id: Option<Uuid>,
+}
+
+#[derive(Debug, Default)]
+struct Tables {
+    groups: HashMap<Uuid, CategoryGroup>,
+    categories: HashMap<Uuid, Category>,
+    budgets: HashMap<Uuid, Budget>,
+}
+
+/// `BudgetRepository` over in-process tables
+#[derive(Debug, Default)]
+pub struct InMemoryBudgetRepository {
+    tables: Mutex<Tables>,
+}
+
+impl InMemoryBudgetRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a category group; returns its id
+    pub fn add_category_group(
+        &self,
+        name: &str,
+        color: &str,
+        sort_order: Option<i32>,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        self.lock().groups.insert(
+            id,
+            CategoryGroup {
+                name: name.to_string(),
+                color: color.to_string(),
+                sort_order,
+            },
+        );
+        id
+    }
+
+    /// Add a category, optionally in a group; returns its id
+    pub fn add_category(
+        &self,
+        name: &str,
+        color: &str,
+        group_id: Option<Uuid>,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        self.lock().categories.insert(
+            id,
+            Category {
+                name: name.to_string(),
+                color: color.to_string(),
+                group_id,
+            },
+        );
+        id
+    }
+
+    /// Insert or replace a budget as is (e.g. with `spent` already set),
+    /// bypassing the constraints checked by `create`
+    pub fn insert_budget(&self, budget: Budget) {
+        self.lock().budgets.insert(budget.id, budget);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Tables> {
+        // A panic while holding the lock leaves the maps consistent
+        self.tables.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Budgets of a month, optionally in one currency
+fn month_budgets<'a>(
+    tables: &'a Tables,
+    month: i16,
+    year: i32,
+    currency: Option<&'a str>,
+) -> impl Iterator<Item = &'a Budget> {
+    tables.budgets.values().filter(move |b| {
+        b.month == month
+            && b.year == year
+            && currency.is_none_or(|c| b.currency == c)
+    })
+}
+
+#[async_trait]
+impl BudgetRepository for InMemoryBudgetRepository {
+    async fn overview(
+        &self,
+        month: i16,
+        year: i32,
+        currency: Option<&str>,
+    ) -> Result<BudgetOverviewApi> {
+        let tables = self.lock();
+
+        // Totals per currency; the first currency wins, as in the SQL query
+        let mut totals: BTreeMap<&str, [Decimal; 3]> = BTreeMap::new();
+        for budget in month_budgets(&tables, month, year, currency) {
+            let sums = totals.entry(budget.currency.trim()).or_default();
+            sums[0] += budget.planned;
+            sums[1] += budget.spent;
+            sums[2] += budget.carryover;
+        }
+
+        let first = totals.into_iter().next();
+        Ok(match first {
+            Some((currency, [planned, spent, carryover])) => {
+                overview_from_totals(
+                    planned,
+                    spent,
+                    carryover,
+                    currency.to_string(),
+                )
+            }
+            None => empty_overview(currency),
+        })
+    }
+
+    async fn category_budgets(
+        &self,
+        month: i16,
+        year: i32,
+        currency: Option<&str>,
+    ) -> Result<Vec<CategoryBudgetApi>> {
+        let tables = self.lock();
+
+        let mut rows: Vec<(i32, CategoryBudgetRow)> = Vec::new();
+        for budget in month_budgets(&tables, month, year, currency) {
+            let Some(category) = tables.categories.get(&budget.category_id)
+            else {
+                continue;
+            };
+            let group = category.group_id.and_then(|id| tables.groups.get(&id));
+
+            rows.push((
+                group
+                    .and_then(|g| g.sort_order)
+                    .unwrap_or(UNGROUPED_SORT_ORDER),
+                CategoryBudgetRow {
+                    id: budget.id,
+                    category_name: category.name.clone(),
+                    group_name: group.map(|g| g.name.clone()),
+                    category_color: category.color.clone(),
+                    group_color: group.map(|g| g.color.clone()),
+                    planned: budget.planned,
+                    spent: budget.spent,
+                    carryover: budget.carryover,
+                    currency: budget.currency.trim().to_string(),
+                },
+            ));
+        }
+        rows.sort_by(|(a_order, a), (b_order, b)| {
+            a_order
+                .cmp(b_order)
+                .then_with(|| a.category_name.cmp(&b.category_name))
+        });
+
+        Ok(rows
+            .into_iter()
+            .map(|(_, row)| CategoryBudgetApi::from(row))
+            .collect())
+    }
+
+    async fn currencies(&self, periods: &[(i16, i32)]) -> Result<Vec<String>> {
+        let tables = self.lock();
+        let currencies: BTreeSet<String> = tables
+            .budgets
+            .values()
+            .filter(|b| periods.contains(&(b.month, b.year)))
+            .map(|b| b.currency.trim().to_string())
+            .collect();
+        Ok(currencies.into_iter().collect())
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Option<Budget>> {
+        Ok(self.lock().budgets.get(&id).cloned())
+    }
+
+    async fn create(&self, budget: NewBudget) -> Result<Budget> {
+        let mut tables = self.lock();
+        if !tables.categories.contains_key(&budget.category_id) {
+            return Err(AppError::Validation(UNKNOWN_CATEGORY.to_string()));
+        }
+        if tables.budgets.values().any(|b| {
+            b.year == budget.year
+                && b.month == budget.month
+                && b.category_id == budget.category_id
+        }) {
+            return Err(AppError::Validation(DUPLICATE_BUDGET.to_string()));
+        }
+
+        let now = Utc::now();
+        let created = Budget {
+            id: Uuid::new_v4(),
+            month: budget.month,
+            year: budget.year,
+            category_id: budget.category_id,
+            planned: budget.planned,
+            spent: Decimal::from(0),
+            carryover: Decimal::from(0),
+            currency: budget.currency,
+            created_at: now,
+            updated_at: now,
+        };
+        tables.budgets.insert(created.id, created.clone());
+        Ok(created)
+    }
+
+    async fn update(
+        &self,
+        id: Uuid,
+        planned: Decimal,
+        carryover: Decimal,
+    ) -> Result<Option<Budget>> {
+        let mut tables = self.lock();
+        Ok(tables.budgets.get_mut(&id).map(|budget| {
+            budget.planned = planned;
+            budget.carryover = carryover;
+            budget.updated_at = Utc::now();
+            budget.clone()
+        }))
+    }
+}
@@ -0,0 +1,114 @@
+//! Budget data access for MoneyWise backend.
+//!
+//! - postgres.rs: SQL queries against the `budgets`, `categories` and
+//!   `category_groups` tables
+//! - memory.rs: In-process tables for tests and database-less development
+//!
+//! HTTP handlers and the cache warm-up only see `BudgetRepository`, so they
+//! can run against either implementation.
+
+pub mod memory;
+pub mod postgres;
+
+pub use memory::InMemoryBudgetRepository;
+pub use postgres::PgBudgetRepository;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    error::Result,
+    models::{Budget, BudgetOverviewApi, CategoryBudgetApi},
+};
+
+/// Shared handle to the repository used by handlers
+pub type SharedBudgetRepository = Arc<dyn BudgetRepository>;
+
+/// A budget to insert; `spent` and `carryover` start at zero
+#[derive(Debug, Clone)]
+pub struct NewBudget {
+    pub month: i16,
+    pub year: i32,
+    pub category_id: Uuid,
+    pub planned: Decimal,
+    pub currency: String,
+}
+
+/// Budget reads and writes used by the API
+#[async_trait]
+pub trait BudgetRepository: Send + Sync {
+    /// Totals of a month, optionally for one currency. Without a filter
+    /// and with several currencies, one currency's totals are returned.
+    /// An empty month gives zeros in `currency` (default EUR).
+    async fn overview(
+        &self,
+        month: i16,
+        year: i32,
+        currency: Option<&str>,
+    ) -> Result<BudgetOverviewApi>;
+
+    /// Budgets of a month with their category and group, ordered by group
+    /// `sort_order` (ungrouped last) then category name
+    async fn category_budgets(
+        &self,
+        month: i16,
+        year: i32,
+        currency: Option<&str>,
+    ) -> Result<Vec<CategoryBudgetApi>>;
+
+    /// Currencies with at least one budget in any of `periods`, sorted
+    async fn currencies(&self, periods: &[(i16, i32)]) -> Result<Vec<String>>;
+
+    /// A single budget; `None` if it does not exist
+    async fn find(&self, id: Uuid) -> Result<Option<Budget>>;
+
+    /// Insert a budget. Fails with `Validation` if the category is unknown
+    /// or already budgeted for that month.
+    async fn create(&self, budget: NewBudget) -> Result<Budget>;
+
+    /// Set a budget's planned amount and carryover; `None` if it does not
+    /// exist
+    async fn update(
+        &self,
+        id: Uuid,
+        planned: Decimal,
+        carryover: Decimal,
+    ) -> Result<Option<Budget>>;
+}
+
+/// Message for a second budget in the same month and category
+pub(crate) const DUPLICATE_BUDGET: &str =
+    "Budget already exists for this year, month, and category";
+
+/// Message for a budget referencing a missing category
+pub(crate) const UNKNOWN_CATEGORY: &str = "Category not found";
+
+/// Currency reported for a month without budgets when none was requested
+pub(crate) const DEFAULT_CURRENCY: &str = "EUR";
+
+/// Overview from a month's totals
+pub(crate) fn overview_from_totals(
+    planned: Decimal,
+    spent: Decimal,
+    carryover: Decimal,
+    currency: String,
+) -> BudgetOverviewApi {
+    BudgetOverviewApi {
+        planned,
+        spent,
+        remaining: planned - spent + carryover,
+        currency,
+    }
+}
+
+/// Overview of a month without budgets
+pub(crate) fn empty_overview(currency: Option<&str>) -> BudgetOverviewApi {
+    BudgetOverviewApi {
+        planned: Decimal::from(0),
+        spent: Decimal::from(0),
+        remaining: Decimal::from(0),
+        currency: currency.unwrap_or(DEFAULT_CURRENCY).to_string(),
+    }
+}
@@ -0,0 +1,219 @@
+//! PostgreSQL budget repository.
+//!
+//! All queries are parameterized; sums and joins run in the database so
+//! only the rows a response needs are transferred.
+//!
+//! Queries use the checked `query!`/`query_as!` macros: they are verified
+//! against the schema at build time, from a live database when
+//! `DATABASE_URL` is set and from the committed `sqlx-data.json` otherwise.
+//! Run `make prepare-sqlx` after changing a query or the schema.
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, Result},
+    models::{Budget, BudgetOverviewApi, CategoryBudgetApi, CategoryBudgetRow},
+    repository::{
+        empty_overview, overview_from_totals, BudgetRepository, NewBudget,
+        DUPLICATE_BUDGET, UNKNOWN_CATEGORY,
+    },
+};
+
+/// `BudgetRepository` over a connection pool
+#[derive(Debug, Clone)]
+pub struct PgBudgetRepository {
+    pool: PgPool,
+}
+
+impl PgBudgetRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl BudgetRepository for PgBudgetRepository {
+    /// Notes:
+    /// - SUMs are done in SQL for efficiency and to reduce data transferred
+    /// - `COALESCE` ensures NULL-safe totals
+    /// - Grouped by currency to support multi-currency budgets; we pick the
+    ///   first (typical single currency per query)
+    async fn overview(
+        &self,
+        month: i16,
+        year: i32,
+        currency: Option<&str>,
+    ) -> Result<BudgetOverviewApi> {
+        let result = sqlx::query!(
+            r#"
+            SELECT
+                COALESCE(SUM(planned), 0) as "planned!",
+                COALESCE(SUM(spent), 0) as "spent!",
+                COALESCE(SUM(carryover), 0) as "carryover!",
+                TRIM(currency) as "currency!"
+            FROM budgets
+            WHERE month = $1::smallint AND year = $2
+            AND ($3::text IS NULL OR currency = $3)
+            GROUP BY currency
+            ORDER BY currency
+            LIMIT 1
+            "#,
+            month,
+            year,
+            currency,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(result) = result else {
+            return Ok(empty_overview(currency));
+        };
+        Ok(overview_from_totals(
+            result.planned,
+            result.spent,
+            result.carryover,
+            result.currency,
+        ))
+    }
+
+    /// Single query joining categories and optional groups; the percentage
+    /// is computed in application code to keep the SQL simple and precise
+    /// with decimals
+    async fn category_budgets(
+        &self,
+        month: i16,
+        year: i32,
+        currency: Option<&str>,
+    ) -> Result<Vec<CategoryBudgetApi>> {
+        let rows = sqlx::query_as!(
+            CategoryBudgetRow,
+            r#"
+            SELECT
+                b.id,
+                c.name as category_name,
+                cg.name as "group_name?",
+                c.color as category_color,
+                cg.color as "group_color?",
+                b.planned,
+                b.spent,
+                b.carryover,
+                TRIM(b.currency) as "currency!"
+            FROM budgets b
+            JOIN categories c ON b.category_id = c.id
+            LEFT JOIN category_groups cg ON c.group_id = cg.id
+            WHERE b.month = $1 AND b.year = $2
+            AND ($3::text IS NULL OR b.currency = $3)
+            ORDER BY COALESCE(cg.sort_order, 999), c.name
+            "#,
+            month,
+            year,
+            currency,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(CategoryBudgetApi::from).collect())
+    }
+
+    async fn currencies(&self, periods: &[(i16, i32)]) -> Result<Vec<String>> {
+        let (months, years): (Vec<i16>, Vec<i32>) =
+            periods.iter().copied().unzip();
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT TRIM(b.currency) as "currency!"
+            FROM budgets b
+            JOIN UNNEST($1::smallint[], $2::integer[]) AS p(month, year)
+                ON b.month = p.month AND b.year = p.year
+            ORDER BY 1
+            "#,
+            &months,
+            &years,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.currency).collect())
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Option<Budget>> {
+        Ok(sqlx::query_as!(
+            Budget,
+            r#"
+            SELECT id, month, year, category_id, planned, spent, carryover,
+                currency, created_at, updated_at
+            FROM budgets
+            WHERE id = $1
+            "#,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    async fn create(&self, budget: NewBudget) -> Result<Budget> {
+        // Random ids prevent enumeration and are globally unique
+        sqlx::query_as!(
+            Budget,
+            r#"
+            INSERT INTO budgets (id, month, year, category_id, planned, currency)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, month, year, category_id, planned, spent, carryover,
+                currency, created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            budget.month,
+            budget.year,
+            budget.category_id,
+            budget.planned,
+            budget.currency,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            // Map constraint violations to friendly 400 errors
+            if let sqlx::Error::Database(db_err) = &e {
+                match db_err.code().as_deref() {
+                    // (year, month, category_id) is unique
+                    Some("23505") => {
+                        return AppError::Validation(
+                            DUPLICATE_BUDGET.to_string(),
+                        )
+                    }
+                    Some("23503") => {
+                        return AppError::Validation(
+                            UNKNOWN_CATEGORY.to_string(),
+                        )
+                    }
+                    _ => {}
+                }
+            }
+            AppError::Database(e)
+        })
+    }
+
+    async fn update(
+        &self,
+        id: Uuid,
+        planned: Decimal,
+        carryover: Decimal,
+    ) -> Result<Option<Budget>> {
+        Ok(sqlx::query_as!(
+            Budget,
+            r#"
+            UPDATE budgets
+            SET planned = $1, carryover = $2, updated_at = CURRENT_TIMESTAMP
+            WHERE id = $3
+            RETURNING id, month, year, category_id, planned, spent, carryover,
+                currency, created_at, updated_at
+            "#,
+            planned,
+            carryover,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+}
@@ -3,3 +3,5 @@
 //! This module provides server functionality including configuration and startup.
 
 pub mod config;
+pub mod request_id;
+pub mod telemetry;
@@ -0,0 +1,116 @@
+//! Request IDs for MoneyWise backend.
+//!
+//! Every request gets an ID: the caller's `x-request-id` when it is a
+//! reasonable token (so IDs from a proxy or the app follow the request
+//! through), otherwise a new UUID. The ID is echoed in the response header,
+//! recorded on the request span, so every log line and exported span of the
+//! request carries it, and added to JSON error bodies (`AppError`, the
+//! admin API and the rate limiter) with `insert_request_id`.
+
+use axum::{
+    body::Body,
+    extract::MatchedPath,
+    http::{HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::fmt;
+use tracing::{field, Instrument};
+use uuid::Uuid;
+
+/// Header carrying the request ID in both directions
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest caller-supplied ID that is kept
+pub const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: RequestId;
+}
+
+/// ID of one request, also stored in the request extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// A new random ID
+    pub fn generate() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+
+    /// Accept a caller-supplied ID of 1 to `MAX_REQUEST_ID_LEN` letters,
+    /// digits, `-`, `_`, `.` or `:`; anything else could forge log lines
+    pub fn parse(value: &str) -> Option<Self> {
+        let valid = !value.is_empty()
+            && value.len() <= MAX_REQUEST_ID_LEN
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.:".contains(c));
+        valid.then(|| Self(value.to_string()))
+    }
+
+    /// ID of the request being handled by the current task, if any
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Add the current request's ID to a JSON error body as `request_id`, so
+/// clients can quote the ID that finds the request in the logs
+pub fn insert_request_id(body: &mut serde_json::Value) {
+    if let Some(request_id) = RequestId::current() {
+        body["request_id"] = serde_json::json!(request_id.as_str());
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Assign the request ID and run the request inside its span.
+///
+/// Install outside the other route layers so rate limit rejections and
+/// their logs carry the ID too.
+pub async fn request_id_middleware(
+    mut req: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(RequestId::parse)
+        .unwrap_or_else(RequestId::generate);
+    req.extensions_mut().insert(id.clone());
+
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        route = %route,
+        status = field::Empty,
+        otel.name = %format!("{} {}", req.method(), route),
+        otel.kind = "server",
+    );
+
+    let mut res = CURRENT
+        .scope(id.clone(), next.run(req).instrument(span.clone()))
+        .await;
+
+    span.record("status", res.status().as_u16());
+    if let Ok(value) = HeaderValue::from_str(id.as_str()) {
+        res.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    res
+}
@@ -0,0 +1,186 @@
+//! Logging and tracing setup for MoneyWise backend.
+//!
+//! Logs go to stdout as text or, with `LOG_FORMAT=json`, as one JSON object
+//! per line including the fields of the enclosing spans (so every line of a
+//! request carries its `request_id`). Setting `OTEL_EXPORTER_OTLP_ENDPOINT`
+//! also exports spans to an OpenTelemetry collector over OTLP/HTTP (JSON
+//! encoding).
+
+use opentelemetry::{
+    trace::{TraceError, TracerProvider as _},
+    KeyValue,
+};
+use opentelemetry_otlp::{Protocol, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{runtime, trace::TracerProvider, Resource};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+use tracing_subscriber::{
+    layer::SubscriberExt, util::SubscriberInitExt, util::TryInitError,
+    EnvFilter, Layer, Registry,
+};
+
+/// `service.name` of exported spans unless `OTEL_SERVICE_NAME` is set
+pub const DEFAULT_SERVICE_NAME: &str = "moneywise-backend";
+
+/// Filter used when `RUST_LOG` is unset; sqlx query logs are too noisy
+pub const DEFAULT_LOG_FILTER: &str = "info,sqlx::query=warn";
+
+/// Log line encoding (`LOG_FORMAT`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Human-readable lines (default)
+    #[default]
+    Text,
+    /// One JSON object per line, for log collectors
+    Json,
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(format!("Unknown log format '{}'", other)),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum TelemetryError {
+    #[error("Failed to create OTLP exporter: {0}")]
+    Exporter(#[from] TraceError),
+
+    #[error("Failed to install tracing subscriber: {0}")]
+    Subscriber(#[from] TryInitError),
+}
+
+/// Logging and span export settings
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    /// `EnvFilter` directives (`RUST_LOG`)
+    pub log_filter: String,
+    pub log_format: LogFormat,
+    /// Collector base URL (`OTEL_EXPORTER_OTLP_ENDPOINT`, e.g.
+    /// `http://localhost:4318`); spans are not exported when unset
+    pub otlp_endpoint: Option<String>,
+    /// `OTEL_SERVICE_NAME`
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        // Runs before logging is set up, so a bad value can only be printed
+        let log_format = match std::env::var("LOG_FORMAT") {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                eprintln!("{}, using text logs", e);
+                LogFormat::Text
+            }),
+            Err(_) => LogFormat::default(),
+        };
+
+        Self {
+            log_filter: std::env::var("RUST_LOG")
+                .unwrap_or_else(|_| DEFAULT_LOG_FILTER.to_string()),
+            log_format,
+            otlp_endpoint: std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .ok()
+                .filter(|endpoint| !endpoint.trim().is_empty()),
+            service_name: std::env::var("OTEL_SERVICE_NAME")
+                .unwrap_or_else(|_| DEFAULT_SERVICE_NAME.to_string()),
+        }
+    }
+}
+
+/// Flushes exported spans when dropped; keep it alive in `main`
+#[must_use = "spans are only flushed while the guard is alive"]
+pub struct TelemetryGuard {
+    provider: Option<TracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to flush exported spans: {}", e);
+            }
+        }
+    }
+}
+
+/// A tracer provider batching spans to the collector at `endpoint`.
+///
+/// `endpoint` is the collector base URL; spans are posted to
+/// `{endpoint}/v1/traces`. Must be called inside a Tokio runtime.
+pub fn otlp_tracer_provider(
+    endpoint: &str,
+    service_name: &str,
+) -> Result<TracerProvider, TraceError> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_protocol(Protocol::HttpJson)
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()?;
+
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            service_name.to_string(),
+        )]))
+        .build())
+}
+
+/// Layer turning `tracing` spans into spans of `provider`
+pub fn otlp_layer<S>(
+    provider: &TracerProvider,
+    service_name: &str,
+) -> impl Layer<S> + Send + Sync
+where
+    S: tracing::Subscriber
+        + for<'span> tracing_subscriber::registry::LookupSpan<'span>
+        + Send
+        + Sync,
+{
+    tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer(service_name.to_string()))
+}
+
+/// Install the global subscriber described by `config`
+pub fn init_tracing(
+    config: &TelemetryConfig,
+) -> Result<TelemetryGuard, TelemetryError> {
+    let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> =
+        vec![EnvFilter::new(&config.log_filter).boxed()];
+    layers.push(match config.log_format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer().json().boxed(),
+    });
+
+    let provider = match &config.otlp_endpoint {
+        Some(endpoint) => {
+            let provider =
+                otlp_tracer_provider(endpoint, &config.service_name)?;
+            layers.push(otlp_layer(&provider, &config.service_name).boxed());
+            Some(provider)
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry().with(layers).try_init()?;
+    if let Some(endpoint) = &config.otlp_endpoint {
+        tracing::info!("Exporting spans to {}", endpoint);
+    }
+    Ok(TelemetryGuard { provider })
+}
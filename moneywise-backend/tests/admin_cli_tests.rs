@@ -0,0 +1,131 @@
+//! Tests for the `moneywise-admin` binary and its maintenance helpers.
+//!
+//! Only commands that fail before connecting to anything are run, so the
+//! tests need neither PostgreSQL nor Redis.
+
+use moneywise_backend::maintenance::BudgetPeriod;
+use std::process::{Command, Output};
+
+/// Run the admin binary with a clean configuration plus `env`
+fn admin(args: &[&str], env: &[(&str, &str)]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_moneywise-admin"));
+    command
+        .args(args)
+        .env_clear()
+        .env(
+            "DATABASE_URL",
+            "postgresql://postgres@localhost:5432/moneywise",
+        )
+        .envs(env.iter().copied());
+    command.output().expect("failed to run moneywise-admin")
+}
+
+/// Test: months are parsed as `YYYY-MM` within the budgets table's bounds
+/// Why: a typo must not copy budgets into a month no API call can read
+/// Impact: `copy-month` only writes months the API accepts
+#[test]
+fn budget_period_parsing() {
+    let period: BudgetPeriod = "2025-08".parse().unwrap();
+    assert_eq!(
+        period,
+        BudgetPeriod {
+            month: 8,
+            year: 2025
+        }
+    );
+    assert_eq!(period.to_string(), "2025-08");
+    assert_eq!(
+        "2024-12".parse::<BudgetPeriod>().unwrap().to_string(),
+        "2024-12"
+    );
+
+    for invalid in ["2025-13", "2025-0", "1999-12", "08/2025", "2025", ""] {
+        assert!(
+            invalid.parse::<BudgetPeriod>().is_err(),
+            "'{}' should be rejected",
+            invalid
+        );
+    }
+}
+
+/// Test: the help lists every operational subcommand
+/// Why: operators discover the tool through `--help`
+/// Impact: each task replaced by the CLI stays reachable
+#[test]
+fn help_lists_subcommands() {
+    let output = admin(&["--help"], &[]);
+    assert!(output.status.success());
+
+    let help = String::from_utf8(output.stdout).unwrap();
+    for command in [
+        "migrate",
+        "seed",
+        "cache",
+        "rate-limits",
+        "copy-month",
+        "check-config",
+    ] {
+        assert!(
+            help.contains(command),
+            "missing '{}' in:\n{}",
+            command,
+            help
+        );
+    }
+}
+
+/// Test: an invalid month is rejected before connecting to the database
+/// Why: argument errors should not depend on the database being up
+/// Impact: usage errors exit with status 2 and a message naming the flag
+#[test]
+fn copy_month_rejects_invalid_month() {
+    let output =
+        admin(&["copy-month", "--from", "2025-13", "--to", "2025-09"], &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--from"));
+}
+
+/// Test: check-config passes with defaults and lists each invalid value
+/// Why: the server silently falls back to defaults for unparsable values
+/// Impact: misconfigurations are caught before a deploy, not in production
+#[test]
+fn check_config_reports_invalid_values() {
+    let output = admin(&["check-config"], &[]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = admin(
+        &["check-config"],
+        &[
+            ("PORT", "abc"),
+            ("CACHE_CODEC", "xml"),
+            ("RATE_LIMIT_DEGRADATION_READ", "maybe"),
+        ],
+    );
+    assert!(!output.status.success());
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(report.lines().count(), 3, "{}", report);
+    for var in ["PORT", "CACHE_CODEC", "RATE_LIMIT_DEGRADATION_READ"] {
+        assert!(report.contains(var), "missing {} in:\n{}", var, report);
+    }
+
+    let output = admin(&["check-config"], &[("DATABASE_URL", "")]);
+    assert!(!output.status.success());
+
+    let output = admin(
+        &["check-config"],
+        &[("RATE_LIMIT_CLEANUP_INTERVAL_SECS", "0")],
+    );
+    assert!(!output.status.success());
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(report.contains("RATE_LIMIT_CLEANUP_INTERVAL_SECS cannot be 0"));
+}
+
+/// Test: cache flush refuses prefixes outside the cache namespace
+/// Why: rate limit counters may share the cache's Redis instance
+/// Impact: the CLI can't wipe rate limiting state by mistake
+#[test]
+fn cache_flush_rejects_foreign_prefix() {
+    let output = admin(&["cache", "flush", "--prefix", "rate_limit:"], &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("moneywise:"));
+}
@@ -0,0 +1,375 @@
+//! Tests for API-key based rate limiting (plan tiers, key hashing, Redis keys).
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware, Router,
+};
+use common::db::TestDatabase;
+use moneywise_backend::rate_limiter::{
+    api_keys::{
+        hash_api_key, is_well_formed_api_key, ApiKeyStore, LOOKUP_LIMIT_PER_IP,
+    },
+    middleware::{extract_api_key, rate_limit_middleware},
+    types::{ApiKeyIdentity, RateLimitKey, TransactionType},
+    ClassifiedRouter, InMemoryBackend, PlanTier, RateLimitBackend,
+    RateLimitConfig, RateLimitService,
+};
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+const ALL_TRANSACTION_TYPES: [TransactionType; 3] = [
+    TransactionType::BudgetModification,
+    TransactionType::BudgetRead,
+    TransactionType::BudgetOverview,
+];
+
+async fn ok() -> &'static str {
+    "ok"
+}
+
+/// Stub app with one limited route, resolving API keys with `store`
+fn app(store: ApiKeyStore) -> Router {
+    let (router, table) = ClassifiedRouter::new()
+        .post("/budgets", ok, TransactionType::BudgetModification)
+        .into_parts();
+    let service = RateLimitService::with_backend(
+        RateLimitConfig::in_memory(),
+        Arc::new(InMemoryBackend::default()),
+    )
+    .with_api_keys(store)
+    .with_route_table(table);
+
+    router.layer(middleware::from_fn_with_state(
+        Arc::new(service),
+        rate_limit_middleware,
+    ))
+}
+
+async fn post_with_key(app: &Router, key: &str) -> StatusCode {
+    let req = Request::builder()
+        .method("POST")
+        .uri("/budgets")
+        .header("x-forwarded-for", "10.0.0.9")
+        .header("x-api-key", key)
+        .body(Body::empty())
+        .unwrap();
+    app.clone().oneshot(req).await.unwrap().status()
+}
+
+/// A well-formed key that was never issued
+fn made_up_key() -> String {
+    format!("mw_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn family_identity() -> ApiKeyIdentity {
+    ApiKeyIdentity {
+        key_id: Uuid::new_v4(),
+        plan: PlanTier::Family,
+    }
+}
+
+/// Test: free plan mirrors the shared limits; family plan is never stricter
+/// Why: a paying household must not end up with fewer requests than anonymous traffic
+/// Impact: guards plan tables against accidental regressions when limits are tuned
+#[test]
+fn plan_limits_are_ordered() {
+    for tx in ALL_TRANSACTION_TYPES {
+        assert_eq!(PlanTier::Free.limit_for(tx), tx.get_limit());
+        assert!(PlanTier::Family.limit_for(tx) >= PlanTier::Free.limit_for(tx));
+    }
+    assert!(PlanTier::Family.daily_quota() > PlanTier::Free.daily_quota());
+}
+
+/// Test: plan names round-trip through the database/header string form and serde
+/// Why: plans are stored as text in `api_keys.plan` and accepted as JSON by the admin API
+/// Impact: prevents keys from silently falling back to the free plan after a rename
+#[test]
+fn plan_tier_string_round_trip() {
+    for plan in [PlanTier::Free, PlanTier::Family] {
+        assert_eq!(plan.as_str().parse::<PlanTier>().unwrap(), plan);
+        let json = serde_json::to_string(&plan).unwrap();
+        assert_eq!(json, format!("\"{}\"", plan.as_str()));
+    }
+    assert!("enterprise".parse::<PlanTier>().is_err());
+}
+
+/// Test: an API key switches tracking from IP/device to the key itself
+/// Why: households behind one NAT must not share a bucket once they use a key
+/// Impact: documents the Redis key layout used for per-key counters
+#[test]
+fn api_key_changes_redis_key_and_limit() {
+    let identity = family_identity();
+    let anonymous = RateLimitKey::new(
+        "10.0.0.1".to_string(),
+        Some("device-1234".to_string()),
+        TransactionType::BudgetRead,
+    );
+    let keyed = anonymous.clone().with_api_key(identity);
+
+    assert_eq!(anonymous.limit(), TransactionType::BudgetRead.get_limit());
+    assert_eq!(
+        keyed.limit(),
+        PlanTier::Family.limit_for(TransactionType::BudgetRead)
+    );
+    assert_eq!(
+        keyed.to_redis_key(),
+        format!("rate_limit:key:{}:budget_read", identity.key_id)
+    );
+
+    // Same key from another IP shares the bucket
+    let elsewhere = RateLimitKey::new(
+        "192.168.1.1".to_string(),
+        None,
+        TransactionType::BudgetRead,
+    )
+    .with_api_key(identity);
+    assert_eq!(keyed.to_redis_key(), elsewhere.to_redis_key());
+}
+
+/// Test: daily quota keys exist only for API keys and roll over per day
+/// Why: anonymous traffic has no daily quota; keyed traffic resets at midnight UTC
+/// Impact: guards the quota window boundaries
+#[test]
+fn daily_key_only_for_api_keys() {
+    let anonymous = RateLimitKey::new(
+        "10.0.0.1".to_string(),
+        None,
+        TransactionType::BudgetOverview,
+    );
+    assert!(anonymous.to_daily_redis_key(20_000).is_none());
+
+    let keyed = anonymous.with_api_key(family_identity());
+    let today = keyed.to_daily_redis_key(20_000).unwrap();
+    let tomorrow = keyed.to_daily_redis_key(20_001).unwrap();
+    assert_ne!(today, tomorrow);
+
+    // Quota is shared across transaction types
+    let mut modification = keyed.clone();
+    modification.transaction_type = TransactionType::BudgetModification;
+    assert_eq!(modification.to_daily_redis_key(20_000).unwrap(), today);
+}
+
+/// Test: key hashing is deterministic and never stores the plaintext
+/// Why: lookups hash the presented key and compare against `api_keys.key_hash`
+/// Impact: a database leak must not reveal usable keys
+#[test]
+fn api_key_hash_is_stable_hex() {
+    let key = format!("mw_{}", "ab".repeat(32));
+    let hash = hash_api_key(&key);
+
+    assert_eq!(hash, hash_api_key(&key));
+    assert_eq!(hash.len(), 64);
+    assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+    assert!(!hash.contains(&key));
+    assert_ne!(hash, hash_api_key("mw_other"));
+}
+
+/// Test: malformed keys are rejected before any database lookup
+/// Why: avoids a Postgres round-trip for garbage headers
+/// Impact: keeps invalid-key floods cheap
+#[test]
+fn malformed_api_keys_are_rejected() {
+    assert!(is_well_formed_api_key(&format!("mw_{}", "0f".repeat(32))));
+    assert!(!is_well_formed_api_key(&"0f".repeat(32)));
+    assert!(!is_well_formed_api_key("mw_short"));
+    assert!(!is_well_formed_api_key(&format!("mw_{}", "zz".repeat(32))));
+}
+
+/// Test: the `x-api-key` header is extracted and trimmed; blank values are ignored
+/// Why: a blank header must behave like an anonymous request, not an invalid key
+/// Impact: clients that always send the header do not get spurious 401s
+#[test]
+fn extract_api_key_from_header() {
+    let req = Request::builder()
+        .uri("/api/budgets")
+        .header("x-api-key", "  mw_abc  ")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(extract_api_key(&req).as_deref(), Some("mw_abc"));
+
+    let blank = Request::builder()
+        .uri("/api/budgets")
+        .header("x-api-key", "   ")
+        .body(Body::empty())
+        .unwrap();
+    assert!(extract_api_key(&blank).is_none());
+
+    let missing = Request::builder()
+        .uri("/api/budgets")
+        .body(Body::empty())
+        .unwrap();
+    assert!(extract_api_key(&missing).is_none());
+}
+
+fn anonymous_key(tx: TransactionType) -> RateLimitKey {
+    RateLimitKey::new("10.0.0.7".to_string(), None, tx)
+}
+
+/// Test: a window limit of N allows the N-th request and denies the N+1-th
+/// Why: the window and the daily quota used to disagree on the boundary, so
+/// `X-RateLimit-Remaining` did not say when a client would be cut off
+/// Impact: the last allowed request reports 0 remaining, the next gets 429
+#[tokio::test]
+async fn window_limit_boundary() {
+    let service = RateLimitService::with_backend(
+        RateLimitConfig::in_memory(),
+        Arc::new(InMemoryBackend::default()),
+    );
+    let tx = TransactionType::BudgetModification;
+    let limit = tx.get_limit();
+
+    for n in 1..=limit {
+        let result = service.check_and_record(anonymous_key(tx)).await.unwrap();
+        assert!(result.allowed, "request {} of {}", n, limit);
+        assert_eq!(result.remaining_requests, limit - n);
+    }
+    let result = service.check_and_record(anonymous_key(tx)).await.unwrap();
+    assert!(!result.allowed);
+}
+
+/// Test: a daily quota of N allows the N-th request and denies the N+1-th
+/// Why: the daily quota must cut clients off at the same point as the window
+/// Impact: `X-RateLimit-Daily-Remaining: 0` is sent on the last allowed request
+#[tokio::test]
+async fn daily_quota_boundary() {
+    let backend = Arc::new(InMemoryBackend::default());
+    let service = RateLimitService::with_backend(
+        RateLimitConfig::in_memory(),
+        backend.clone(),
+    );
+    let identity = ApiKeyIdentity {
+        key_id: Uuid::new_v4(),
+        plan: PlanTier::Free,
+    };
+    let key =
+        anonymous_key(TransactionType::BudgetOverview).with_api_key(identity);
+    let quota = PlanTier::Free.daily_quota();
+
+    // Use up all but one request of today's quota
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let daily_key = key.to_daily_redis_key(now.as_secs() / 86_400).unwrap();
+    for _ in 1..quota {
+        backend.increment(&daily_key, 86_400).await.unwrap();
+    }
+
+    let last = service.check_and_record(key.clone()).await.unwrap();
+    assert!(last.allowed);
+    assert_eq!(last.daily.unwrap().remaining, 0);
+
+    let denied = service.check_and_record(key).await.unwrap();
+    assert!(!denied.allowed);
+    assert_eq!(denied.daily.unwrap().remaining, 0);
+}
+
+/// Test: a request whose key lookup fails is counted once, by IP/device
+/// Why: the fallback after a failed lookup charges the IP/device limit; the
+/// lookup itself only uses the IP's lookup budget
+/// Impact: a database outage does not halve the limit of key holders
+#[tokio::test]
+async fn failed_lookup_counted_once() {
+    let pool = PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(100))
+        .connect_lazy("postgres://postgres@127.0.0.1:1/moneywise")
+        .unwrap();
+    let app = app(ApiKeyStore::new(pool));
+    let key = made_up_key();
+    let limit = TransactionType::BudgetModification.get_limit();
+
+    // Counted twice, requests would be rejected from about `limit / 2` on
+    for _ in 0..limit {
+        assert_eq!(post_with_key(&app, &key).await, StatusCode::OK);
+    }
+    assert_eq!(
+        post_with_key(&app, &key).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+}
+
+/// Test: lookups are reused for the cache TTL, including unknown keys
+/// Why: every request with `x-api-key` used to query the database
+/// Impact: repeated keys cost one query per TTL; revocations take effect
+/// within `LOOKUP_CACHE_TTL`
+#[tokio::test]
+#[ignore = "needs PostgreSQL at DATABASE_URL (creates scratch databases)"]
+async fn api_key_lookups_are_cached() {
+    let db = TestDatabase::new().await;
+    let store = ApiKeyStore::new(db.pool.clone());
+    let issued = store.issue("cache-test", PlanTier::Family).await.unwrap();
+    assert_eq!(store.cached(&issued.key), None);
+    assert!(store.authenticate(&issued.key).await.unwrap().is_some());
+
+    sqlx::query("UPDATE api_keys SET revoked_at = now()")
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    assert!(store.authenticate(&issued.key).await.unwrap().is_some());
+    let fresh = ApiKeyStore::new(db.pool.clone());
+    assert!(fresh.authenticate(&issued.key).await.unwrap().is_none());
+
+    let unknown = made_up_key();
+    assert!(store.authenticate(&unknown).await.unwrap().is_none());
+    assert_eq!(store.cached(&unknown), Some(None));
+
+    let expiring =
+        ApiKeyStore::new(db.pool.clone()).with_lookup_ttl(Duration::ZERO);
+    assert!(expiring.authenticate(&unknown).await.unwrap().is_none());
+    assert_eq!(expiring.cached(&unknown), None);
+}
+
+/// Test: made-up keys are rejected once the IP's lookup budget is used up
+/// Why: each new key needs a lookup, so a flood of them must be shed before
+/// reaching the database
+/// Impact: bogus keys cannot turn the limiter into a database load generator
+#[tokio::test]
+#[ignore = "needs PostgreSQL at DATABASE_URL (creates scratch databases)"]
+async fn made_up_keys_limited_before_lookup() {
+    let db = TestDatabase::new().await;
+    let app = app(ApiKeyStore::new(db.pool.clone()));
+
+    for _ in 0..LOOKUP_LIMIT_PER_IP {
+        let status = post_with_key(&app, &made_up_key()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+    assert_eq!(
+        post_with_key(&app, &made_up_key()).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+}
+
+/// Test: a valid key that is not cached yet is allowed behind an exhausted IP
+/// Why: keys exist so households behind one NAT stop sharing a bucket; the
+/// lookup must not be charged to the NAT's IP/device limit
+/// Impact: key holders are not throttled each time their cached lookup expires
+#[tokio::test]
+#[ignore = "needs PostgreSQL at DATABASE_URL (creates scratch databases)"]
+async fn valid_key_allowed_behind_exhausted_ip() {
+    let db = TestDatabase::new().await;
+    let store = ApiKeyStore::new(db.pool.clone());
+    let issued = store.issue("nat-test", PlanTier::Free).await.unwrap();
+    let app = app(store);
+    let limit = TransactionType::BudgetModification.get_limit();
+
+    let anonymous = || {
+        Request::builder()
+            .method("POST")
+            .uri("/budgets")
+            .header("x-forwarded-for", "10.0.0.9")
+            .body(Body::empty())
+            .unwrap()
+    };
+    for _ in 0..limit {
+        app.clone().oneshot(anonymous()).await.unwrap();
+    }
+    let exhausted = app.clone().oneshot(anonymous()).await.unwrap();
+    assert_eq!(exhausted.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    assert_eq!(post_with_key(&app, &issued.key).await, StatusCode::OK);
+    // The lookup is cached now; still counted per key only
+    assert_eq!(post_with_key(&app, &issued.key).await, StatusCode::OK);
+}
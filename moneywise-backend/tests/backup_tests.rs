@@ -0,0 +1,203 @@
+//! Tests for the JSON-lines backup archive and transactional restores.
+
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::db::TestDatabase;
+use moneywise_backend::database::backup::{
+    create_backup, restore_backup, Backup, BackupError, BudgetRow,
+    CategoryGroupRow, CategoryRow, ConflictStrategy, TableCounts,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn sample_backup() -> Backup {
+    let at = Utc.with_ymd_and_hms(2025, 8, 11, 12, 57, 46).unwrap();
+    let group = CategoryGroupRow {
+        id: Uuid::new_v4(),
+        name: "Housing".to_string(),
+        sort_order: Some(1),
+        color: "#FF5733".to_string(),
+        icon: Some("🏠".to_string()),
+        created_at: at,
+        updated_at: at,
+    };
+    let category = CategoryRow {
+        id: Uuid::new_v4(),
+        name: "Rent".to_string(),
+        group_id: Some(group.id),
+        category_type: "expense".to_string(),
+        icon: None,
+        color: "#FF5733".to_string(),
+        is_default: Some(true),
+        created_at: at,
+        updated_at: at,
+    };
+    let budget = BudgetRow {
+        id: Uuid::new_v4(),
+        month: 8,
+        year: 2025,
+        category_id: category.id,
+        planned: Decimal::new(110050, 2),
+        spent: Decimal::new(95000, 2),
+        carryover: Decimal::ZERO,
+        currency: "USD".to_string(),
+        created_at: at,
+        updated_at: at,
+    };
+
+    Backup {
+        created_at: at,
+        category_groups: vec![group],
+        categories: vec![category],
+        budgets: vec![budget],
+    }
+}
+
+fn archive(backup: &Backup) -> String {
+    let mut bytes = Vec::new();
+    backup.write_to(&mut bytes).unwrap();
+    String::from_utf8(bytes).unwrap()
+}
+
+/// Test: an archive reads back to the same rows
+/// Why: decimals, optional columns and timestamps must survive JSON
+/// Impact: restores reproduce the backed-up data exactly
+#[test]
+fn archive_round_trip() {
+    let backup = sample_backup();
+    let text = archive(&backup);
+
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 5); // header, 3 records, trailer
+    assert!(lines[0].contains(r#""format":"moneywise-backup""#));
+    assert!(lines[1].starts_with(r#"{"table":"category_groups""#));
+    assert!(lines[3].contains(r#""planned":"1100.50""#));
+
+    assert_eq!(Backup::read_from(text.as_bytes()).unwrap(), backup);
+}
+
+/// Test: edited and truncated archives are rejected
+/// Why: a corrupted file must not be restored half-way
+/// Impact: nothing is written unless the whole archive is intact
+#[test]
+fn damaged_archives_are_rejected() {
+    let text = archive(&sample_backup());
+
+    let edited = text.replace("1100.50", "9999.00");
+    assert!(matches!(
+        Backup::read_from(edited.as_bytes()),
+        Err(BackupError::Checksum { .. })
+    ));
+
+    let truncated: String = text
+        .lines()
+        .take(3)
+        .map(|line| format!("{}\n", line))
+        .collect();
+    assert!(matches!(
+        Backup::read_from(truncated.as_bytes()),
+        Err(BackupError::Format(_))
+    ));
+
+    assert!(matches!(
+        Backup::read_from(&b""[..]),
+        Err(BackupError::Format(_))
+    ));
+}
+
+/// Test: archives from a newer format version are refused
+/// Why: an older binary would misread rows it doesn't know
+/// Impact: operators get a clear version error instead of bad data
+#[test]
+fn newer_archive_version_is_rejected() {
+    let text = archive(&sample_backup()).replacen(
+        r#""version":1"#,
+        r#""version":2"#,
+        1,
+    );
+
+    assert!(matches!(
+        Backup::read_from(text.as_bytes()),
+        Err(BackupError::UnsupportedVersion(2))
+    ));
+}
+
+/// Test: conflict strategies parse from their CLI names
+/// Why: `--on-conflict` takes these names
+/// Impact: typos are rejected instead of defaulting silently
+#[test]
+fn conflict_strategy_names() {
+    for name in ["fail", "skip", "overwrite", "replace"] {
+        let strategy: ConflictStrategy = name.parse().unwrap();
+        assert_eq!(strategy.to_string(), name);
+    }
+    assert_eq!(ConflictStrategy::default(), ConflictStrategy::Fail);
+    assert!("merge".parse::<ConflictStrategy>().is_err());
+}
+
+/// Test: backup, restore into another database and backup again agree
+/// Why: the archive must capture every column the restore writes back
+/// Impact: `backup`/`restore` can move data between environments
+#[tokio::test]
+#[ignore = "needs PostgreSQL at DATABASE_URL (creates scratch databases)"]
+async fn database_round_trip() {
+    let source_db = TestDatabase::with_sample_data().await;
+    let target_db = TestDatabase::with_sample_data().await;
+    let (source, target) = (&source_db.pool, &target_db.pool);
+
+    let before = create_backup(source).await.unwrap().counts();
+    restore_backup(source, &sample_backup(), ConflictStrategy::Fail)
+        .await
+        .unwrap();
+    let backup = Backup::read_from(
+        archive(&create_backup(source).await.unwrap()).as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(backup.counts().budgets, before.budgets + 1);
+
+    // The target's sample rows conflict with the archive
+    assert!(matches!(
+        restore_backup(target, &backup, ConflictStrategy::Fail).await,
+        Err(BackupError::Conflict { .. })
+    ));
+    let skipped = restore_backup(target, &backup, ConflictStrategy::Skip)
+        .await
+        .unwrap();
+    assert_eq!(
+        skipped,
+        TableCounts {
+            category_groups: 1,
+            categories: 1,
+            budgets: 1,
+        }
+    );
+
+    let replaced = restore_backup(target, &backup, ConflictStrategy::Replace)
+        .await
+        .unwrap();
+    assert_eq!(replaced, backup.counts());
+    let mut restored = create_backup(target).await.unwrap();
+    restored.created_at = backup.created_at;
+    assert_eq!(restored, backup);
+}
+
+/// Test: a restore into a new database succeeds with the default strategy
+/// Why: `restore` migrates an empty database first, and migrations must
+/// not add rows of their own
+/// Impact: restoring into a fresh environment yields exactly the archive
+#[tokio::test]
+#[ignore = "needs PostgreSQL at DATABASE_URL (creates scratch databases)"]
+async fn restore_into_new_database() {
+    let db = TestDatabase::new().await;
+    let backup = sample_backup();
+
+    let restored = restore_backup(&db.pool, &backup, ConflictStrategy::Fail)
+        .await
+        .unwrap();
+    assert_eq!(restored, backup.counts());
+    assert_eq!(
+        create_backup(&db.pool).await.unwrap().counts(),
+        backup.counts()
+    );
+}
@@ -0,0 +1,190 @@
+//! In-process tests for the budget API.
+//!
+//! The whole router (routes, rate limiting, cache) runs against
+//! `InMemoryBudgetRepository` and an in-memory cache, so these need neither
+//! PostgreSQL nor Redis.
+
+mod common;
+
+use axum::http::StatusCode;
+use chrono::Utc;
+use common::api::{app, send};
+use moneywise_backend::{models::Budget, repository::InMemoryBudgetRepository};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn budget(
+    category_id: Uuid,
+    planned: i64,
+    spent: i64,
+    currency: &str,
+) -> Budget {
+    Budget {
+        id: Uuid::new_v4(),
+        month: 6,
+        year: 2025,
+        category_id,
+        planned: Decimal::from(planned),
+        spent: Decimal::from(spent),
+        carryover: Decimal::ZERO,
+        currency: currency.to_string(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+/// Test: a created budget can be read back and updated
+/// Why: covers create, get-by-id and update through the real handlers
+/// Impact: an update must not be hidden by the cached copy of the budget
+#[tokio::test]
+async fn create_get_and_update_budget() {
+    let repo = Arc::new(InMemoryBudgetRepository::new());
+    let rent = repo.add_category("Rent", "#FF5733", None);
+    let app = app(repo);
+
+    let (status, created) = send(
+        &app,
+        "POST",
+        "/api/budgets",
+        Some(json!({
+            "category_id": rent.to_string(),
+            "planned": "1100.50",
+            "currency": "USD",
+            "month": 6,
+            "year": 2025
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(created["planned"], "1100.50");
+    assert_eq!(created["spent"], "0");
+    let uri = format!("/api/budgets/{}", created["id"].as_str().unwrap());
+
+    let (status, fetched) = send(&app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched, created);
+
+    let (status, updated) = send(
+        &app,
+        "PUT",
+        &uri,
+        Some(json!({ "planned": "1200", "carryover": "25" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["planned"], "1200");
+    assert_eq!(updated["carryover"], "25");
+
+    let (_, fetched) = send(&app, "GET", &uri, None).await;
+    assert_eq!(fetched["planned"], "1200");
+}
+
+/// Test: the month view sums budgets and orders categories by group
+/// Why: the in-memory repository must answer like the SQL queries
+/// Impact: dashboard tests can rely on totals, ordering and insights
+#[tokio::test]
+async fn month_overview_and_categories() {
+    let repo = Arc::new(InMemoryBudgetRepository::new());
+    let housing = repo.add_category_group("Housing", "#224466", Some(1));
+    let food = repo.add_category_group("Food", "#00AA88", Some(2));
+    let rent = repo.add_category("Rent", "#FF5733", Some(housing));
+    let groceries = repo.add_category("Groceries", "#33FF57", Some(food));
+    let misc = repo.add_category("Misc", "#999999", None);
+    repo.insert_budget(budget(misc, 50, 10, "USD"));
+    repo.insert_budget(budget(groceries, 400, 450, "USD"));
+    repo.insert_budget(budget(rent, 1000, 1000, "USD"));
+    let app = app(repo);
+
+    let (status, overview) =
+        send(&app, "GET", "/api/budgets/overview?month=6&year=2025", None)
+            .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        overview,
+        json!({
+            "planned": "1450",
+            "spent": "1460",
+            "remaining": "-10",
+            "currency": "USD"
+        })
+    );
+
+    let (status, view) = send(
+        &app,
+        "GET",
+        "/api/budgets?month=6&year=2025&currency=USD",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = view["categories"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["category_name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Rent", "Groceries", "Misc"]);
+    assert_eq!(view["categories"][1]["percentage"], "112.50");
+    assert_eq!(view["insights"][0]["type_"], "warning");
+
+    // An empty month reports zeros in the requested currency
+    let (_, empty) = send(
+        &app,
+        "GET",
+        "/api/budgets/overview?month=1&year=2025&currency=GBP",
+        None,
+    )
+    .await;
+    assert_eq!(empty["planned"], "0");
+    assert_eq!(empty["currency"], "GBP");
+}
+
+/// Test: bad requests get the documented status codes and messages
+/// Why: constraint errors come from the repository, not the database
+/// Impact: clients see the same 400/404 responses with either backend
+#[tokio::test]
+async fn invalid_requests_are_rejected() {
+    let repo = Arc::new(InMemoryBudgetRepository::new());
+    let rent = repo.add_category("Rent", "#FF5733", None);
+    let app = app(repo);
+    let create = |category_id: Uuid| {
+        json!({
+            "category_id": category_id.to_string(),
+            "planned": "100",
+            "currency": "EUR",
+            "month": 6,
+            "year": 2025
+        })
+    };
+
+    let (status, _) =
+        send(&app, "POST", "/api/budgets", Some(create(rent))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) =
+        send(&app, "POST", "/api/budgets", Some(create(rent))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "Budget already exists for this year, month, and category"
+    );
+
+    let (status, body) =
+        send(&app, "POST", "/api/budgets", Some(create(Uuid::new_v4()))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Category not found");
+
+    let (status, body) =
+        send(&app, "GET", "/api/budgets/not-a-uuid", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Invalid budget ID format");
+
+    let missing = format!("/api/budgets/{}", Uuid::new_v4());
+    let (status, _) = send(&app, "GET", &missing, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) =
+        send(&app, "PUT", &missing, Some(json!({ "planned": "5" }))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -2,7 +2,7 @@
 
 mod common;
 
-use common::MockBudgetCache;
+use common::memory_budget_cache;
 use moneywise_backend::{cache::CacheConfig, models::BudgetApi};
 use rust_decimal::Decimal;
 
@@ -11,7 +11,7 @@ use rust_decimal::Decimal;
 /// Impact: prevents regressions in item-level caching and documents the intended API usage
 #[tokio::test]
 async fn budget_roundtrip_and_invalidate() {
-    let cache = MockBudgetCache::new(CacheConfig::default());
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
     let budget = BudgetApi {
         id: "budget-123".to_string(),
         month: 1,
@@ -25,13 +25,14 @@ async fn budget_roundtrip_and_invalidate() {
         updated_at: chrono::Utc::now(),
     };
 
-    assert!(cache.get_cached_budget(&budget.id).await.is_none());
-    cache.cache_budget(&budget.id, &budget).await;
-    let cached_budget = cache.get_cached_budget(&budget.id).await.unwrap();
+    assert!(cache.get_cached_budget(&budget.id).await.unwrap().is_none());
+    cache.cache_budget(&budget.id, &budget).await.unwrap();
+    let cached_budget =
+        cache.get_cached_budget(&budget.id).await.unwrap().unwrap();
     assert_eq!(cached_budget.id, budget.id);
     assert_eq!(cached_budget.category_id, budget.category_id);
     assert_eq!(cached_budget.planned, budget.planned);
 
-    cache.invalidate_budget_cache(&budget.id).await;
-    assert!(cache.get_cached_budget(&budget.id).await.is_none());
+    cache.invalidate_budget_cache(&budget.id).await.unwrap();
+    assert!(cache.get_cached_budget(&budget.id).await.unwrap().is_none());
 }
@@ -0,0 +1,460 @@
+//! Property-based tests for budget money arithmetic and insights.
+//!
+//! Budget sets are generated and checked against a reference model: a
+//! naive re-implementation of the aggregation that the in-memory and
+//! PostgreSQL repositories must agree with. The PostgreSQL comparison is
+//! ignored by default; run it with:
+//!   DATABASE_URL=postgresql://postgres@localhost:5432/moneywise \
+//!     cargo test --test budget_property_tests -- --include-ignored
+
+mod common;
+
+use chrono::Utc;
+use common::db::TestDatabase;
+use moneywise_backend::{
+    api::budget::generate_budget_insights,
+    models::{Budget, BudgetOverviewApi, CategoryBudgetApi, CategoryBudgetRow},
+    repository::{
+        BudgetRepository, InMemoryBudgetRepository, PgBudgetRepository,
+    },
+};
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use rust_decimal::Decimal;
+use sqlx::{Executor, PgPool, Row};
+use std::collections::BTreeSet;
+use std::future::Future;
+use uuid::Uuid;
+
+const MONTH: i16 = 6;
+const YEAR: i32 = 2025;
+const CURRENCIES: [&str; 3] = ["EUR", "GBP", "USD"];
+
+/// One category and its budget for the month
+#[derive(Debug, Clone)]
+struct GenBudget {
+    group: Option<usize>,
+    planned: Decimal,
+    spent: Decimal,
+    carryover: Decimal,
+    currency: &'static str,
+}
+
+/// A month of budgets; categories are named after their index
+#[derive(Debug, Clone)]
+struct GenMonth {
+    group_sort_orders: Vec<Option<i32>>,
+    budgets: Vec<GenBudget>,
+}
+
+impl GenMonth {
+    fn category_name(index: usize) -> String {
+        format!("Category {:02}", index)
+    }
+}
+
+/// Amounts as stored in `numeric(12,2)`
+fn amount(max_cents: i64) -> impl Strategy<Value = Decimal> {
+    (0..=max_cents).prop_map(|cents| Decimal::new(cents, 2))
+}
+
+fn gen_budget(groups: usize) -> impl Strategy<Value = GenBudget> {
+    (
+        proptest::option::of(0..groups),
+        amount(100_000_000),
+        amount(100_000_000),
+        amount(10_000_000),
+        proptest::sample::select(&CURRENCIES[..]),
+    )
+        .prop_map(|(group, planned, spent, carryover, currency)| {
+            GenBudget {
+                group,
+                planned,
+                spent,
+                carryover,
+                currency,
+            }
+        })
+}
+
+fn gen_month() -> impl Strategy<Value = GenMonth> {
+    (
+        proptest::collection::vec(proptest::option::of(0..4i32), 3),
+        proptest::collection::vec(gen_budget(3), 0..12),
+    )
+        .prop_map(|(group_sort_orders, budgets)| GenMonth {
+            group_sort_orders,
+            budgets,
+        })
+}
+
+// ================================================================
+// Reference model
+// ================================================================
+
+/// Budgets of the month in `currency` (all when `None`), with their index
+fn model_budgets<'a>(
+    month: &'a GenMonth,
+    currency: Option<&'a str>,
+) -> impl Iterator<Item = (usize, &'a GenBudget)> {
+    month
+        .budgets
+        .iter()
+        .enumerate()
+        .filter(move |(_, b)| currency.is_none_or(|c| b.currency == c))
+}
+
+/// Expected overview: the alphabetically first currency's totals
+fn model_overview(
+    month: &GenMonth,
+    currency: Option<&str>,
+) -> BudgetOverviewApi {
+    let first = model_budgets(month, currency)
+        .map(|(_, b)| b.currency)
+        .min();
+    let Some(first) = first else {
+        return BudgetOverviewApi {
+            planned: Decimal::ZERO,
+            spent: Decimal::ZERO,
+            remaining: Decimal::ZERO,
+            currency: currency.unwrap_or("EUR").to_string(),
+        };
+    };
+
+    let (mut planned, mut spent, mut carryover) =
+        (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO);
+    for (_, b) in model_budgets(month, Some(first)) {
+        planned += b.planned;
+        spent += b.spent;
+        carryover += b.carryover;
+    }
+    BudgetOverviewApi {
+        planned,
+        spent,
+        remaining: planned - spent + carryover,
+        currency: first.to_string(),
+    }
+}
+
+/// Expected category rows as (name, remaining), in display order
+fn model_categories(
+    month: &GenMonth,
+    currency: Option<&str>,
+) -> Vec<(String, Decimal)> {
+    let mut rows: Vec<(i32, String, Decimal)> = model_budgets(month, currency)
+        .map(|(i, b)| {
+            let sort_order = b
+                .group
+                .and_then(|g| month.group_sort_orders[g])
+                .unwrap_or(999);
+            (
+                sort_order,
+                GenMonth::category_name(i),
+                b.planned - b.spent + b.carryover,
+            )
+        })
+        .collect();
+    rows.sort();
+    rows.into_iter()
+        .map(|(_, name, remaining)| (name, remaining))
+        .collect()
+}
+
+/// Check every read of `repo` against the model
+async fn check_against_model(
+    repo: &dyn BudgetRepository,
+    month: &GenMonth,
+) -> Result<(), TestCaseError> {
+    let filters = std::iter::once(None).chain(CURRENCIES.map(Some));
+    for currency in filters {
+        let expected = model_overview(month, currency);
+        let actual = repo.overview(MONTH, YEAR, currency).await.unwrap();
+        prop_assert_eq!(actual.planned, expected.planned);
+        prop_assert_eq!(actual.spent, expected.spent);
+        prop_assert_eq!(actual.remaining, expected.remaining);
+        prop_assert_eq!(actual.currency, expected.currency);
+
+        let rows: Vec<(String, Decimal)> = repo
+            .category_budgets(MONTH, YEAR, currency)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.category_name, c.remaining))
+            .collect();
+        prop_assert_eq!(rows, model_categories(month, currency));
+    }
+
+    let expected: Vec<String> = month
+        .budgets
+        .iter()
+        .map(|b| b.currency.to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let actual = repo.currencies(&[(MONTH, YEAR)]).await.unwrap();
+    prop_assert_eq!(actual, expected);
+    Ok(())
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+fn memory_repository(month: &GenMonth) -> InMemoryBudgetRepository {
+    let repo = InMemoryBudgetRepository::new();
+    let groups: Vec<Uuid> = month
+        .group_sort_orders
+        .iter()
+        .enumerate()
+        .map(|(i, &sort_order)| {
+            repo.add_category_group(
+                &format!("Group {}", i),
+                "#224466",
+                sort_order,
+            )
+        })
+        .collect();
+
+    for (i, b) in month.budgets.iter().enumerate() {
+        let category = repo.add_category(
+            &GenMonth::category_name(i),
+            "#FF5733",
+            b.group.map(|g| groups[g]),
+        );
+        repo.insert_budget(Budget {
+            id: Uuid::new_v4(),
+            month: MONTH,
+            year: YEAR,
+            category_id: category,
+            planned: b.planned,
+            spent: b.spent,
+            carryover: b.carryover,
+            currency: b.currency.to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        });
+    }
+    repo
+}
+
+/// Replace the month's data in `pool` with `month`
+async fn load_postgres(pool: &PgPool, month: &GenMonth) {
+    pool.execute("TRUNCATE category_groups, categories, budgets CASCADE")
+        .await
+        .unwrap();
+
+    let mut groups = Vec::new();
+    for (i, sort_order) in month.group_sort_orders.iter().enumerate() {
+        let id: Uuid = sqlx::query(
+            "INSERT INTO category_groups (name, sort_order, color) \
+             VALUES ($1, $2, '#224466') RETURNING id",
+        )
+        .bind(format!("Group {}", i))
+        .bind(sort_order)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+        .get("id");
+        groups.push(id);
+    }
+
+    for (i, b) in month.budgets.iter().enumerate() {
+        let category: Uuid = sqlx::query(
+            "INSERT INTO categories (name, group_id, type, color) \
+             VALUES ($1, $2, 'expense', '#FF5733') RETURNING id",
+        )
+        .bind(GenMonth::category_name(i))
+        .bind(b.group.map(|g| groups[g]))
+        .fetch_one(pool)
+        .await
+        .unwrap()
+        .get("id");
+        sqlx::query(
+            "INSERT INTO budgets \
+             (month, year, category_id, planned, spent, carryover, currency) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(MONTH)
+        .bind(YEAR)
+        .bind(category)
+        .bind(b.planned)
+        .bind(b.spent)
+        .bind(b.carryover)
+        .bind(b.currency)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+}
+
+fn category(percentage: Decimal, name: &str) -> CategoryBudgetApi {
+    CategoryBudgetApi {
+        id: Uuid::new_v4().to_string(),
+        category_name: name.to_string(),
+        group_name: None,
+        category_color: "#FF5733".to_string(),
+        group_color: None,
+        planned: Decimal::ZERO,
+        spent: Decimal::ZERO,
+        remaining: Decimal::ZERO,
+        percentage,
+        currency: "EUR".to_string(),
+    }
+}
+
+proptest! {
+    /// Test: per currency, category remainings add up to the overview
+    /// Why: both are derived from `planned - spent + carryover` separately
+    /// Impact: the dashboard total always matches its category rows
+    #[test]
+    fn category_remainings_sum_to_overview(month in gen_month()) {
+        let repo = memory_repository(&month);
+        for currency in CURRENCIES {
+            let (overview, categories) = block_on(async {
+                (
+                    repo.overview(MONTH, YEAR, Some(currency)).await.unwrap(),
+                    repo.category_budgets(MONTH, YEAR, Some(currency))
+                        .await
+                        .unwrap(),
+                )
+            });
+            let sum = |f: fn(&CategoryBudgetApi) -> Decimal| {
+                categories.iter().map(f).sum::<Decimal>()
+            };
+            prop_assert_eq!(sum(|c| c.remaining), overview.remaining);
+            prop_assert_eq!(sum(|c| c.planned), overview.planned);
+            prop_assert_eq!(sum(|c| c.spent), overview.spent);
+        }
+    }
+
+    /// Test: percentages are non-negative and rounded to 2 places
+    /// Why: `round_dp(2)` of `spent / planned` must stay within half a
+    /// hundredth of the exact ratio, and a zero plan must not divide
+    /// Impact: progress bars never show negative or runaway values
+    #[test]
+    fn percentages_are_rounded_and_non_negative(
+        planned in amount(1_000_000_000),
+        spent in amount(1_000_000_000),
+        carryover in amount(1_000_000_000),
+    ) {
+        let api = CategoryBudgetApi::from(CategoryBudgetRow {
+            id: Uuid::new_v4(),
+            category_name: "Rent".to_string(),
+            group_name: None,
+            category_color: "#FF5733".to_string(),
+            group_color: None,
+            planned,
+            spent,
+            carryover,
+            currency: "EUR".to_string(),
+        });
+
+        prop_assert!(api.percentage >= Decimal::ZERO);
+        prop_assert!(api.percentage.scale() <= 2);
+        prop_assert_eq!(api.remaining, planned - spent + carryover);
+        if planned.is_zero() {
+            prop_assert_eq!(api.percentage, Decimal::ZERO);
+        } else {
+            let exact = spent * Decimal::from(100) / planned;
+            prop_assert!((api.percentage - exact).abs() <= Decimal::new(5001, 6));
+        }
+    }
+
+    /// Test: insights follow the 100% / 90% and remaining-sign thresholds
+    /// Why: the messages are the only user-facing summary of the month
+    /// Impact: no missing or contradictory warnings and suggestions
+    #[test]
+    fn insights_follow_thresholds(
+        remaining in (-1_000_000i64..=1_000_000).prop_map(|c| Decimal::new(c, 2)),
+        percentages in proptest::collection::vec(
+            (0i64..=20_000).prop_map(|p| Decimal::new(p, 2)),
+            0..8,
+        ),
+    ) {
+        let overview = BudgetOverviewApi {
+            planned: Decimal::ZERO,
+            spent: Decimal::ZERO,
+            remaining,
+            currency: "EUR".to_string(),
+        };
+        let categories: Vec<CategoryBudgetApi> = percentages
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| category(p, &GenMonth::category_name(i)))
+            .collect();
+        let insights = generate_budget_insights(&overview, &categories);
+
+        let over: Vec<&CategoryBudgetApi> = categories
+            .iter()
+            .filter(|c| c.percentage > Decimal::from(100))
+            .collect();
+        let category_warnings: Vec<&str> = insights
+            .iter()
+            .filter(|i| i.message.contains("over budget on"))
+            .map(|i| i.message.as_str())
+            .collect();
+        prop_assert_eq!(category_warnings.len(), over.len());
+        for (message, c) in category_warnings.iter().zip(&over) {
+            prop_assert!(message.ends_with(&c.category_name));
+        }
+
+        let has = |type_: &str, text: &str| {
+            insights
+                .iter()
+                .any(|i| i.type_ == type_ && i.message.contains(text))
+        };
+        prop_assert_eq!(has("positive", "remaining"), remaining > Decimal::ZERO);
+        prop_assert_eq!(
+            has("warning", "over your total budget"),
+            remaining < Decimal::ZERO
+        );
+        let near_limit = categories
+            .iter()
+            .any(|c| c.percentage > Decimal::from(90));
+        prop_assert_eq!(has("suggestion", "near budget limits"), near_limit);
+
+        let expected = over.len()
+            + usize::from(!remaining.is_zero())
+            + usize::from(near_limit);
+        prop_assert_eq!(insights.len(), expected);
+    }
+
+    /// Test: the in-memory repository agrees with the reference model
+    /// Why: handler tests use it in place of PostgreSQL
+    /// Impact: totals, ordering and currency selection match production
+    #[test]
+    fn memory_repository_matches_model(month in gen_month()) {
+        let repo = memory_repository(&month);
+        block_on(check_against_model(&repo, &month))?;
+    }
+}
+
+/// Test: the aggregation SQL agrees with the reference model
+/// Why: SUM/GROUP BY, COALESCE and the sort order are only checked here
+/// Impact: the model (and so the in-memory repository) stays faithful
+#[test]
+#[ignore = "needs PostgreSQL at DATABASE_URL (creates scratch databases)"]
+fn postgres_matches_model() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let db = runtime.block_on(TestDatabase::new());
+    let repo = PgBudgetRepository::new(db.pool.clone());
+
+    let mut runner = TestRunner::new(Config {
+        cases: 32,
+        ..Config::default()
+    });
+    runner
+        .run(&gen_month(), |month| {
+            runtime.block_on(async {
+                load_postgres(&db.pool, &month).await;
+                check_against_model(&repo, &month).await
+            })
+        })
+        .unwrap();
+
+    runtime.block_on(db.pool.close());
+}
@@ -0,0 +1,118 @@
+//! Tests for the in-memory cache backend and backend selection.
+
+mod common;
+
+use common::memory_budget_cache;
+use moneywise_backend::{
+    cache::{
+        core::backend::{CacheBackend, CacheBackendKind, InMemoryCacheBackend},
+        CacheConfig,
+    },
+    models::BudgetOverviewApi,
+};
+use rust_decimal::Decimal;
+use std::time::Duration;
+
+/// Test: `scan` follows Redis glob rules, including escapes
+/// Why: namespace flushes build `prefix*` patterns from real key names
+/// Impact: flushing works the same against Redis and the in-memory backend
+#[tokio::test]
+async fn scan_matches_redis_globs() {
+    let backend = InMemoryCacheBackend::default();
+    for key in ["ns:a:1", "ns:a:2", "ns:b:1", "ns*x", "other:a:1"] {
+        backend.set_ex(key, b"1", 60).await.unwrap();
+    }
+
+    let mut keys = backend.scan("ns:a:*").await.unwrap();
+    keys.sort();
+    assert_eq!(keys, ["ns:a:1", "ns:a:2"]);
+
+    let mut keys = backend.scan("ns:?:1").await.unwrap();
+    keys.sort();
+    assert_eq!(keys, ["ns:a:1", "ns:b:1"]);
+
+    assert_eq!(backend.scan("ns\\*x").await.unwrap(), ["ns*x"]);
+    assert_eq!(backend.scan("*").await.unwrap().len(), 5);
+}
+
+/// Test: tag sets collect keys and are emptied when taken
+/// Why: tag invalidation relies on `take_set` returning each key once
+/// Impact: a second invalidation of the same tag is a no-op
+#[tokio::test(start_paused = true)]
+async fn tag_sets_are_taken_once() {
+    let backend = InMemoryCacheBackend::default();
+    let tags = ["tag:a".to_string()];
+    backend.set_ex_tagged("k1", b"1", 10, &tags).await.unwrap();
+    backend.set_ex_tagged("k2", b"2", 60, &tags).await.unwrap();
+
+    // The set outlives its shortest-lived member
+    tokio::time::advance(Duration::from_secs(30)).await;
+
+    let mut keys = backend.take_set("tag:a").await.unwrap();
+    keys.sort();
+    assert_eq!(keys, ["k1", "k2"]);
+    assert!(backend.take_set("tag:a").await.unwrap().is_empty());
+}
+
+/// Test: locks are exclusive, owner-checked and expire
+/// Why: the fill lock must not be released by an instance that lost it
+/// Impact: cross-fill coordination behaves like the Redis `SET NX` lock
+#[tokio::test(start_paused = true)]
+async fn locks_are_exclusive_and_expire() {
+    let backend = InMemoryCacheBackend::default();
+
+    assert!(backend.try_lock("lock", "a", 1000).await.unwrap());
+    assert!(!backend.try_lock("lock", "b", 1000).await.unwrap());
+
+    // Wrong token leaves the lock in place
+    backend.unlock("lock", "b").await.unwrap();
+    assert!(!backend.try_lock("lock", "b", 1000).await.unwrap());
+
+    tokio::time::advance(Duration::from_millis(1500)).await;
+    assert!(backend.try_lock("lock", "b", 1000).await.unwrap());
+
+    backend.unlock("lock", "b").await.unwrap();
+    assert!(backend.try_lock("lock", "a", 1000).await.unwrap());
+}
+
+/// Test: `BudgetCache::flush` removes every budget key on the memory backend
+/// Why: the admin flush endpoint must work in Redis-less local development
+/// Impact: the whole budget namespace is cleared in one call
+#[tokio::test]
+async fn budget_flush_on_memory_backend() {
+    let (cache, backend) = memory_budget_cache(CacheConfig::default());
+    let overview = BudgetOverviewApi {
+        planned: Decimal::from(10),
+        spent: Decimal::ZERO,
+        remaining: Decimal::from(10),
+        currency: "USD".to_string(),
+    };
+    for month in ["1", "2", "3"] {
+        cache
+            .cache_budget_overview(month, "2025", None, &overview)
+            .await
+            .unwrap();
+    }
+    backend.set_ex("unrelated", b"1", 60).await.unwrap();
+
+    assert_eq!(cache.flush().await.unwrap(), 3);
+    // Tag sets live outside the namespace and expire on their own
+    assert!(backend
+        .scan("moneywise:{budget:*")
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(backend.get("unrelated").await.unwrap().is_some());
+}
+
+/// Test: backend names parse from their `CACHE_BACKEND` spellings
+/// Why: an unknown name must be rejected rather than silently picked
+/// Impact: `CACHE_BACKEND=memory` selects the in-process backend
+#[test]
+fn backend_kind_parses() {
+    for kind in [CacheBackendKind::Redis, CacheBackendKind::Memory] {
+        assert_eq!(kind.to_string().parse(), Ok(kind));
+    }
+    assert_eq!("MEMORY".parse(), Ok(CacheBackendKind::Memory));
+    assert!("memcached".parse::<CacheBackendKind>().is_err());
+}
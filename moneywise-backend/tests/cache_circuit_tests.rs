@@ -0,0 +1,326 @@
+//! Tests for cache timeouts, lazy Redis connections and the circuit breaker.
+//!
+//! Redis is replaced by local sockets: a closed port (refused), a listener
+//! that never answers (timeouts), and a minimal RESP responder (recovery).
+
+use async_trait::async_trait;
+use moneywise_backend::cache::{
+    core::{
+        backend::{
+            CacheBackend, CacheBackendKind, InMemoryCacheBackend,
+            RedisCacheBackend,
+        },
+        circuit::CircuitBreaker,
+        service::CacheService,
+    },
+    CacheConfig,
+};
+use moneywise_backend::error::{AppError, Result};
+use moneywise_backend::redis_topology::RedisTopology;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+fn redis_config(port: u16) -> CacheConfig {
+    CacheConfig {
+        backend: CacheBackendKind::Redis,
+        redis_url: format!("redis://127.0.0.1:{}", port),
+        redis_topology: RedisTopology::Standalone,
+        max_connections: 2,
+        connection_timeout: Duration::from_millis(200),
+        retry_attempts: 0,
+        circuit_failure_threshold: 2,
+        circuit_probe_interval: Duration::from_secs(60),
+        l1_enabled: false,
+        ..CacheConfig::default()
+    }
+}
+
+/// A port with nothing listening on it
+async fn closed_port() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+/// Answer every RESP command on `listener` with `+PONG` or `+OK`
+fn serve_fake_redis(listener: TcpListener) {
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buffer = Vec::new();
+                let mut chunk = [0u8; 1024];
+                while let Ok(read) = socket.read(&mut chunk).await {
+                    if read == 0 {
+                        return;
+                    }
+                    buffer.extend_from_slice(&chunk[..read]);
+                    while let Some((len, is_ping)) = parse_command(&buffer) {
+                        buffer.drain(..len);
+                        let reply: &[u8] =
+                            if is_ping { b"+PONG\r\n" } else { b"+OK\r\n" };
+                        if socket.write_all(reply).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Length of the first complete `*N` array of bulk strings in `buffer`,
+/// and whether it is a `PING`
+fn parse_command(buffer: &[u8]) -> Option<(usize, bool)> {
+    fn line(buffer: &[u8], at: usize) -> Option<(&[u8], usize)> {
+        let end = buffer[at..].windows(2).position(|w| w == b"\r\n")? + at;
+        Some((&buffer[at..end], end + 2))
+    }
+    let number = |bytes: &[u8]| std::str::from_utf8(bytes).ok()?.parse().ok();
+
+    let (header, mut at) = line(buffer, 0)?;
+    let args: usize = number(header.strip_prefix(b"*")?)?;
+    let mut name = Vec::new();
+    for i in 0..args {
+        let (header, start) = line(buffer, at)?;
+        let len: usize = number(header.strip_prefix(b"$")?)?;
+        if buffer.len() < start + len + 2 {
+            return None;
+        }
+        if i == 0 {
+            name = buffer[start..start + len].to_ascii_uppercase();
+        }
+        at = start + len + 2;
+    }
+    Some((at, name == b"PING"))
+}
+
+/// Test: the breaker opens after the threshold, once, and can be closed
+/// Why: only the failure that opens the circuit may start a probe
+/// Impact: one background probe per outage, not one per failed request
+#[test]
+fn breaker_opens_at_threshold() {
+    let breaker = CircuitBreaker::new(3);
+    assert!(!breaker.record_failure());
+    breaker.record_success();
+    assert!(!breaker.record_failure());
+    assert!(!breaker.record_failure());
+    assert!(breaker.record_failure());
+    assert!(breaker.is_open());
+    assert!(!breaker.record_failure());
+
+    breaker.close();
+    assert!(!breaker.is_open());
+    assert!(!breaker.record_failure());
+
+    let disabled = CircuitBreaker::new(0);
+    for _ in 0..10 {
+        assert!(!disabled.record_failure());
+    }
+    assert!(!disabled.is_open());
+}
+
+/// Test: a down Redis neither blocks nor fails startup, and reads miss
+/// Why: `init_connections` used to abort when Redis was unreachable
+/// Impact: the API starts and serves from the database during an outage
+#[tokio::test]
+async fn unreachable_redis_is_bypassed() {
+    let service = CacheService::new(redis_config(closed_port().await))
+        .await
+        .expect("startup must not connect to Redis");
+
+    for _ in 0..2 {
+        assert_eq!(
+            service
+                .get_cached_data::<u32>("moneywise:circ:1")
+                .await
+                .unwrap(),
+            None
+        );
+    }
+    assert!(service.stats().circuit_open);
+
+    // Short-circuited: no connection attempt, writes fail fast
+    let started = Instant::now();
+    assert!(service
+        .get_cached_data::<u32>("moneywise:circ:1")
+        .await
+        .unwrap()
+        .is_none());
+    assert!(service
+        .cache_data("moneywise:circ:1", &1u32, 60)
+        .await
+        .is_err());
+    assert!(started.elapsed() < Duration::from_millis(50));
+}
+
+/// Test: a server that never answers is cut off by `connection_timeout`
+/// Why: a hung Redis must not hang requests
+/// Impact: each cache call waits at most the configured timeout per attempt
+#[tokio::test]
+async fn silent_redis_times_out() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    // Accept connections but never reply
+    tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            sockets.push(socket);
+        }
+    });
+
+    let backend = RedisCacheBackend::new(&redis_config(port)).unwrap();
+    let started = Instant::now();
+    assert_eq!(backend.get("moneywise:circ:2").await.unwrap(), None);
+    assert!(backend.set_ex("moneywise:circ:2", b"1", 60).await.is_err());
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+/// Test: the background probe closes the circuit once Redis answers
+/// Why: the cache must come back on its own after an outage
+/// Impact: no restart is needed when Redis recovers
+#[tokio::test]
+async fn probe_closes_circuit_when_redis_returns() {
+    let port = closed_port().await;
+    let config = CacheConfig {
+        circuit_failure_threshold: 1,
+        circuit_probe_interval: Duration::from_millis(50),
+        ..redis_config(port)
+    };
+    let backend = RedisCacheBackend::new(&config).unwrap();
+
+    assert!(backend.set_ex("moneywise:circ:3", b"1", 60).await.is_err());
+    assert!(!backend.is_available());
+
+    serve_fake_redis(TcpListener::bind(("127.0.0.1", port)).await.unwrap());
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !backend.is_available() {
+        assert!(Instant::now() < deadline, "circuit never closed");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    backend.set_ex("moneywise:circ:3", b"1", 60).await.unwrap();
+}
+
+/// In-memory backend that fails every call while `down` is set
+struct FlakyBackend {
+    inner: InMemoryCacheBackend,
+    down: AtomicBool,
+}
+
+impl FlakyBackend {
+    fn check(&self) -> Result<()> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err(AppError::Cache(
+                (redis::ErrorKind::IoError, "Cache circuit open").into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CacheBackend for FlakyBackend {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.check()?;
+        self.inner.get(key).await
+    }
+
+    async fn get_with_ttl(
+        &self,
+        key: &str,
+    ) -> Result<Option<(Vec<u8>, Option<Duration>)>> {
+        self.check()?;
+        self.inner.get_with_ttl(key).await
+    }
+
+    async fn set_ex(&self, key: &str, value: &[u8], ttl: usize) -> Result<()> {
+        self.check()?;
+        self.inner.set_ex(key, value, ttl).await
+    }
+
+    async fn set_ex_tagged(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl: usize,
+        tag_keys: &[String],
+    ) -> Result<()> {
+        self.check()?;
+        self.inner.set_ex_tagged(key, value, ttl, tag_keys).await
+    }
+
+    async fn del(&self, keys: &[&str]) -> Result<()> {
+        self.check()?;
+        self.inner.del(keys).await
+    }
+
+    async fn scan(&self, pattern: &str) -> Result<Vec<String>> {
+        self.check()?;
+        self.inner.scan(pattern).await
+    }
+
+    async fn take_set(&self, key: &str) -> Result<Vec<String>> {
+        self.check()?;
+        self.inner.take_set(key).await
+    }
+
+    async fn try_lock(&self, key: &str, token: &str, ttl: u64) -> Result<bool> {
+        self.check()?;
+        self.inner.try_lock(key, token, ttl).await
+    }
+
+    async fn unlock(&self, key: &str, token: &str) -> Result<()> {
+        self.check()?;
+        self.inner.unlock(key, token).await
+    }
+
+    async fn publish(&self, channel: &str, message: &str) -> Result<usize> {
+        self.check()?;
+        self.inner.publish(channel, message).await
+    }
+
+    fn is_available(&self) -> bool {
+        !self.down.load(Ordering::SeqCst)
+    }
+
+    fn name(&self) -> &'static str {
+        "flaky"
+    }
+}
+
+/// Test: invalidations made while the backend is down fail, and are
+/// replayed before the first read after it recovers
+/// Why: budget writes during an outage would otherwise leave stale
+/// entries that are served until their TTL once Redis is back
+/// Impact: reads after an outage never see data invalidated during it
+#[tokio::test]
+async fn missed_invalidations_replayed_after_outage() {
+    let backend = Arc::new(FlakyBackend {
+        inner: InMemoryCacheBackend::new(100),
+        down: AtomicBool::new(false),
+    });
+    let config = CacheConfig {
+        backend: CacheBackendKind::Memory,
+        l1_enabled: false,
+        ..CacheConfig::default()
+    };
+    let service = CacheService::with_backend(config, backend.clone());
+    let (key, tagged) = ("moneywise:circ:4", "moneywise:circ:5");
+    service.cache_data(key, &1u32, 60).await.unwrap();
+    service
+        .cache_data_tagged(tagged, &2u32, 60, &["circ:period"])
+        .await
+        .unwrap();
+
+    backend.down.store(true, Ordering::SeqCst);
+    assert!(service.invalidate_cache(key).await.is_err());
+    assert!(service.invalidate_tag("circ:period").await.is_err());
+
+    backend.down.store(false, Ordering::SeqCst);
+    assert_eq!(service.get_cached_data::<u32>(key).await.unwrap(), None);
+    assert_eq!(service.get_cached_data::<u32>(tagged).await.unwrap(), None);
+    assert_eq!(backend.inner.get(key).await.unwrap(), None);
+}
@@ -0,0 +1,87 @@
+//! Tests for schema-versioned cache keys.
+
+use moneywise_backend::cache::{
+    core::schema::{schema_version, struct_shape},
+    domains::budget::keys,
+};
+use serde::Deserialize;
+
+/// Old and new shape of a cached model, as seen by two instances mid-deploy
+mod before {
+    #[derive(serde::Deserialize)]
+    #[allow(dead_code)]
+    pub struct Overview {
+        pub planned: String,
+        pub spent: String,
+    }
+}
+
+mod after {
+    #[derive(serde::Deserialize)]
+    #[allow(dead_code)]
+    pub struct Overview {
+        pub planned: String,
+        pub spent: String,
+        pub remaining: String,
+    }
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Renamed {
+    #[serde(rename = "total")]
+    planned: String,
+}
+
+/// Test: adding a field to a cached model changes its schema version
+/// Why: old and new instances must not read each other's entries during a rolling deploy
+/// Impact: a model change moves its entries to new keys instead of purging them as corrupt
+#[test]
+fn field_changes_change_the_version() {
+    let old = schema_version::<before::Overview>(1);
+    let new = schema_version::<after::Overview>(1);
+    assert_ne!(old, new);
+
+    // Stable across calls, and bumped by the manual revision
+    assert_eq!(old, schema_version::<before::Overview>(1));
+    assert_ne!(old, schema_version::<before::Overview>(2));
+    assert!(old.starts_with('v') && old.len() == 9);
+}
+
+/// Test: the fingerprint uses serialized field names
+/// Why: serde renames change the cached JSON even when the Rust field is unchanged
+/// Impact: renames are versioned like any other shape change
+#[test]
+fn fingerprint_uses_serde_field_names() {
+    let (name, fields) = struct_shape::<Renamed>().unwrap();
+    assert_eq!(name, "Renamed");
+    assert_eq!(fields, &["total"]);
+    assert!(struct_shape::<Vec<String>>().is_none());
+}
+
+/// Test: every budget key embeds its model's version under the namespace
+/// Why: the namespace flush relies on all versions sharing `keys::NAMESPACE`
+/// Impact: documents the key layouts `moneywise:{budget:<period>}:<version>:<kind>:...`
+/// and `moneywise:{budget:item:<id>}:<version>`
+#[test]
+fn budget_keys_embed_versions() {
+    let overview = keys::overview_key("8", "2025", Some("USD"));
+    assert_eq!(
+        overview,
+        format!(
+            "moneywise:{{budget:2025-08}}:{}:overview:8:2025:USD",
+            keys::overview_version()
+        )
+    );
+    assert!(
+        keys::categories_key("8", "2025", None).starts_with(&format!(
+            "{}2025-08}}:{}:",
+            keys::NAMESPACE,
+            keys::categories_version()
+        ))
+    );
+    assert_eq!(
+        keys::budget_key("abc"),
+        format!("{}item:abc}}:{}", keys::NAMESPACE, keys::budget_version())
+    );
+}
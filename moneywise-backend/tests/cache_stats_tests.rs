@@ -0,0 +1,102 @@
+//! Tests for cache statistics and prefix-based key inspection.
+//!
+//! Counters are process-wide, so each test uses its own key domain.
+
+mod common;
+
+use common::memory_budget_cache;
+use moneywise_backend::cache::{
+    core::backend::{prefix_pattern, CacheBackend},
+    CacheConfig,
+};
+
+/// Test: hits, misses and purged corrupt values are counted per domain
+/// Why: `GET /admin/cache/stats` must show whether the cache helps
+/// Impact: the hit ratio reflects real reads, with corrupt values as misses
+#[tokio::test]
+async fn lookups_are_counted_per_domain() {
+    let (cache, backend) = memory_budget_cache(CacheConfig::default());
+    let service = cache.service();
+
+    assert!(service
+        .get_cached_data::<u32>("moneywise:statsa:1")
+        .await
+        .unwrap()
+        .is_none());
+    service
+        .cache_data("moneywise:statsa:1", &7u32, 60)
+        .await
+        .unwrap();
+    for _ in 0..3 {
+        assert_eq!(
+            service
+                .get_cached_data::<u32>("moneywise:statsa:1")
+                .await
+                .unwrap(),
+            Some(7)
+        );
+    }
+    backend
+        .set_ex("moneywise:statsa:2", b"{not-json", 60)
+        .await
+        .unwrap();
+    assert!(service
+        .get_cached_data::<u32>("moneywise:statsa:2")
+        .await
+        .unwrap()
+        .is_none());
+
+    let stats = service.stats();
+    assert_eq!(stats.backend, "memory");
+    let domain = &stats.domains["statsa"];
+    assert_eq!((domain.hits, domain.misses, domain.corrupt), (3, 1, 1));
+    assert_eq!(domain.hit_ratio, Some(0.6));
+    assert_eq!(domain.latency["get"].count, 5);
+    assert_eq!(domain.latency["set"].count, 1);
+    assert!(stats.hits >= 3);
+}
+
+/// Test: a filled miss counts once, not once per internal re-check
+/// Why: `get_or_fill` re-reads the key inside the single flight
+/// Impact: misses match the number of database loads
+#[tokio::test]
+async fn get_or_fill_counts_one_miss() {
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
+    let service = cache.service();
+
+    for _ in 0..2 {
+        let value = service
+            .get_or_fill("moneywise:statsb:1", 60, &[], || async { Ok(1u8) })
+            .await
+            .unwrap();
+        assert_eq!(value, 1);
+    }
+
+    let domain = &service.stats().domains["statsb"];
+    assert_eq!((domain.hits, domain.misses), (1, 1));
+}
+
+/// Test: prefixes are matched literally, even with glob characters
+/// Why: admin prefixes are user input and must not widen a purge
+/// Impact: `DELETE /admin/cache/keys?prefix=moneywise:*` deletes nothing
+/// but keys literally starting with `moneywise:*`
+#[tokio::test]
+async fn prefixes_match_literally() {
+    assert_eq!(prefix_pattern("a*b?[c]\\"), "a\\*b\\?\\[c\\]\\\\*");
+
+    let (cache, backend) = memory_budget_cache(CacheConfig::default());
+    for key in ["moneywise:statsc:1", "moneywise:statsc:2", "moneywise:*x"] {
+        backend.set_ex(key, b"1", 60).await.unwrap();
+    }
+    let service = cache.service();
+
+    let mut keys = service.keys_with_prefix("moneywise:statsc:").await.unwrap();
+    keys.sort();
+    assert_eq!(keys, ["moneywise:statsc:1", "moneywise:statsc:2"]);
+
+    assert_eq!(service.flush_namespace("moneywise:*").await.unwrap(), 1);
+    assert_eq!(
+        service.keys_with_prefix("moneywise:").await.unwrap().len(),
+        2
+    );
+}
@@ -2,7 +2,7 @@
 
 mod common;
 
-use common::MockBudgetCache;
+use common::memory_budget_cache;
 use moneywise_backend::{cache::CacheConfig, models::CategoryBudgetApi};
 use rust_decimal::Decimal;
 
@@ -11,7 +11,7 @@ use rust_decimal::Decimal;
 /// Impact: gives confidence that category pages can leverage the cache without surprises
 #[tokio::test]
 async fn categories_roundtrip() {
-    let cache = MockBudgetCache::new(CacheConfig::default());
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
     let categories = vec![
         CategoryBudgetApi {
             id: "1".to_string(),
@@ -43,17 +43,20 @@ async fn categories_roundtrip() {
     assert!(cache
         .get_cached_category_budgets("January", "2024", Some("USD"))
         .await
+        .unwrap()
         .is_none());
 
     // cache the categories
     cache
         .cache_category_budgets("January", "2024", Some("USD"), &categories)
-        .await;
+        .await
+        .unwrap();
 
     // verify the categories are cached
     let cached = cache
         .get_cached_category_budgets("January", "2024", Some("USD"))
-        .await;
+        .await
+        .unwrap();
     assert!(cached.is_some());
 
     // verify the categories are in the cache
@@ -0,0 +1,157 @@
+//! Tests for cache value codecs and compression.
+
+use moneywise_backend::{
+    cache::core::{
+        codec::{decode, inspect, CacheCompression, CacheFormat, Codec},
+        serialization::serialize,
+    },
+    models::{BudgetApi, CategoryBudgetApi},
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+const FORMATS: [CacheFormat; 3] = [
+    CacheFormat::Json,
+    CacheFormat::MessagePack,
+    CacheFormat::Bincode,
+];
+const COMPRESSIONS: [CacheCompression; 3] = [
+    CacheCompression::None,
+    CacheCompression::Zstd,
+    CacheCompression::Lz4,
+];
+
+fn categories(count: usize) -> Vec<CategoryBudgetApi> {
+    (0..count)
+        .map(|i| CategoryBudgetApi {
+            id: format!("budget-{}", i),
+            category_name: format!("Category {}", i),
+            group_name: (i % 2 == 0).then(|| "Essentials".to_string()),
+            category_color: "#FF0000".to_string(),
+            group_color: None,
+            planned: Decimal::from_str("1200.50").unwrap(),
+            spent: Decimal::from_str("850.25").unwrap(),
+            remaining: Decimal::from_str("350.25").unwrap(),
+            percentage: Decimal::from_str("70.83").unwrap(),
+            currency: "EUR".to_string(),
+        })
+        .collect()
+}
+
+fn codec(format: CacheFormat, compression: CacheCompression) -> Codec {
+    Codec {
+        format,
+        compression,
+        compression_threshold: 0,
+    }
+}
+
+/// Test: every format/compression combination round-trips budget models
+/// Why: decimals and timestamps must survive binary formats, not just JSON
+/// Impact: any `CACHE_CODEC`/`CACHE_COMPRESSION` setting is safe to deploy
+#[test]
+fn all_codecs_round_trip() {
+    let list = categories(20);
+    let budget = BudgetApi {
+        id: "budget-1".to_string(),
+        month: 8,
+        year: 2025,
+        category_id: "category-1".to_string(),
+        planned: Decimal::from_str("300.00").unwrap(),
+        spent: Decimal::from(0),
+        carryover: Decimal::from_str("10.5").unwrap(),
+        currency: "USD".to_string(),
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+
+    for format in FORMATS {
+        for compression in COMPRESSIONS {
+            let codec = codec(format, compression);
+
+            let value = codec.encode(&list).unwrap();
+            assert_eq!(inspect(&value), Some((format, compression)));
+            let decoded: Vec<CategoryBudgetApi> =
+                decode(&value).unwrap().unwrap();
+            assert_eq!(decoded.len(), list.len());
+            assert_eq!(decoded[3].planned, list[3].planned);
+            assert_eq!(decoded[3].group_name, list[3].group_name);
+
+            let value = codec.encode(&budget).unwrap();
+            let decoded: BudgetApi = decode(&value).unwrap().unwrap();
+            assert_eq!(decoded.carryover, budget.carryover);
+            assert_eq!(decoded.created_at, budget.created_at);
+        }
+    }
+}
+
+/// Test: values written before codecs existed still decode
+/// Why: a deploy must not turn every existing JSON entry into a miss
+/// Impact: switching codecs needs no cache flush
+#[test]
+fn legacy_json_values_decode() {
+    let list = categories(2);
+    let legacy = serialize(&list).unwrap();
+
+    assert_eq!(inspect(legacy.as_bytes()), None);
+    let decoded: Vec<CategoryBudgetApi> =
+        decode(legacy.as_bytes()).unwrap().unwrap();
+    assert_eq!(decoded[1].id, "budget-1");
+}
+
+/// Test: compression only applies from the configured threshold
+/// Why: compressing tiny overview payloads costs CPU and can grow them
+/// Impact: small values stay uncompressed; large category lists shrink
+#[test]
+fn compression_respects_threshold() {
+    let codec = Codec {
+        format: CacheFormat::Json,
+        compression: CacheCompression::Zstd,
+        compression_threshold: 1024,
+    };
+
+    let small = codec.encode(&categories(1)).unwrap();
+    assert_eq!(
+        inspect(&small),
+        Some((CacheFormat::Json, CacheCompression::None))
+    );
+
+    let large_list = categories(200);
+    let large = codec.encode(&large_list).unwrap();
+    assert_eq!(
+        inspect(&large),
+        Some((CacheFormat::Json, CacheCompression::Zstd))
+    );
+    assert!(large.len() < serialize(&large_list).unwrap().len() / 4);
+}
+
+/// Test: corrupt or mismatched values decode to a miss
+/// Why: readers must degrade to the database instead of failing requests
+/// Impact: mirrors the self-healing contract of `get_value`
+#[test]
+fn corrupt_values_are_misses() {
+    let value = codec(CacheFormat::MessagePack, CacheCompression::Lz4)
+        .encode(&categories(5))
+        .unwrap();
+
+    assert!(decode::<Vec<CategoryBudgetApi>>(&value[..value.len() / 2])
+        .unwrap()
+        .is_none());
+    assert!(decode::<BudgetApi>(&value).unwrap().is_none());
+    assert!(decode::<BudgetApi>(&[]).unwrap().is_none());
+}
+
+/// Test: codec and compression names parse from their env var spellings
+/// Why: they are configured with `CACHE_CODEC` and `CACHE_COMPRESSION`
+/// Impact: a typo falls back to the default instead of writing an unknown format
+#[test]
+fn codec_names_parse() {
+    for format in FORMATS {
+        assert_eq!(format.to_string().parse(), Ok(format));
+    }
+    for compression in COMPRESSIONS {
+        assert_eq!(compression.to_string().parse(), Ok(compression));
+    }
+    assert!("protobuf".parse::<CacheFormat>().is_err());
+    assert!("gzip".parse::<CacheCompression>().is_err());
+}
@@ -0,0 +1,77 @@
+// Purpose: the API router as mounted by `main`, driven in-process.
+// Why: handler tests need the real routes, rate limiting and cache without
+// binding a port.
+// Impact: the same request helper serves in-memory and PostgreSQL tests.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware, Router,
+};
+use moneywise_backend::{
+    api::{create_api_router, create_metrics_router},
+    cache::CacheConfig,
+    metrics::http::track_http_metrics,
+    rate_limiter::{
+        middleware::rate_limit_middleware, ClassifiedRouter, InMemoryBackend,
+        RateLimitConfig, RateLimitService,
+    },
+    repository::SharedBudgetRepository,
+    server::request_id::request_id_middleware,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+use super::memory_budget_cache;
+
+/// `/api` and `/metrics` over `repo`, with in-memory rate limiting and cache
+/// and the request ID layer
+pub fn app(repo: SharedBudgetRepository) -> Router {
+    let (router, table) = ClassifiedRouter::new()
+        .nest("/api", create_api_router())
+        .merge(create_metrics_router())
+        .into_parts();
+    let rate_limiter = RateLimitService::with_backend(
+        RateLimitConfig::in_memory(),
+        Arc::new(InMemoryBackend::default()),
+    )
+    .with_route_table(table);
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
+
+    router
+        .layer(middleware::from_fn_with_state(
+            Arc::new(rate_limiter),
+            rate_limit_middleware,
+        ))
+        .layer(middleware::from_fn(track_http_metrics))
+        .layer(middleware::from_fn(request_id_middleware))
+        .with_state((repo, cache))
+}
+
+/// Send a request and return its status and JSON body
+pub async fn send(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("x-forwarded-for", "10.0.0.1")
+        .header("content-type", "application/json");
+    let body = match body {
+        Some(body) => Body::from(body.to_string()),
+        None => Body::empty(),
+    };
+
+    let res = app
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
@@ -0,0 +1,90 @@
+// Purpose: throwaway PostgreSQL databases for end-to-end tests.
+// Why: create/update and the aggregation SQL can only be checked against a
+// real server; each test gets its own database so tests can run in
+// parallel and leave nothing behind. Migrations write to the `public`
+// schema by name, so isolation is per database rather than per schema.
+// Impact: tests using this are `#[ignore]`d and need `DATABASE_URL` to point
+// at a local server whose user may create databases. Point it at a migrated
+// database: the `query!` macros check against it when the crate rebuilds.
+//   DATABASE_URL=postgresql://postgres@localhost:5432/moneywise \
+//     cargo test --test budget_e2e_tests -- --include-ignored
+
+use moneywise_backend::{
+    database::migrations::run_migrations, maintenance::seed_sample_data,
+};
+use sqlx::{Connection, Executor, PgConnection, PgPool};
+use uuid::Uuid;
+
+/// A migrated database on the server at `DATABASE_URL`, dropped with it
+pub struct TestDatabase {
+    pub pool: PgPool,
+    server_url: String,
+    name: String,
+}
+
+impl TestDatabase {
+    /// A migrated database; migrations create no rows, so it holds no
+    /// category groups, categories or budgets
+    pub async fn new() -> Self {
+        let server_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must point at a PostgreSQL server");
+        let name = format!("moneywise_test_{}", Uuid::new_v4().simple());
+
+        let mut admin = PgConnection::connect(&server_url).await.unwrap();
+        admin
+            .execute(format!("CREATE DATABASE {}", name).as_str())
+            .await
+            .unwrap();
+
+        let url = match server_url.rsplit_once('/') {
+            Some((server, _)) => format!("{}/{}", server, name),
+            None => panic!("DATABASE_URL has no database name"),
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        Self {
+            pool,
+            server_url,
+            name,
+        }
+    }
+
+    /// A migrated database holding the sample data of `moneywise-admin seed`
+    pub async fn with_sample_data() -> Self {
+        let db = Self::new().await;
+        seed_sample_data(&db.pool).await.unwrap();
+        db
+    }
+}
+
+impl Drop for TestDatabase {
+    // Also runs when the test panics; `FORCE` closes the pool's connections
+    fn drop(&mut self) {
+        let (server_url, name) = (self.server_url.clone(), self.name.clone());
+        let dropped = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async {
+                let mut admin = PgConnection::connect(&server_url).await?;
+                admin
+                    .execute(
+                        format!(
+                            "DROP DATABASE IF EXISTS {} WITH (FORCE)",
+                            name
+                        )
+                        .as_str(),
+                    )
+                    .await
+                    .map(|_| ())
+            })
+        })
+        .join();
+
+        if !matches!(dropped, Ok(Ok(()))) {
+            eprintln!("failed to drop test database {}", self.name);
+        }
+    }
+}
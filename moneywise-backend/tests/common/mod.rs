@@ -1,10 +1,37 @@
 #![allow(dead_code)]
-// Purpose: Shared test utilities for cache-focused integration tests.
-// Why: avoids duplication across test files by centralizing mock types.
+// Purpose: Shared test utilities for integration tests.
+// Why: avoids duplication across test files by centralizing cache, router
+// and database setup.
 // Impact: keeps tests concise and consistent, while permitting unused items
 // in specific crates without noisy warnings.
 
-pub mod mock_budget_cache;
-pub mod mock_redis;
+pub mod api;
+pub mod db;
 
-pub use mock_budget_cache::MockBudgetCache;
+use std::sync::Arc;
+
+use moneywise_backend::cache::{
+    core::backend::InMemoryCacheBackend, domains::budget::BudgetCache,
+    CacheConfig,
+};
+
+/// The real `BudgetCache` over an in-memory backend holding at most
+/// `max_entries` values; the backend is returned for direct inspection.
+pub fn memory_budget_cache_with_capacity(
+    config: CacheConfig,
+    max_entries: usize,
+) -> (BudgetCache, Arc<InMemoryCacheBackend>) {
+    let backend = Arc::new(InMemoryCacheBackend::new(max_entries));
+    let cache = BudgetCache::with_backend(config, backend.clone());
+    (cache, backend)
+}
+
+/// `memory_budget_cache_with_capacity` with the default capacity
+pub fn memory_budget_cache(
+    config: CacheConfig,
+) -> (BudgetCache, Arc<InMemoryCacheBackend>) {
+    memory_budget_cache_with_capacity(
+        config,
+        moneywise_backend::cache::core::backend::memory::DEFAULT_MAX_ENTRIES,
+    )
+}
@@ -8,20 +8,40 @@
 // - Each test includes short notes on intent (Test), reason (Why), and production value (Impact).
 
 use moneywise_backend::database;
-use moneywise_backend::{cache::core::service::CacheService, CacheConfig};
+use moneywise_backend::{
+    cache::core::{
+        backend::CacheBackendKind, codec::Codec, service::CacheService,
+    },
+    redis_topology::RedisTopology,
+    CacheConfig,
+};
 use std::env;
 use std::time::Duration;
 
 #[test]
 fn test_cache_config_usage() {
     let config = CacheConfig {
+        backend: CacheBackendKind::Redis,
+        memory_max_entries: 10_000,
         redis_url: "redis://localhost:6379".to_string(),
+        redis_topology: RedisTopology::Standalone,
         overview_ttl: Duration::from_secs(900),
         categories_ttl: Duration::from_secs(300),
         budget_ttl: Duration::from_secs(600),
         max_connections: 15,
         connection_timeout: Duration::from_secs(10),
         retry_attempts: 5,
+        circuit_failure_threshold: 5,
+        circuit_probe_interval: Duration::from_secs(5),
+        fill_lock: false,
+        fill_lock_ttl: Duration::from_millis(5000),
+        l1_enabled: false,
+        l1_max_entries: 1000,
+        l1_ttl: Duration::from_secs(30),
+        codec: Codec::default(),
+        refresh_ahead: Duration::from_secs(60),
+        warmup_enabled: true,
+        warmup_interval: Duration::from_secs(600),
     };
 
     // Test: basic field access on explicit config
@@ -37,11 +57,13 @@ fn test_cache_config_usage() {
 // Impact: improves operability by surfacing configuration issues immediately
 #[tokio::test]
 async fn test_cache_service_invalid_url_returns_error() {
-    let mut config = CacheConfig::default();
-    // Use a clearly malformed URL so `Client::open` fails synchronously.
-    config.redis_url = "invalid://".to_string();
-    // Keep attempts minimal to fail fast.
-    config.max_connections = 1;
+    let config = CacheConfig {
+        // Use a clearly malformed URL so `Client::open` fails synchronously.
+        redis_url: "invalid://".to_string(),
+        // Keep attempts minimal to fail fast.
+        max_connections: 1,
+        ..CacheConfig::default()
+    };
 
     let result = CacheService::new(config).await;
     assert!(result.is_err());
@@ -53,8 +75,10 @@ async fn test_cache_service_invalid_url_returns_error() {
 #[tokio::test]
 #[should_panic]
 async fn test_cache_service_zero_pool_panics_on_use() {
-    let mut config = CacheConfig::default();
-    config.max_connections = 0;
+    let config = CacheConfig {
+        max_connections: 0,
+        ..CacheConfig::default()
+    };
 
     // Any operation that selects a connection will panic (modulo-by-zero on empty pool).
     if let Ok(service) = CacheService::new(config).await {
@@ -0,0 +1,223 @@
+//! Tests for per-transaction-type degradation policies when the rate limit
+//! backend is unavailable.
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware, Router,
+};
+use moneywise_backend::rate_limiter::{
+    backend::CounterState,
+    config::DegradationPolicies,
+    metrics::{decision_count, Decision},
+    middleware::rate_limit_middleware,
+    types::{DegradationPolicy, RateLimitError, TransactionType},
+    ClassifiedRouter, RateLimitBackend, RateLimitConfig, RateLimitService,
+    RedisBackend,
+};
+use moneywise_backend::redis_topology::RedisTopology;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tower::ServiceExt;
+
+/// Backend that fails every operation, like an unreachable Redis
+struct DownBackend;
+
+fn down() -> RateLimitError {
+    RateLimitError::RedisError(redis::RedisError::from((
+        redis::ErrorKind::IoError,
+        "connection refused",
+    )))
+}
+
+#[async_trait]
+impl RateLimitBackend for DownBackend {
+    async fn increment(&self, _: &str, _: u64) -> Result<u32, RateLimitError> {
+        Err(down())
+    }
+
+    async fn peek(
+        &self,
+        _: &str,
+    ) -> Result<Option<CounterState>, RateLimitError> {
+        Err(down())
+    }
+
+    async fn reset(&self, _: &str) -> Result<bool, RateLimitError> {
+        Err(down())
+    }
+
+    fn name(&self) -> &'static str {
+        "down"
+    }
+}
+
+async fn ok() -> &'static str {
+    "ok"
+}
+
+fn app(degradation: DegradationPolicies) -> Router {
+    app_with(degradation, Arc::new(DownBackend))
+}
+
+fn app_with(
+    degradation: DegradationPolicies,
+    backend: Arc<dyn RateLimitBackend>,
+) -> Router {
+    let (router, table) = ClassifiedRouter::new()
+        .post("/budgets", ok, TransactionType::BudgetModification)
+        .get("/budgets", ok, TransactionType::BudgetRead)
+        .into_parts();
+
+    let config = RateLimitConfig {
+        degradation,
+        ..RateLimitConfig::in_memory()
+    };
+    let service =
+        RateLimitService::with_backend(config, backend).with_route_table(table);
+
+    router.layer(middleware::from_fn_with_state(
+        Arc::new(service),
+        rate_limit_middleware,
+    ))
+}
+
+fn request(method: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri("/budgets")
+        .header("x-forwarded-for", "10.1.1.1")
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Test: writes fail closed while reads fail open during an outage
+/// Why: a Redis outage must not silently disable write protection
+/// Impact: fail-closed types get 503 + Retry-After; fail-open types are served
+#[tokio::test]
+async fn fail_closed_writes_and_fail_open_reads() {
+    let app = app(DegradationPolicies {
+        budget_modification: DegradationPolicy::FailClosed,
+        ..DegradationPolicies::uniform(DegradationPolicy::FailOpen)
+    });
+
+    let write = app.clone().oneshot(request("POST")).await.unwrap();
+    assert_eq!(write.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(write.headers()["X-RateLimit-Status"], "degraded");
+    assert!(write.headers().contains_key("Retry-After"));
+
+    let read = app.oneshot(request("GET")).await.unwrap();
+    assert_eq!(read.status(), StatusCode::OK);
+    assert_eq!(read.headers()["X-RateLimit-Status"], "degraded");
+}
+
+/// Test: fail-closed rejections and fail-open passes are counted apart
+/// Why: both used to be recorded as `degraded`
+/// Impact: dashboards show how many requests an outage actually rejected
+#[tokio::test]
+async fn outage_decisions_are_labelled() {
+    let app = app(DegradationPolicies {
+        budget_modification: DegradationPolicy::FailClosed,
+        ..DegradationPolicies::uniform(DegradationPolicy::FailOpen)
+    });
+    let (write, read) = (
+        TransactionType::BudgetModification,
+        TransactionType::BudgetRead,
+    );
+    let unavailable_before = decision_count(Decision::Unavailable, write);
+    let degraded_before = decision_count(Decision::Degraded, read);
+
+    app.clone().oneshot(request("POST")).await.unwrap();
+    app.oneshot(request("GET")).await.unwrap();
+
+    assert!(decision_count(Decision::Unavailable, write) > unavailable_before);
+    assert!(decision_count(Decision::Degraded, read) > degraded_before);
+    assert_eq!(decision_count(Decision::Unavailable, read), 0);
+}
+
+/// Test: the local fallback keeps approximate limits while the backend is down
+/// Why: fail-open alone lets a client flood writes during an outage
+/// Impact: writes are still throttled per process until Redis recovers
+#[tokio::test]
+async fn local_fallback_keeps_limits() {
+    let app = app(DegradationPolicies::uniform(
+        DegradationPolicy::LocalFallback,
+    ));
+    let limit = TransactionType::BudgetModification.get_limit();
+
+    let first = app.clone().oneshot(request("POST")).await.unwrap();
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(first.headers()["X-RateLimit-Status"], "degraded");
+
+    let mut throttled = false;
+    for _ in 0..limit {
+        let res = app.clone().oneshot(request("POST")).await.unwrap();
+        if res.status() == StatusCode::TOO_MANY_REQUESTS {
+            throttled = true;
+            break;
+        }
+    }
+    assert!(throttled, "local fallback never enforced the limit");
+}
+
+/// Accept connections and never answer, like a Redis that has hung
+async fn silent_redis() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut open = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            open.push(socket);
+        }
+    });
+    format!("redis://{}", addr)
+}
+
+/// Test: a Redis that accepts connections but never replies times out
+/// Why: without a timeout every rate-limited request hung with Redis
+/// Impact: the call fails within the connection timeout and the degradation
+/// policy decides the request
+#[tokio::test]
+async fn unresponsive_redis_times_out() {
+    let url = silent_redis().await;
+    let timeout = Duration::from_millis(200);
+    let backend = Arc::new(
+        RedisBackend::new(&url, RedisTopology::Standalone, timeout).unwrap(),
+    );
+
+    let started = Instant::now();
+    assert!(backend.increment("rate_limit:timeout", 60).await.is_err());
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    let app = app_with(
+        DegradationPolicies::uniform(DegradationPolicy::FailOpen),
+        backend,
+    );
+    let res = tokio::time::timeout(
+        Duration::from_secs(2),
+        app.oneshot(request("GET")),
+    )
+    .await
+    .expect("request hung on an unresponsive Redis")
+    .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["X-RateLimit-Status"], "degraded");
+}
+
+/// Test: policy names parse from their env var spellings
+/// Why: policies are configured with `RATE_LIMIT_DEGRADATION_*` variables
+/// Impact: a typo falls back to the default instead of silently failing open
+#[test]
+fn degradation_policy_parsing() {
+    for policy in [
+        DegradationPolicy::FailOpen,
+        DegradationPolicy::FailClosed,
+        DegradationPolicy::LocalFallback,
+    ] {
+        assert_eq!(policy.to_string().parse::<DegradationPolicy>(), Ok(policy));
+    }
+    assert_eq!("closed".parse(), Ok(DegradationPolicy::FailClosed));
+    assert!("sometimes".parse::<DegradationPolicy>().is_err());
+}
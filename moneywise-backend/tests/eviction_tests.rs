@@ -1,71 +1,55 @@
-//! Tests for LRU eviction behavior on the mock cache backend.
+//! Tests for LRU eviction behavior on the in-memory cache backend.
 
 mod common;
 
-use common::MockBudgetCache;
+use common::memory_budget_cache_with_capacity;
 use moneywise_backend::cache::CacheConfig;
 use moneywise_backend::models::BudgetOverviewApi;
 use rust_decimal::Decimal;
 
-/// Test: LRU eviction triggers when memory budget is exceeded
-/// Why: models production-like AllKeys-LRU policy; keeps mock faithful to real Redis behavior
-/// Impact: guards the eviction algorithm and memory accounting from regressions
-#[tokio::test]
-async fn lru_eviction_evicts_oldest_when_over_limit() {
-    // Very small memory budget to force eviction
-    let cfg = CacheConfig::default();
-    // Build a custom cache with tiny memory by constructing the inner mock directly
-    // through the public fields exposed by MockBudgetCache.
-    let mut cache = MockBudgetCache::new(cfg);
-    cache.mock = crate::common::mock_redis::MockRedis::new(
-        64,
-        crate::common::mock_redis::EvictionPolicy::AllKeysLru,
-    );
-
-    // Insert three small values; keys differ in length so memory varies
-    let ov = |p| BudgetOverviewApi {
-        planned: p,
+fn overview(planned: i64) -> BudgetOverviewApi {
+    BudgetOverviewApi {
+        planned: Decimal::from(planned),
         spent: Decimal::ZERO,
-        remaining: p,
+        remaining: Decimal::from(planned),
         currency: "USD".to_string(),
-    };
-    cache
-        .cache_budget_overview("M1", "Y", Some("USD"), &ov(Decimal::from(1)))
-        .await; // oldest
-    cache
-        .cache_budget_overview("M2", "Y", Some("USD"), &ov(Decimal::from(2)))
-        .await;
-    cache
-        .cache_budget_overview("M3", "Y", Some("USD"), &ov(Decimal::from(3)))
-        .await;
+    }
+}
 
-    // Add a large value to exceed memory and trigger LRU eviction
-    let big = BudgetOverviewApi {
-        planned: Decimal::from(99999),
-        spent: Decimal::ZERO,
-        remaining: Decimal::from(99999),
-        currency: "USD".to_string(),
-    };
-    cache
-        .cache_budget_overview("M_big", "Y", Some("USD"), &big)
-        .await;
+/// Test: LRU eviction triggers when the entry bound is exceeded
+/// Why: the in-memory backend must stay bounded like Redis with `allkeys-lru`
+/// Impact: guards the eviction order against regressions
+#[tokio::test]
+async fn lru_eviction_evicts_least_recently_used() {
+    let (cache, backend) =
+        memory_budget_cache_with_capacity(CacheConfig::default(), 3);
 
-    // Oldest likely evicted: M1
-    let m1 = cache
-        .get_cached_budget_overview("M1", "Y", Some("USD"))
-        .await;
-    let m2 = cache
-        .get_cached_budget_overview("M2", "Y", Some("USD"))
-        .await;
-    let m3 = cache
-        .get_cached_budget_overview("M3", "Y", Some("USD"))
-        .await;
-    let big_got = cache
-        .get_cached_budget_overview("M_big", "Y", Some("USD"))
-        .await;
+    for (month, planned) in [("1", 1), ("2", 2), ("3", 3)] {
+        cache
+            .cache_budget_overview(month, "2025", None, &overview(planned))
+            .await
+            .unwrap();
+    }
+
+    // Reading month 1 makes month 2 the least recently used entry
+    assert!(cache
+        .get_cached_budget_overview("1", "2025", None)
+        .await
+        .unwrap()
+        .is_some());
+
+    cache
+        .cache_budget_overview("4", "2025", None, &overview(4))
+        .await
+        .unwrap();
 
-    // We only assert that at least one of the earlier keys is evicted and the last is present,
-    // to avoid over-constraining the exact byte math.
-    assert!(big_got.is_some());
-    assert!(m1.is_none() || m2.is_none() || m3.is_none());
+    assert_eq!(backend.len(), 3);
+    for (month, cached) in [("1", true), ("2", false), ("3", true), ("4", true)]
+    {
+        let got = cache
+            .get_cached_budget_overview(month, "2025", None)
+            .await
+            .unwrap();
+        assert_eq!(got.is_some(), cached, "month {}", month);
+    }
 }
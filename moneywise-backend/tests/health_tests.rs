@@ -0,0 +1,207 @@
+//! Tests for the `/health/live` and `/health/ready` probes.
+//!
+//! The ready probe is driven with a pool pointing at a closed port, an
+//! in-memory cache and a stub rate limit backend; the last test needs a
+//! real server:
+//!   DATABASE_URL=postgresql://postgres@localhost:5432/moneywise \
+//!     cargo test --test health_tests -- --include-ignored
+
+mod common;
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    Extension, Router,
+};
+use common::{db::TestDatabase, memory_budget_cache};
+use moneywise_backend::{
+    api::{create_health_router, health::ReadinessProbe},
+    cache::CacheConfig,
+    rate_limiter::{
+        backend::CounterState,
+        config::DegradationPolicies,
+        types::{DegradationPolicy, RateLimitError},
+        ClassifiedRouter, InMemoryBackend, RateLimitBackend, RateLimitClass,
+        RateLimitConfig, RateLimitService,
+    },
+    repository::InMemoryBudgetRepository,
+};
+use serde_json::Value;
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use std::{sync::Arc, time::Duration};
+use tower::ServiceExt;
+
+/// Backend whose store is unreachable, like a Redis that is down
+struct DownBackend;
+
+fn down() -> RateLimitError {
+    RateLimitError::RedisError(redis::RedisError::from((
+        redis::ErrorKind::IoError,
+        "connection refused",
+    )))
+}
+
+#[async_trait]
+impl RateLimitBackend for DownBackend {
+    async fn increment(&self, _: &str, _: u64) -> Result<u32, RateLimitError> {
+        Err(down())
+    }
+
+    async fn peek(
+        &self,
+        _: &str,
+    ) -> Result<Option<CounterState>, RateLimitError> {
+        Err(down())
+    }
+
+    async fn reset(&self, _: &str) -> Result<bool, RateLimitError> {
+        Err(down())
+    }
+
+    async fn ping(&self) -> Result<(), RateLimitError> {
+        Err(down())
+    }
+
+    fn name(&self) -> &'static str {
+        "down"
+    }
+}
+
+/// A pool whose connections are refused; nothing listens on port 1
+fn unreachable_pool() -> PgPool {
+    PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(200))
+        .connect_lazy("postgres://postgres@127.0.0.1:1/moneywise")
+        .unwrap()
+}
+
+fn probe(pool: PgPool, rate_limiter: RateLimitService) -> ReadinessProbe {
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
+    ReadinessProbe::new(pool, cache, Arc::new(rate_limiter))
+        .with_timeout(Duration::from_secs(1))
+}
+
+fn memory_rate_limiter() -> RateLimitService {
+    RateLimitService::with_backend(
+        RateLimitConfig::in_memory(),
+        Arc::new(InMemoryBackend::default()),
+    )
+}
+
+/// `/health` as mounted by `main`
+fn app(probe: ReadinessProbe) -> Router {
+    let (router, _) = ClassifiedRouter::new()
+        .nest("/health", create_health_router())
+        .into_parts();
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
+    let repo = Arc::new(InMemoryBudgetRepository::new());
+
+    router
+        .layer(Extension(Arc::new(probe)))
+        .with_state((repo, cache))
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+    let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let res = app.clone().oneshot(req).await.unwrap();
+    let status = res.status();
+    let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+/// Test: liveness answers 200 even when every dependency is down
+/// Why: restarting the process cannot fix an unreachable database
+/// Impact: an outage does not turn into a restart loop
+#[tokio::test]
+async fn live_ignores_dependencies() {
+    let app = app(probe(unreachable_pool(), memory_rate_limiter()));
+
+    let (status, body) = get(&app, "/health/live").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "live");
+}
+
+/// Test: readiness is 503 with a per-dependency report when Postgres is down
+/// Why: a replica without its database must be taken out of rotation
+/// Impact: the report names the failing dependency for operators
+#[tokio::test]
+async fn ready_fails_without_database() {
+    let app = app(probe(unreachable_pool(), memory_rate_limiter()));
+
+    let (status, body) = get(&app, "/health/ready").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "not_ready");
+
+    let checks = &body["checks"];
+    for required in ["database", "migrations"] {
+        assert_eq!(checks[required]["status"], "down");
+        assert_eq!(checks[required]["required"], true);
+        assert!(checks[required]["error"].is_string());
+    }
+    assert_eq!(checks["cache"]["status"], "up");
+    assert_eq!(checks["cache"]["detail"], "memory");
+    assert_eq!(checks["rate_limiter"]["status"], "up");
+    assert!(checks["database"]["latency_ms"].is_u64());
+}
+
+/// Test: the rate limiter backend is required only when a type fails closed
+/// Why: fail-open and local-fallback policies keep serving without Redis
+/// Impact: a Redis outage only takes replicas out when it would reject
+/// requests anyway
+#[tokio::test]
+async fn rate_limiter_required_when_failing_closed() {
+    let pool = unreachable_pool();
+    for (policy, required) in [
+        (DegradationPolicy::LocalFallback, false),
+        (DegradationPolicy::FailClosed, true),
+    ] {
+        let config = RateLimitConfig {
+            degradation: DegradationPolicies::uniform(policy),
+            ..RateLimitConfig::in_memory()
+        };
+        let rate_limiter =
+            RateLimitService::with_backend(config, Arc::new(DownBackend));
+
+        let report = probe(pool.clone(), rate_limiter).check().await;
+        let check = &report.checks["rate_limiter"];
+        assert_eq!(check.required, required, "{}", policy);
+        assert_eq!(check.detail.as_deref(), Some("down"));
+        assert!(check.error.as_deref().unwrap().contains("refused"));
+    }
+}
+
+/// Test: both probes are classified as exempt from rate limiting
+/// Why: orchestrators poll them from a handful of addresses
+/// Impact: heavy probing never gets a replica marked unhealthy with 429
+#[test]
+fn health_routes_are_exempt() {
+    let (_, table) = ClassifiedRouter::new()
+        .nest("/health", create_health_router())
+        .into_parts();
+    for path in ["/health/live", "/health/ready"] {
+        assert_eq!(
+            table.classify(&Method::GET, Some(path)),
+            RateLimitClass::Exempt
+        );
+    }
+}
+
+/// Test: readiness is 200 against a migrated database
+/// Why: `SELECT 1` and the migration check must pass on a healthy server
+/// Impact: a deploy whose migrations ran is put into rotation
+#[tokio::test]
+#[ignore = "needs PostgreSQL at DATABASE_URL (creates scratch databases)"]
+async fn ready_with_migrated_database() {
+    let db = TestDatabase::new().await;
+    let app = app(probe(db.pool.clone(), memory_rate_limiter()));
+
+    let (status, body) = get(&app, "/health/ready").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["checks"]["database"]["status"], "up");
+    assert!(body["checks"]["migrations"]["detail"]
+        .as_str()
+        .unwrap()
+        .ends_with("applied"));
+}
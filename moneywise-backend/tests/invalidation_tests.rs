@@ -2,19 +2,19 @@
 
 mod common;
 
-use common::MockBudgetCache;
+use common::memory_budget_cache;
 use moneywise_backend::{
-    cache::CacheConfig,
-    models::{BudgetOverviewApi, CategoryBudgetApi},
+    cache::{domains::budget::keys, CacheConfig},
+    models::{BudgetApi, BudgetOverviewApi, CategoryBudgetApi},
 };
 use rust_decimal::Decimal;
 
-/// Test: invalidating a month removes both overview and categories entries
+/// Test: invalidating a period removes both overview and categories entries
 /// Why: asserts multi-key invalidation contract so UI doesn't mix fresh and stale data
 /// Impact: ensures coherence of month views and documents invalidation breadth
 #[tokio::test]
 async fn month_invalidation_removes_overview_and_categories() {
-    let cache = MockBudgetCache::new(CacheConfig::default());
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
 
     let overview = BudgetOverviewApi {
         planned: Decimal::from(1000),
@@ -24,31 +24,137 @@ async fn month_invalidation_removes_overview_and_categories() {
     };
     cache
         .cache_budget_overview("January", "2024", Some("USD"), &overview)
-        .await;
+        .await
+        .unwrap();
     let categories: Vec<CategoryBudgetApi> = vec![];
     cache
         .cache_category_budgets("January", "2024", Some("USD"), &categories)
-        .await;
+        .await
+        .unwrap();
 
     assert!(cache
         .get_cached_budget_overview("January", "2024", Some("USD"))
         .await
+        .unwrap()
         .is_some());
     assert!(cache
         .get_cached_category_budgets("January", "2024", Some("USD"))
         .await
+        .unwrap()
         .is_some());
 
-    cache
-        .invalidate_month_cache("January", "2024", Some("USD"))
-        .await;
+    cache.invalidate_period("January", "2024").await.unwrap();
 
     assert!(cache
         .get_cached_budget_overview("January", "2024", Some("USD"))
         .await
+        .unwrap()
         .is_none());
     assert!(cache
         .get_cached_category_budgets("January", "2024", Some("USD"))
         .await
+        .unwrap()
+        .is_none());
+}
+
+fn overview(currency: &str) -> BudgetOverviewApi {
+    BudgetOverviewApi {
+        planned: Decimal::from(1000),
+        spent: Decimal::from(500),
+        remaining: Decimal::from(500),
+        currency: currency.to_string(),
+    }
+}
+
+/// Test: invalidating a period removes every currency variant of the month
+/// Why: writes used to delete only the `(month, year, currency)` keys, leaving the currency-less overview stale
+/// Impact: after `create_budget`/`update_budget` no view of the month is served stale
+#[tokio::test]
+async fn period_invalidation_covers_all_currency_variants() {
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
+
+    cache
+        .cache_budget_overview("8", "2025", None, &overview("USD"))
+        .await
+        .unwrap();
+    cache
+        .cache_budget_overview("8", "2025", Some("USD"), &overview("USD"))
+        .await
+        .unwrap();
+    cache
+        .cache_category_budgets("8", "2025", Some("EUR"), &[])
+        .await
+        .unwrap();
+    cache
+        .cache_budget_overview("9", "2025", None, &overview("USD"))
+        .await
+        .unwrap();
+
+    assert_eq!(cache.invalidate_period("8", "2025").await.unwrap(), 3);
+
+    assert!(cache
+        .get_cached_budget_overview("8", "2025", None)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(cache
+        .get_cached_budget_overview("8", "2025", Some("USD"))
+        .await
+        .unwrap()
+        .is_none());
+    assert!(cache
+        .get_cached_category_budgets("8", "2025", Some("EUR"))
+        .await
+        .unwrap()
         .is_none());
+    // Other months keep their entries
+    assert!(cache
+        .get_cached_budget_overview("9", "2025", None)
+        .await
+        .unwrap()
+        .is_some());
+}
+
+/// Test: individual budgets are cached without tag sets and invalidated by id
+/// Why: each budget has its own cluster slot, so it cannot join its month's tag set
+/// Impact: budget writes leave no tag sets behind; copy-month evicts the budgets it overwrote
+#[tokio::test]
+async fn budgets_are_invalidated_by_id() {
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
+    let budget = BudgetApi {
+        id: "budget-1".to_string(),
+        month: 8,
+        year: 2025,
+        category_id: "category-1".to_string(),
+        planned: Decimal::from(300),
+        spent: Decimal::from(0),
+        carryover: Decimal::from(0),
+        currency: "USD".to_string(),
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+    cache.cache_budget(&budget.id, &budget).await.unwrap();
+
+    let tag_sets = cache
+        .service()
+        .keys_with_prefix("moneywise:tag:")
+        .await
+        .unwrap();
+    assert!(tag_sets.is_empty(), "{:?}", tag_sets);
+
+    cache
+        .invalidate_budgets(&["budget-1".to_string(), "budget-2".to_string()])
+        .await
+        .unwrap();
+    assert!(cache.get_cached_budget("budget-1").await.unwrap().is_none());
+}
+
+/// Test: period tags use a zero-padded `yyyy-mm` form
+/// Why: handlers pass months as plain numbers ("8"); tags must not depend on that formatting
+/// Impact: "8" and "08" invalidate the same entries
+#[test]
+fn period_tag_format() {
+    assert_eq!(keys::period_tag("8", "2025"), "{budget:2025-08}:period");
+    assert_eq!(keys::period_tag("08", "2025"), "{budget:2025-08}:period");
+    assert_eq!(keys::period_tag("12", "2025"), "{budget:2025-12}:period");
 }
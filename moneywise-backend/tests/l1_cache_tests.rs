@@ -0,0 +1,66 @@
+//! Tests for the in-process L1 cache and its invalidation messages.
+
+use moneywise_backend::cache::core::local::{
+    decode_invalidation, encode_invalidation, LocalCache,
+};
+use std::time::Duration;
+
+/// Test: the L1 holds at most `max_entries`, evicting the least recently used
+/// Why: L1 lives in every API process and must not grow without bound
+/// Impact: memory use per instance is capped by `CACHE_L1_MAX_ENTRIES`
+#[tokio::test]
+async fn evicts_least_recently_used_when_full() {
+    let l1 = LocalCache::new(2, Duration::from_secs(30));
+
+    l1.insert("overview:1", b"a".to_vec(), 900);
+    l1.insert("overview:2", b"b".to_vec(), 900);
+    // Touch 1 so 2 becomes the least recently used
+    assert_eq!(l1.get("overview:1").as_deref(), Some(&b"a"[..]));
+    l1.insert("overview:3", b"c".to_vec(), 900);
+
+    assert_eq!(l1.len(), 2);
+    assert!(l1.get("overview:2").is_none());
+    assert!(l1.get("overview:1").is_some());
+    assert!(l1.get("overview:3").is_some());
+}
+
+/// Test: entries expire after the L1 TTL, or earlier if the Redis TTL is shorter
+/// Why: a missed pub/sub message must only leave stale data for a bounded time
+/// Impact: L1 never serves a value Redis would already have expired
+#[tokio::test(start_paused = true)]
+async fn entries_expire_after_local_or_redis_ttl() {
+    let l1 = LocalCache::new(10, Duration::from_secs(30));
+
+    l1.insert("long", b"a".to_vec(), 900);
+    l1.insert("short", b"b".to_vec(), 5);
+
+    tokio::time::advance(Duration::from_secs(6)).await;
+    assert!(l1.get("short").is_none());
+    assert!(l1.get("long").is_some());
+
+    tokio::time::advance(Duration::from_secs(25)).await;
+    assert!(l1.get("long").is_none());
+    assert!(l1.is_empty());
+}
+
+/// Test: received invalidations evict exactly the listed keys
+/// Why: `invalidate_period` on one instance must evict L1 entries on all of them
+/// Impact: guards the message format shared by publisher and subscribers
+#[test]
+fn invalidation_messages_evict_listed_keys() {
+    let l1 = LocalCache::new(10, Duration::from_secs(30));
+    l1.insert("moneywise:budget:overview:1:2025", b"a".to_vec(), 900);
+    l1.insert("moneywise:budget:categories:1:2025", b"b".to_vec(), 900);
+    l1.insert("moneywise:budget:overview:2:2025", b"c".to_vec(), 900);
+
+    let message = encode_invalidation(&[
+        "moneywise:budget:overview:1:2025",
+        "moneywise:budget:categories:1:2025",
+    ]);
+    let keys = decode_invalidation(&message).unwrap();
+    l1.remove(&keys);
+
+    assert_eq!(l1.len(), 1);
+    assert!(l1.get("moneywise:budget:overview:2:2025").is_some());
+    assert!(decode_invalidation("not json").is_none());
+}
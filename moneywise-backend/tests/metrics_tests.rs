@@ -0,0 +1,214 @@
+//! Tests for the `/metrics` endpoint and the series it exposes.
+//!
+//! The registry is process-wide and tests in this file run in parallel, so
+//! counters are compared before and after rather than checked for exact
+//! values.
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use common::{api::app, api::send, db::TestDatabase};
+use moneywise_backend::{
+    api::warmup::spawn_budget_cache_warmup,
+    cache::CacheConfig,
+    metrics::{
+        http::{request_count, UNMATCHED_ROUTE},
+        jobs::{job, job_count, JobOutcome},
+        pool::PoolCollector,
+    },
+    rate_limiter::{
+        metrics::{decision_count, Decision},
+        types::TransactionType,
+    },
+    repository::InMemoryBudgetRepository,
+};
+use prometheus::core::Collector;
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use std::{sync::Arc, time::Duration};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+fn memory_app() -> Router {
+    app(Arc::new(InMemoryBudgetRepository::new()))
+}
+
+/// Scrape `/metrics`, checking the content type
+async fn scrape(app: &Router) -> String {
+    let req = Request::builder()
+        .uri("/metrics")
+        .body(Body::empty())
+        .unwrap();
+    let res = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], prometheus::TEXT_FORMAT);
+    let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// `state` gauge of a pool collector
+fn pool_connections(collector: &PoolCollector, state: &str) -> i64 {
+    collector
+        .collect()
+        .iter()
+        .flat_map(|family| family.get_metric())
+        .find(|metric| {
+            metric
+                .get_label()
+                .iter()
+                .any(|label| label.get_value() == state)
+        })
+        .map(|metric| metric.get_gauge().get_value() as i64)
+        .unwrap()
+}
+
+/// Test: requests are counted by route template and status
+/// Why: labelling by raw path would create a series per budget id
+/// Impact: per-route dashboards stay bounded and include 404s
+#[tokio::test]
+async fn requests_counted_by_route_and_status() {
+    let app = memory_app();
+    let by_id = "/api/budgets/:id";
+    let found_before = request_count(&Method::GET, by_id, 404);
+    let ok_before = request_count(&Method::GET, "/api/budgets/overview", 200);
+    let unmatched_before = request_count(&Method::GET, UNMATCHED_ROUTE, 404);
+
+    let uri = format!("/api/budgets/{}", Uuid::new_v4());
+    let (status, _) = send(&app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) =
+        send(&app, "GET", "/api/budgets/overview?month=6&year=2025", None)
+            .await;
+    assert_eq!(status, StatusCode::OK);
+    let req = Request::builder()
+        .uri("/no/such/route")
+        .body(Body::empty())
+        .unwrap();
+    app.clone().oneshot(req).await.unwrap();
+
+    assert_eq!(request_count(&Method::GET, by_id, 404), found_before + 1);
+    assert!(
+        request_count(&Method::GET, "/api/budgets/overview", 200) > ok_before
+    );
+    assert!(
+        request_count(&Method::GET, UNMATCHED_ROUTE, 404) > unmatched_before
+    );
+
+    let text = scrape(&app).await;
+    assert!(text.contains(
+        r#"moneywise_http_requests_total{method="GET",route="/api/budgets/:id",status="404"}"#
+    ));
+    assert!(text.contains("moneywise_http_request_duration_seconds_bucket"));
+    assert!(!text.contains(&uri));
+}
+
+/// Test: rejected requests are recorded with their 429 status
+/// Why: the request metrics wrap the rate limiter, not only the handlers
+/// Impact: throttled traffic shows up next to the decision counters
+#[tokio::test]
+async fn rate_limited_requests_are_counted() {
+    let app = memory_app();
+    let tx = TransactionType::BudgetModification;
+    let throttled_before = request_count(&Method::POST, "/api/budgets", 429);
+    let denied_before = decision_count(Decision::Denied, tx);
+
+    let body = serde_json::json!({
+        "category_id": Uuid::new_v4().to_string(),
+        "planned": "10",
+        "currency": "USD",
+        "month": 6,
+        "year": 2025
+    });
+    for _ in 0..=tx.get_limit() {
+        send(&app, "POST", "/api/budgets", Some(body.clone())).await;
+    }
+
+    assert!(
+        request_count(&Method::POST, "/api/budgets", 429) > throttled_before
+    );
+    assert!(decision_count(Decision::Denied, tx) > denied_before);
+    assert!(scrape(&app)
+        .await
+        .contains("moneywise_rate_limit_decisions_total{decision=\"denied\""));
+}
+
+/// Test: cache lookups of API reads are exported
+/// Why: the hit ratio is the main signal that the cache is doing its job
+/// Impact: guards the `moneywise_cache_lookups_total` series on `/metrics`
+#[tokio::test]
+async fn cache_lookups_are_exported() {
+    let app = memory_app();
+    let uri = "/api/budgets/overview?month=2&year=1999";
+    send(&app, "GET", uri, None).await;
+    send(&app, "GET", uri, None).await;
+
+    let text = scrape(&app).await;
+    assert!(text.contains(
+        r#"moneywise_cache_lookups_total{domain="budget",result="hit"}"#
+    ));
+    assert!(text.contains(
+        r#"moneywise_cache_lookups_total{domain="budget",result="miss"}"#
+    ));
+}
+
+/// Test: warm-up runs are counted by outcome
+/// Why: a warm-up failing on every run is otherwise only visible in logs
+/// Impact: guards `moneywise_background_jobs_total{job="cache_warmup"}`
+#[tokio::test]
+async fn warmup_runs_are_counted() {
+    let before = job_count(job::CACHE_WARMUP, JobOutcome::Success);
+    let (cache, _) = common::memory_budget_cache(CacheConfig::default());
+
+    spawn_budget_cache_warmup(
+        Arc::new(InMemoryBudgetRepository::new()),
+        cache,
+        Duration::ZERO,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        job_count(job::CACHE_WARMUP, JobOutcome::Success),
+        before + 1
+    );
+    assert!(scrape(&memory_app()).await.contains(
+        r#"moneywise_background_jobs_total{job="cache_warmup",outcome="success"}"#
+    ));
+}
+
+/// Test: pool gauges report idle and in-use connections at collection
+/// Why: a saturated pool shows up as request latency before errors
+/// Impact: guards `moneywise_db_pool_connections`
+#[tokio::test]
+async fn pool_gauges_track_connections() {
+    let lazy: PgPool = PgPoolOptions::new()
+        .connect_lazy("postgres://postgres@127.0.0.1:1/moneywise")
+        .unwrap();
+    let collector = PoolCollector::new(lazy);
+    assert_eq!(pool_connections(&collector, "idle"), 0);
+    assert_eq!(pool_connections(&collector, "in_use"), 0);
+}
+
+/// Test: a checked-out connection is reported as in use, then idle
+/// Why: the gauges are read from the live pool at every scrape
+/// Impact: dashboards see pool usage change without a background task
+#[tokio::test]
+#[ignore = "needs PostgreSQL at DATABASE_URL (creates scratch databases)"]
+async fn pool_gauges_follow_checkouts() {
+    let db = TestDatabase::new().await;
+    let collector = PoolCollector::new(db.pool.clone());
+    // Dropped connections return to the pool from a spawned task
+    let settle = || tokio::time::sleep(Duration::from_millis(100));
+
+    settle().await;
+    let in_use = pool_connections(&collector, "in_use");
+    let conn = db.pool.acquire().await.unwrap();
+    assert_eq!(pool_connections(&collector, "in_use"), in_use + 1);
+    drop(conn);
+    settle().await;
+    assert_eq!(pool_connections(&collector, "in_use"), in_use);
+    assert!(pool_connections(&collector, "idle") >= 1);
+}
@@ -0,0 +1,150 @@
+//! Tests for embedded database migrations and schema checks.
+
+mod common;
+
+use common::db::TestDatabase;
+use moneywise_backend::database::migrations::{
+    check_applied, run_migrations, MigrationStatus, LEGACY_CHECKSUMS, MIGRATOR,
+};
+use sqlx::migrate::{AppliedMigration, MigrateError};
+use sqlx::Row;
+use std::borrow::Cow;
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn applied(version: i64, checksum: &[u8]) -> AppliedMigration {
+    AppliedMigration {
+        version,
+        checksum: Cow::Owned(checksum.to_vec()),
+    }
+}
+
+/// The embedded migrations as they would be recorded once applied
+fn all_applied() -> Vec<AppliedMigration> {
+    MIGRATOR
+        .iter()
+        .map(|m| applied(m.version, &m.checksum))
+        .collect()
+}
+
+/// Test: every SQL file in `database/migrations` is embedded, in order
+/// Why: a file with a malformed name would silently never be applied
+/// Impact: deployments run exactly the migrations in the repository
+#[test]
+fn migrations_are_embedded_in_order() {
+    let versions: Vec<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+    assert_eq!(versions, [20250827000000, 20250916000000]);
+    assert_eq!(
+        MIGRATOR.iter().next().unwrap().description,
+        "initial schema"
+    );
+}
+
+/// Test: pending migrations are those not yet recorded
+/// Why: startup with migrations disabled must detect a stale schema
+/// Impact: the server never runs against an unmigrated database
+#[test]
+fn pending_migrations_are_reported() {
+    let status = check_applied(&MIGRATOR, &all_applied()[..1]).unwrap();
+    assert_eq!(
+        status,
+        MigrationStatus {
+            applied: vec![20250827000000],
+            pending: vec![20250916000000],
+        }
+    );
+
+    let status = check_applied(&MIGRATOR, &all_applied()).unwrap();
+    assert!(status.pending.is_empty());
+}
+
+/// Test: a database with unknown migrations is reported as ahead
+/// Why: an older binary must not run against a newer schema
+/// Impact: rollbacks fail at startup instead of corrupting data
+#[test]
+fn unknown_applied_migration_is_rejected() {
+    let mut migrations = all_applied();
+    migrations.push(applied(20990101000000, b"future"));
+
+    assert!(matches!(
+        check_applied(&MIGRATOR, &migrations),
+        Err(MigrateError::VersionMissing(20990101000000))
+    ));
+}
+
+/// Test: an applied migration edited afterwards is rejected
+/// Why: the recorded schema would no longer match the file
+/// Impact: edits to shipped migrations are caught instead of ignored
+#[test]
+fn modified_migration_is_rejected() {
+    let mut migrations = all_applied();
+    migrations[0].checksum = Cow::Owned(b"edited".to_vec());
+
+    assert!(matches!(
+        check_applied(&MIGRATOR, &migrations),
+        Err(MigrateError::VersionMismatch(20250827000000))
+    ));
+}
+
+/// Test: the released checksum of an edited migration is still accepted
+/// Why: the initial migration lost its sample data inserts after release
+/// Impact: databases migrated before that keep starting
+#[test]
+fn legacy_checksum_is_accepted() {
+    let (version, legacy) = LEGACY_CHECKSUMS[0];
+    let mut migrations = all_applied();
+    let initial = migrations
+        .iter_mut()
+        .find(|m| m.version == version)
+        .unwrap();
+    assert_ne!(initial.checksum.as_ref(), from_hex(legacy).as_slice());
+    initial.checksum = Cow::Owned(from_hex(legacy));
+
+    let status = check_applied(&MIGRATOR, &migrations).unwrap();
+    assert!(status.pending.is_empty());
+}
+
+/// Test: migrations create no rows, and a legacy checksum is rewritten
+/// when they run
+/// Why: sample budgets must not appear in production databases, and
+/// `Migrator::run` itself rejects checksums it does not know
+/// Impact: fresh databases start empty; old ones migrate without errors
+#[tokio::test]
+#[ignore = "needs PostgreSQL at DATABASE_URL (creates scratch databases)"]
+async fn migrated_database_is_empty_and_legacy_checksum_upgraded() {
+    let db = TestDatabase::new().await;
+    for table in ["category_groups", "categories", "budgets"] {
+        let count: i64 =
+            sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 0, "{}", table);
+    }
+
+    let (version, legacy) = LEGACY_CHECKSUMS[0];
+    sqlx::query("UPDATE _sqlx_migrations SET checksum = $1 WHERE version = $2")
+        .bind(from_hex(legacy))
+        .bind(version)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    run_migrations(&db.pool).await.unwrap();
+
+    let row =
+        sqlx::query("SELECT checksum FROM _sqlx_migrations WHERE version = $1")
+            .bind(version)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    let embedded = MIGRATOR.iter().find(|m| m.version == version).unwrap();
+    assert_eq!(
+        row.get::<Vec<u8>, _>("checksum"),
+        embedded.checksum.as_ref()
+    );
+}
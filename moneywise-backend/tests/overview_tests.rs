@@ -2,8 +2,11 @@
 
 mod common;
 
-use common::MockBudgetCache;
-use moneywise_backend::{cache::CacheConfig, models::BudgetOverviewApi};
+use common::memory_budget_cache;
+use moneywise_backend::{
+    cache::{core::backend::CacheBackend, domains::budget::keys, CacheConfig},
+    models::BudgetOverviewApi,
+};
 use rust_decimal::Decimal;
 use std::time::Duration;
 
@@ -12,7 +15,7 @@ use std::time::Duration;
 /// Impact: provides a minimal, readable example for learners
 #[tokio::test]
 async fn overview_miss_set_hit() {
-    let cache = MockBudgetCache::new(CacheConfig::default());
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
 
     let overview = BudgetOverviewApi {
         planned: Decimal::from(1000),
@@ -24,28 +27,32 @@ async fn overview_miss_set_hit() {
     assert!(cache
         .get_cached_budget_overview("January", "2024", Some("USD"))
         .await
+        .unwrap()
         .is_none());
     cache
         .cache_budget_overview("January", "2024", Some("USD"), &overview)
-        .await;
+        .await
+        .unwrap();
     let cached = cache
         .get_cached_budget_overview("January", "2024", Some("USD"))
-        .await;
+        .await
+        .unwrap();
     assert!(cached.is_some());
     assert_eq!(cached.unwrap().planned, overview.planned);
 }
 
 /// Test: TTL expiration is honored for overview entries
-/// Why: validates time-based invalidation and tests small TTLs deterministically
+/// Why: validates time-based invalidation; paused time keeps it deterministic
 /// Impact: avoids stale data lingering beyond configured lifetime
-#[tokio::test]
+#[tokio::test(start_paused = true)]
 async fn ttl_expiration_observed() {
-    let mut cfg = CacheConfig::default();
-
-    // set a small TTL to test expiration
-    cfg.overview_ttl = Duration::from_millis(150);
+    let cfg = CacheConfig {
+        // set a small TTL to test expiration
+        overview_ttl: Duration::from_secs(1),
+        ..CacheConfig::default()
+    };
 
-    let cache = MockBudgetCache::new(cfg);
+    let (cache, backend) = memory_budget_cache(cfg);
 
     let overview = BudgetOverviewApi {
         planned: Decimal::from(1),
@@ -57,21 +64,26 @@ async fn ttl_expiration_observed() {
     // cache the overview
     cache
         .cache_budget_overview("Feb", "2025", Some("USD"), &overview)
-        .await;
+        .await
+        .unwrap();
 
-    // verify the overview is cached
+    // verify the overview is cached with the configured TTL
+    let key = keys::overview_key("Feb", "2025", Some("USD"));
+    assert_eq!(backend.ttl(&key), Some(Duration::from_secs(1)));
     assert!(cache
         .get_cached_budget_overview("Feb", "2025", Some("USD"))
         .await
+        .unwrap()
         .is_some());
 
-    // sleep for longer than the TTL
-    tokio::time::sleep(Duration::from_millis(250)).await;
+    // advance past the TTL
+    tokio::time::advance(Duration::from_millis(1500)).await;
 
     // verify the overview is not cached
     assert!(cache
         .get_cached_budget_overview("Feb", "2025", Some("USD"))
         .await
+        .unwrap()
         .is_none());
 }
 
@@ -80,28 +92,19 @@ async fn ttl_expiration_observed() {
 /// Impact: favors availability—subsequent reads become clean cache misses
 #[tokio::test]
 async fn corrupt_overview_self_heals() {
-    let cache = MockBudgetCache::new(CacheConfig::default());
+    let (cache, backend) = memory_budget_cache(CacheConfig::default());
 
     // Manually insert corrupt JSON
-    let key = moneywise_backend::cache::domains::budget::keys::overview_key(
-        "Mar",
-        "2025",
-        Some("USD"),
-    );
-    cache
-        .mock
-        .set(key.clone(), "{not-json".to_string(), None)
-        .await;
+    let key = keys::overview_key("Mar", "2025", Some("USD"));
+    backend.set_ex(&key, b"{not-json", 60).await.unwrap();
 
     // First read should observe corruption, delete the key, and return None
     assert!(cache
         .get_cached_budget_overview("Mar", "2025", Some("USD"))
         .await
+        .unwrap()
         .is_none());
 
-    // Second read should be a miss (key deleted)
-    assert!(cache
-        .get_cached_budget_overview("Mar", "2025", Some("USD"))
-        .await
-        .is_none());
+    // The key is gone, so later reads are plain misses
+    assert!(backend.get(&key).await.unwrap().is_none());
 }
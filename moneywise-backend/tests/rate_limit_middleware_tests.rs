@@ -0,0 +1,252 @@
+//! In-process tests for the rate limiting middleware and in-memory backend.
+//!
+//! These run without Redis: the middleware is mounted on a stub router and
+//! backed by `InMemoryBackend`.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware, Router,
+};
+use moneywise_backend::rate_limiter::{
+    metrics::{decision_count, Decision},
+    middleware::rate_limit_middleware,
+    types::TransactionType,
+    ClassifiedRouter, InMemoryBackend, RateLimitBackend, RateLimitClass,
+    RateLimitConfig, RateLimitService,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+async fn ok() -> &'static str {
+    "ok"
+}
+
+/// Stub app with one route per class, limited by an in-memory backend
+fn app(backend: InMemoryBackend) -> Router {
+    let (router, table) = ClassifiedRouter::new()
+        .post("/budgets", ok, TransactionType::BudgetModification)
+        .get("/budgets", ok, TransactionType::BudgetRead)
+        .get("/health", ok, RateLimitClass::Exempt)
+        .into_parts();
+
+    let service = RateLimitService::with_backend(
+        RateLimitConfig::in_memory(),
+        Arc::new(backend),
+    )
+    .with_route_table(table);
+
+    router.layer(middleware::from_fn_with_state(
+        Arc::new(service),
+        rate_limit_middleware,
+    ))
+}
+
+fn request(method: &str, uri: &str, ip: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("x-forwarded-for", ip)
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Test: writes are rejected with 429 once the modification limit is used up
+/// Why: the middleware must enforce limits without Redis in single-node setups
+/// Impact: covers the full request path (classification, counting, headers, 429 body)
+#[tokio::test]
+async fn modification_limit_returns_429() {
+    let app = app(InMemoryBackend::default());
+    let limit = TransactionType::BudgetModification.get_limit();
+
+    let first = app
+        .clone()
+        .oneshot(request("POST", "/budgets", "10.0.0.1"))
+        .await
+        .unwrap();
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(first.headers()["X-RateLimit-Limit"], limit.to_string());
+    assert_eq!(
+        first.headers()["X-RateLimit-Remaining"],
+        (limit - 1).to_string()
+    );
+
+    let mut denied = None;
+    for _ in 1..=limit {
+        let res = app
+            .clone()
+            .oneshot(request("POST", "/budgets", "10.0.0.1"))
+            .await
+            .unwrap();
+        if res.status() == StatusCode::TOO_MANY_REQUESTS {
+            denied = Some(res);
+            break;
+        }
+    }
+
+    let denied = denied.expect("limit was never enforced");
+    assert!(denied.headers().contains_key("Retry-After"));
+    let body = hyper::body::to_bytes(denied.into_body()).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"], "Rate limit exceeded");
+    assert_eq!(json["limit_type"], "BudgetModification");
+}
+
+/// Test: counters are separate per client IP and per transaction type
+/// Why: one noisy client or exhausted write bucket must not block others
+/// Impact: guards the key layout used by the in-memory backend
+#[tokio::test]
+async fn buckets_are_isolated_by_ip_and_type() {
+    let app = app(InMemoryBackend::default());
+    let limit = TransactionType::BudgetModification.get_limit();
+
+    for _ in 0..limit {
+        app.clone()
+            .oneshot(request("POST", "/budgets", "10.0.0.1"))
+            .await
+            .unwrap();
+    }
+    let exhausted = app
+        .clone()
+        .oneshot(request("POST", "/budgets", "10.0.0.1"))
+        .await
+        .unwrap();
+    assert_eq!(exhausted.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let other_ip = app
+        .clone()
+        .oneshot(request("POST", "/budgets", "10.0.0.2"))
+        .await
+        .unwrap();
+    assert_eq!(other_ip.status(), StatusCode::OK);
+
+    let read = app
+        .clone()
+        .oneshot(request("GET", "/budgets", "10.0.0.1"))
+        .await
+        .unwrap();
+    assert_eq!(read.status(), StatusCode::OK);
+}
+
+/// Test: exempt routes bypass the limiter and get no rate limit headers
+/// Why: probes must keep working while a client is throttled
+/// Impact: confirms `RateLimitClass::Exempt` short-circuits the middleware
+#[tokio::test]
+async fn exempt_routes_are_not_counted() {
+    let backend = InMemoryBackend::default();
+    let app = app(backend.clone());
+
+    let res = app
+        .oneshot(request("GET", "/health", "10.0.0.1"))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(!res.headers().contains_key("X-RateLimit-Limit"));
+    assert!(backend.is_empty());
+}
+
+/// Test: in-memory counters reset after their window and are purged by cleanup
+/// Why: without expiry a long-running single-node server would throttle forever and leak memory
+/// Impact: validates the window semantics that mirror Redis INCR + EXPIRE
+#[tokio::test(start_paused = true)]
+async fn memory_counters_expire_and_are_purged() {
+    let backend = InMemoryBackend::new(4);
+    let cleanup = backend.spawn_cleanup(Duration::from_secs(30));
+
+    assert_eq!(backend.increment("a", 60).await.unwrap(), 1);
+    assert_eq!(backend.increment("a", 60).await.unwrap(), 2);
+    assert_eq!(backend.increment("b", 10).await.unwrap(), 1);
+
+    // "b" expires first and is purged by the next cleanup tick
+    tokio::time::sleep(Duration::from_secs(31)).await;
+    assert_eq!(backend.len(), 1);
+
+    // "a" restarts its window once expired
+    tokio::time::advance(Duration::from_secs(30)).await;
+    assert_eq!(backend.increment("a", 60).await.unwrap(), 1);
+
+    // Cleanup stops once the backend is dropped
+    drop(backend);
+    tokio::time::sleep(Duration::from_secs(31)).await;
+    assert!(cleanup.is_finished());
+}
+
+/// Test: a zero cleanup interval is raised to the minimum instead of panicking
+/// Why: `RATE_LIMIT_CLEANUP_INTERVAL_SECS=0` used to kill the cleanup task,
+/// so in-memory counters grew without bound
+/// Impact: counters are still purged whatever interval is configured
+#[tokio::test(start_paused = true)]
+async fn zero_cleanup_interval_still_purges() {
+    let backend = InMemoryBackend::new(4);
+    let cleanup = backend.spawn_cleanup(Duration::ZERO);
+
+    backend.increment("a", 1).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    assert!(!cleanup.is_finished());
+    assert!(backend.is_empty());
+}
+
+/// Test: admin inspection reports per-type counts and reset clears them
+/// Why: operators need to see and clear a client's counters when it complains about 429s
+/// Impact: backs `GET`/`DELETE /admin/rate-limits`
+#[tokio::test]
+async fn inspect_and_reset_client_counters() {
+    let backend = InMemoryBackend::default();
+    let app = app(backend.clone());
+    let service = RateLimitService::with_backend(
+        RateLimitConfig::in_memory(),
+        Arc::new(backend),
+    );
+
+    for _ in 0..3 {
+        app.clone()
+            .oneshot(request("POST", "/budgets", "10.0.0.9"))
+            .await
+            .unwrap();
+    }
+
+    let statuses = service.inspect("10.0.0.9", None).await.unwrap();
+    assert_eq!(statuses.len(), TransactionType::ALL.len());
+    let modification = statuses
+        .iter()
+        .find(|s| s.transaction_type == TransactionType::BudgetModification)
+        .unwrap();
+    assert_eq!(modification.count, 3);
+    assert_eq!(modification.remaining, modification.limit - 3);
+    assert!(modification.reset_in_seconds.is_some());
+
+    let untouched = statuses
+        .iter()
+        .find(|s| s.transaction_type == TransactionType::BudgetRead)
+        .unwrap();
+    assert_eq!(untouched.count, 0);
+    assert!(untouched.reset_in_seconds.is_none());
+
+    assert_eq!(service.reset("10.0.0.9", None).await.unwrap(), 1);
+    let after = service.inspect("10.0.0.9", None).await.unwrap();
+    assert!(after.iter().all(|s| s.count == 0));
+}
+
+/// Test: allowed and denied decisions are counted in the metrics registry
+/// Why: 429 spikes must be visible without reading logs
+/// Impact: guards `moneywise_rate_limit_decisions_total`
+#[tokio::test]
+async fn decisions_are_counted_as_metrics() {
+    let app = app(InMemoryBackend::default());
+    let tx = TransactionType::BudgetModification;
+    let allowed_before = decision_count(Decision::Allowed, tx);
+    let denied_before = decision_count(Decision::Denied, tx);
+
+    for _ in 0..=tx.get_limit() {
+        app.clone()
+            .oneshot(request("POST", "/budgets", "10.0.0.77"))
+            .await
+            .unwrap();
+    }
+
+    assert!(decision_count(Decision::Allowed, tx) > allowed_before);
+    assert!(decision_count(Decision::Denied, tx) > denied_before);
+    assert!(moneywise_backend::metrics::render()
+        .contains("moneywise_rate_limit_decisions_total"));
+}
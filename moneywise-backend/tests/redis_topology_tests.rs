@@ -0,0 +1,221 @@
+//! Tests for Redis Sentinel/Cluster configuration and hash-tagged keys.
+//!
+//! The ignored tests need a local multi-process Redis, e.g. a cluster of
+//! three primaries:
+//!
+//! ```bash
+//! for port in 7000 7001 7002; do
+//!   redis-server --port $port --cluster-enabled yes \
+//!     --cluster-config-file nodes-$port.conf --daemonize yes
+//! done
+//! redis-cli --cluster create 127.0.0.1:7000 127.0.0.1:7001 127.0.0.1:7002
+//! REDIS_CLUSTER_URLS=redis://127.0.0.1:7000 cargo test -- --ignored cluster
+//! ```
+//!
+//! or a primary watched by a sentinel (`sentinel monitor mymaster 127.0.0.1
+//! 6379 1`), with `REDIS_SENTINEL_URLS=redis://127.0.0.1:26379`.
+
+use moneywise_backend::{
+    cache::{
+        core::{backend::CacheBackendKind, service::KEY_PREFIX},
+        domains::budget::{keys, BudgetCache},
+        CacheConfig,
+    },
+    models::BudgetOverviewApi,
+    redis_topology::{
+        group_by_slot, hash_slot, parse_cluster_primaries, parse_url_list,
+        RedisMode, RedisTopology,
+    },
+};
+use rust_decimal::Decimal;
+
+/// Test: hash slots match Redis, including `{hash tag}` rules
+/// Why: key placement is only predictable if we compute slots like Redis
+/// Impact: slot assertions in other tests are meaningful
+#[test]
+fn hash_slot_matches_redis() {
+    assert_eq!(hash_slot("123456789"), 12739);
+    assert_eq!(hash_slot("foo"), 12182);
+    assert_eq!(
+        hash_slot("{user1000}.following"),
+        hash_slot("{user1000}.followers")
+    );
+    // An empty tag hashes the whole key
+    assert_ne!(hash_slot("{}a"), hash_slot("{}b"));
+}
+
+/// Test: a month's keys, its tag set and their locks share one slot
+/// Why: tagged writes run as one script, which Cluster rejects across slots
+/// Impact: the budget cache works unchanged on Redis Cluster
+#[test]
+fn period_keys_and_tags_share_a_slot() {
+    let slot = hash_slot(&keys::period_hash_tag("8", "2025"));
+    let tag_key = |tag: String| format!("{}tag:{}", KEY_PREFIX, tag);
+    for key in [
+        keys::overview_key("8", "2025", None),
+        keys::overview_key("08", "2025", Some("USD")),
+        keys::categories_key("8", "2025", Some("EUR")),
+        format!("{}:fill_lock", keys::overview_key("8", "2025", None)),
+        tag_key(keys::period_tag("8", "2025")),
+    ] {
+        assert_eq!(hash_slot(&key), slot, "{}", key);
+    }
+    let item = keys::budget_key("budget-1");
+    assert_eq!(hash_slot(&item), hash_slot(&format!("{}:fill_lock", item)));
+}
+
+/// Test: different months and budgets hash to different slots
+/// Why: a single `{budget}` hash tag put the whole budget cache on one node
+/// Impact: budget data spreads over the cluster's primaries
+#[test]
+fn budget_keys_spread_over_slots() {
+    let slots: std::collections::HashSet<u16> =
+        (1..=12)
+            .map(|month| {
+                hash_slot(&keys::overview_key(&month.to_string(), "2025", None))
+            })
+            .chain((0..12).map(|i| {
+                hash_slot(&keys::budget_key(&format!("budget-{}", i)))
+            }))
+            .collect();
+    assert!(slots.len() > 20, "{:?}", slots);
+}
+
+/// Test: keys are grouped by hash slot, in slot order
+/// Why: a multi-key `DEL` fails with CROSSSLOT on a cluster
+/// Impact: flushes and multi-key invalidations work on Redis Cluster
+#[test]
+fn keys_are_grouped_by_slot() {
+    let a = keys::overview_key("8", "2025", None);
+    let b = keys::categories_key("8", "2025", None);
+    let c = keys::overview_key("9", "2025", None);
+    let groups = group_by_slot(&[&a, &c, &b]);
+
+    assert_eq!(groups.len(), 2);
+    assert!(groups.contains(&vec![a.as_str(), b.as_str()]));
+    assert!(groups.contains(&vec![c.as_str()]));
+    assert!(hash_slot(groups[0][0]) < hash_slot(groups[1][0]));
+}
+
+/// Test: serving primaries are read from `CLUSTER NODES`
+/// Why: `SCAN` is node-local, so every primary must be scanned
+/// Impact: namespace flushes and key listings cover the whole cluster
+#[test]
+fn cluster_primaries_are_parsed() {
+    let nodes = "\
+07c3 127.0.0.1:7000@17000 myself,master - 0 0 1 connected 0-5460
+67ed 127.0.0.1:7001@17001,redis-b master - 0 0 2 connected 5461-10922
+292f 127.0.0.1:7002@17002 master,fail - 0 0 3 connected 10923-16383
+6ec2 127.0.0.1:7003@17003 slave 07c3 0 0 1 connected
+824f :0@0 master,noaddr - 0 0 0 connected
+";
+    assert_eq!(
+        parse_cluster_primaries(nodes),
+        [
+            ("127.0.0.1".to_string(), 7000),
+            ("127.0.0.1".to_string(), 7001)
+        ]
+    );
+}
+
+/// Test: modes and address lists parse from their environment spellings
+/// Why: a typo in `REDIS_MODE` must be rejected, not guessed
+/// Impact: misconfigured deployments fall back to standalone with a warning
+#[test]
+fn modes_and_url_lists_parse() {
+    assert_eq!("Cluster".parse::<RedisMode>(), Ok(RedisMode::Cluster));
+    assert_eq!("sentinel".parse::<RedisMode>(), Ok(RedisMode::Sentinel));
+    assert!("replica".parse::<RedisMode>().is_err());
+    assert_eq!(
+        parse_url_list(" redis://a:1, ,redis://b:2 "),
+        ["redis://a:1", "redis://b:2"]
+    );
+}
+
+/// Test: malformed addresses are rejected for every topology
+/// Why: configuration errors must fail at startup, not on the first request
+/// Impact: `CacheService::new` and `RateLimitService::new` fail fast
+#[test]
+fn malformed_addresses_are_rejected() {
+    let sentinel = RedisTopology::Sentinel {
+        sentinels: vec!["invalid://".to_string()],
+        master_name: "mymaster".to_string(),
+    };
+    let cluster = RedisTopology::Cluster {
+        nodes: vec!["invalid://".to_string()],
+    };
+    assert!(sentinel.validate("redis://localhost:6379").is_err());
+    assert!(cluster.validate("redis://localhost:6379").is_err());
+    assert!(RedisTopology::Standalone.validate("invalid://").is_err());
+    assert!(RedisTopology::Cluster { nodes: vec![] }
+        .validate("redis://localhost:7000")
+        .is_ok());
+}
+
+fn overview(planned: i64) -> BudgetOverviewApi {
+    BudgetOverviewApi {
+        planned: Decimal::from(planned),
+        spent: Decimal::ZERO,
+        remaining: Decimal::from(planned),
+        currency: "USD".to_string(),
+    }
+}
+
+async fn redis_budget_cache(topology: RedisTopology) -> BudgetCache {
+    let config = CacheConfig {
+        backend: CacheBackendKind::Redis,
+        redis_topology: topology,
+        max_connections: 1,
+        ..CacheConfig::default()
+    };
+    BudgetCache::new(config).await.unwrap()
+}
+
+/// Cache round trip, tag invalidation and namespace flush on a live server
+async fn exercise_budget_cache(cache: &BudgetCache) {
+    cache.flush().await.unwrap();
+    for month in ["1", "2", "3"] {
+        cache
+            .cache_budget_overview(month, "2030", None, &overview(5))
+            .await
+            .unwrap();
+    }
+    let cached = cache
+        .get_cached_budget_overview("1", "2030", None)
+        .await
+        .unwrap();
+    assert_eq!(cached.map(|o| o.planned), Some(Decimal::from(5)));
+
+    assert_eq!(cache.invalidate_period("1", "2030").await.unwrap(), 1);
+    // Months 2 and 3 live on different slots
+    assert_eq!(cache.flush().await.unwrap(), 2);
+}
+
+/// Test: the budget cache works on a Redis Cluster
+/// Why: tagged writes and flushes must not hit `CROSSSLOT` or miss nodes
+/// Impact: the cache can be deployed on a cluster
+#[tokio::test]
+#[ignore = "needs a Redis Cluster at REDIS_CLUSTER_URLS"]
+async fn cluster_budget_cache_round_trip() {
+    let nodes = parse_url_list(&std::env::var("REDIS_CLUSTER_URLS").unwrap());
+    let cache = redis_budget_cache(RedisTopology::Cluster { nodes }).await;
+    exercise_budget_cache(&cache).await;
+}
+
+/// Test: the budget cache works on a primary discovered through Sentinel
+/// Why: the primary's address comes from Sentinel, not `REDIS_URL`
+/// Impact: the cache can be deployed behind Sentinel
+#[tokio::test]
+#[ignore = "needs Redis Sentinel at REDIS_SENTINEL_URLS"]
+async fn sentinel_budget_cache_round_trip() {
+    let sentinels =
+        parse_url_list(&std::env::var("REDIS_SENTINEL_URLS").unwrap());
+    let master_name = std::env::var("REDIS_SENTINEL_MASTER")
+        .unwrap_or_else(|_| "mymaster".to_string());
+    let cache = redis_budget_cache(RedisTopology::Sentinel {
+        sentinels,
+        master_name,
+    })
+    .await;
+    exercise_budget_cache(&cache).await;
+}
@@ -0,0 +1,304 @@
+//! Tests for request IDs, JSON logs and OTLP span export.
+//!
+//! Span export is checked against a stand-in collector: an in-process HTTP
+//! server accepting OTLP/HTTP JSON on `/v1/traces`.
+
+mod common;
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Request, StatusCode},
+    middleware,
+    response::Response,
+    routing::{get, post},
+    Router,
+};
+use common::{api::app, memory_budget_cache};
+use moneywise_backend::{
+    api::create_admin_router,
+    cache::CacheConfig,
+    rate_limiter::{
+        api_keys::API_KEY_HEADER, types::TransactionType, ClassifiedRouter,
+    },
+    repository::{InMemoryBudgetRepository, SharedBudgetRepository},
+    server::{
+        request_id::{
+            request_id_middleware, RequestId, MAX_REQUEST_ID_LEN,
+            REQUEST_ID_HEADER,
+        },
+        telemetry::{otlp_layer, otlp_tracer_provider, LogFormat},
+    },
+};
+use serde_json::Value;
+use std::{
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tower::ServiceExt;
+use tracing_subscriber::{fmt::MakeWriter, layer::SubscriberExt};
+use uuid::Uuid;
+
+fn memory_app() -> Router {
+    app(Arc::new(InMemoryBudgetRepository::new()))
+}
+
+async fn call(app: &Router, uri: &str, request_id: Option<&str>) -> Response {
+    let mut req = Request::builder()
+        .uri(uri)
+        .header("x-forwarded-for", "10.0.0.2");
+    if let Some(id) = request_id {
+        req = req.header(REQUEST_ID_HEADER, id);
+    }
+    app.clone()
+        .oneshot(req.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+fn header_id(res: &Response) -> String {
+    res.headers()[REQUEST_ID_HEADER]
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn json_body(res: Response) -> Value {
+    let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+/// A router with one handler that logs, behind the request ID layer
+fn logging_app() -> Router {
+    Router::new()
+        .route(
+            "/ping",
+            get(|| async {
+                tracing::info!("handling ping");
+                "pong"
+            }),
+        )
+        .layer(middleware::from_fn(request_id_middleware))
+}
+
+/// Log output captured in memory
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Captured {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// OTLP request bodies received by the stand-in collector
+type Received = Arc<Mutex<Vec<String>>>;
+
+async fn collect_traces(
+    State(received): State<Received>,
+    body: String,
+) -> &'static str {
+    received.lock().unwrap().push(body);
+    "{}"
+}
+
+/// Start the stand-in collector; returns its base URL
+fn spawn_collector(received: Received) -> String {
+    let app = Router::new()
+        .route("/v1/traces", post(collect_traces))
+        .with_state(received);
+    let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+        .serve(app.into_make_service());
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    url
+}
+
+/// Test: a request without an ID gets a new UUID in the response header
+/// Why: every request must be traceable, not only those from our clients
+/// Impact: support can always ask for the `x-request-id` of a response
+#[tokio::test]
+async fn generates_missing_id() {
+    let res = call(&memory_app(), "/api/budgets?month=6&year=2025", None).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(Uuid::parse_str(&header_id(&res)).is_ok());
+}
+
+/// Test: a well-formed incoming ID is kept and a malformed one replaced
+/// Why: IDs set by a proxy or the app must follow the request, but raw
+/// header values end up in log lines
+/// Impact: one ID spans the whole call chain without enabling log forging
+#[tokio::test]
+async fn propagates_valid_id_only() {
+    let app = memory_app();
+    let uri = "/api/budgets?month=6&year=2025";
+
+    let res = call(&app, uri, Some("app-7f3a.1:retry_2")).await;
+    assert_eq!(header_id(&res), "app-7f3a.1:retry_2");
+
+    let too_long = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+    for bad in ["has space", "quote\"d", too_long.as_str()] {
+        let res = call(&app, uri, Some(bad)).await;
+        let id = header_id(&res);
+        assert_ne!(id, bad);
+        assert!(Uuid::parse_str(&id).is_ok());
+    }
+    assert!(RequestId::parse("").is_none());
+}
+
+/// Test: JSON error bodies carry the request ID of the response header
+/// Why: clients usually report the error body, not the headers
+/// Impact: a reported error can be matched to its logs and spans
+#[tokio::test]
+async fn error_body_carries_request_id() {
+    let uri = format!("/api/budgets/{}", Uuid::new_v4());
+    let res = call(&memory_app(), &uri, Some("support-1234")).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(header_id(&res), "support-1234");
+
+    let body = json_body(res).await;
+    assert_eq!(body["request_id"], "support-1234");
+    assert_eq!(body["status"], 404);
+}
+
+/// Test: rate-limited responses get a request ID too
+/// Why: the layer wraps the rate limiter, so rejections are traceable
+/// Impact: 429s reported by clients can be found in the logs
+#[tokio::test]
+async fn rate_limited_responses_have_id() {
+    let app = memory_app();
+    let tx = TransactionType::BudgetOverview;
+    let uri = "/api/budgets/overview?month=6&year=2025";
+
+    let mut last = None;
+    for _ in 0..=tx.get_limit() {
+        last = Some(call(&app, uri, None).await);
+    }
+    let res = last.unwrap();
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    let id = header_id(&res);
+    assert!(Uuid::parse_str(&id).is_ok());
+    assert_eq!(json_body(res).await["request_id"], id.as_str());
+}
+
+/// Test: API key rejections carry the request ID in their body
+/// Why: the rate limiter builds its own error bodies, outside `AppError`
+/// Impact: a reported 401 can be matched to its logs like any other error
+#[tokio::test]
+async fn invalid_api_key_body_has_id() {
+    let req = Request::builder()
+        .uri("/api/budgets/overview?month=6&year=2025")
+        .header("x-forwarded-for", "10.0.0.3")
+        .header(API_KEY_HEADER, "not-a-key")
+        .header(REQUEST_ID_HEADER, "support-401")
+        .body(Body::empty())
+        .unwrap();
+    let res = memory_app().oneshot(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let body = json_body(res).await;
+    assert_eq!(body["request_id"], "support-401");
+    assert_eq!(body["status"], 401);
+}
+
+/// Test: admin API rejections carry the request ID in their body
+/// Why: the admin token check builds its own error bodies, outside `AppError`
+/// Impact: operators can find a rejected admin call in the logs
+#[tokio::test]
+async fn admin_error_body_has_id() {
+    let (router, _) = ClassifiedRouter::new()
+        .nest("/admin", create_admin_router())
+        .into_parts();
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
+    let repo: SharedBudgetRepository =
+        Arc::new(InMemoryBudgetRepository::new());
+    let app = router
+        .layer(middleware::from_fn(request_id_middleware))
+        .with_state((repo, cache));
+
+    // 403 when the admin API is disabled, 401 without the token otherwise
+    let res = call(&app, "/admin/cache/stats", Some("support-admin")).await;
+    assert!(res.status().is_client_error());
+
+    let body = json_body(res).await;
+    assert_eq!(body["request_id"], "support-admin");
+}
+
+/// Test: JSON log lines inside a request include its request ID
+/// Why: `LOG_FORMAT=json` output is searched by field in log collectors
+/// Impact: every log line of a request can be found from its ID
+#[tokio::test]
+async fn json_logs_include_request_id() {
+    assert_eq!("JSON".parse::<LogFormat>(), Ok(LogFormat::Json));
+    assert!("xml".parse::<LogFormat>().is_err());
+
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::registry().with(
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_writer(captured.clone()),
+    );
+    let _default = tracing::subscriber::set_default(subscriber);
+
+    let res = call(&logging_app(), "/ping", Some("log-test-42")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+    let line: Value = output
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .find(|line| line["fields"]["message"] == "handling ping")
+        .expect("handler log line");
+    assert_eq!(line["span"]["request_id"], "log-test-42");
+    assert_eq!(line["span"]["route"], "/ping");
+}
+
+/// Test: request spans are exported over OTLP/HTTP with their request ID
+/// Why: traces must be searchable by the ID clients see
+/// Impact: guards the exporter setup used when
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` is set
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn spans_exported_to_collector() {
+    let received = Received::default();
+    let endpoint = spawn_collector(received.clone());
+
+    let provider = otlp_tracer_provider(&endpoint, "moneywise-test").unwrap();
+    let subscriber = tracing_subscriber::registry()
+        .with(otlp_layer(&provider, "moneywise-test"));
+    {
+        let _default = tracing::subscriber::set_default(subscriber);
+        let res = call(&logging_app(), "/ping", Some("otlp-test-7")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    let flushing = provider.clone();
+    tokio::task::spawn_blocking(move || flushing.force_flush())
+        .await
+        .unwrap();
+    for _ in 0..50 {
+        if !received.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let bodies = received.lock().unwrap().join("\n");
+    assert!(bodies.contains("GET /ping"), "{}", bodies);
+    assert!(bodies.contains("otlp-test-7"), "{}", bodies);
+    assert!(bodies.contains("moneywise-test"), "{}", bodies);
+}
@@ -0,0 +1,148 @@
+//! Tests for route-table-driven rate-limit classification.
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, State},
+    http::{Method, Request, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    routing::get,
+};
+use moneywise_backend::rate_limiter::{
+    types::TransactionType, ClassifiedRouter, RateLimitClass, RouteTable,
+};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Header used by the probe middleware to echo the class it resolved
+const CLASS_HEADER: &str = "x-test-class";
+
+async fn ok() -> &'static str {
+    "ok"
+}
+
+/// Same shape as `api::create_api_router` + main, with stub handlers
+fn app_routes() -> ClassifiedRouter {
+    let budgets = ClassifiedRouter::new()
+        .get("/", ok, TransactionType::BudgetRead)
+        .get("/overview", ok, TransactionType::BudgetOverview)
+        .post("/", ok, TransactionType::BudgetModification)
+        .put("/:id", ok, TransactionType::BudgetModification)
+        .get("/:id", ok, TransactionType::BudgetRead);
+
+    ClassifiedRouter::new()
+        .nest("/api", ClassifiedRouter::new().nest("/budgets", budgets))
+        .get("/health", ok, RateLimitClass::Exempt)
+        .route_unclassified("/legacy", get(ok))
+}
+
+/// Resolves the class the same way `rate_limit_middleware` does
+async fn probe_class(
+    State(routes): State<Arc<RouteTable>>,
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let matched_path = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let class = routes.classify(req.method(), matched_path.as_deref());
+
+    let mut res = next.run(req).await;
+    res.headers_mut()
+        .insert(CLASS_HEADER, class.to_string().parse().unwrap());
+    res
+}
+
+async fn class_of(method: Method, uri: &str) -> (StatusCode, String) {
+    let (router, table) = app_routes().into_parts();
+    let app = router
+        .layer(middleware::from_fn_with_state(Arc::new(table), probe_class));
+
+    let res = app
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let class = res.headers()[CLASS_HEADER].to_str().unwrap().to_string();
+    (res.status(), class)
+}
+
+/// Test: nested budget routes resolve to the class declared next to each route
+/// Why: classification used to be hand-written string matching on request paths
+/// Impact: ensures `/api/budgets/:id` is keyed by the route pattern, not the raw id
+#[tokio::test]
+async fn declared_classes_are_resolved_through_nesting() {
+    let cases = [
+        (Method::GET, "/api/budgets", "budget_read"),
+        (Method::GET, "/api/budgets/overview", "budget_overview"),
+        (Method::POST, "/api/budgets", "budget_modification"),
+        (Method::PUT, "/api/budgets/42", "budget_modification"),
+        (Method::GET, "/api/budgets/42", "budget_read"),
+        (Method::GET, "/health", "exempt"),
+    ];
+
+    for (method, uri, expected) in cases {
+        let (status, class) = class_of(method.clone(), uri).await;
+        assert_eq!(status, StatusCode::OK, "{} {}", method, uri);
+        assert_eq!(class, expected, "{} {}", method, uri);
+    }
+}
+
+/// Test: unclassified routes fall back to the strictest class; unmatched paths to reads
+/// Why: a new endpoint that forgets to opt in must not get a generous limit
+/// Impact: mistakes fail safe and 404 scans stay cheap to serve
+#[tokio::test]
+async fn unclassified_and_unmatched_fallbacks() {
+    let (status, class) = class_of(Method::GET, "/legacy").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(class, "budget_modification");
+
+    let (status, class) = class_of(Method::GET, "/api/unknown").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(class, "budget_read");
+}
+
+/// Test: HEAD requests take the class of the GET route that serves them
+/// Why: axum answers HEAD with GET handlers, but the table only had GET keys
+/// Impact: HEAD probes on exempt or read routes are no longer limited as writes
+#[tokio::test]
+async fn head_requests_use_get_class() {
+    let cases = [
+        ("/api/budgets/42", "budget_read"),
+        ("/api/budgets/overview", "budget_overview"),
+        ("/health", "exempt"),
+    ];
+
+    for (uri, expected) in cases {
+        let (status, class) = class_of(Method::HEAD, uri).await;
+        assert_eq!(status, StatusCode::OK, "HEAD {}", uri);
+        assert_eq!(class, expected, "HEAD {}", uri);
+    }
+
+    let (_, table) = app_routes().into_parts();
+    assert_eq!(
+        table.classify(&Method::HEAD, Some("/legacy")),
+        RateLimitClass::Limited(TransactionType::BudgetModification)
+    );
+}
+
+/// Test: the startup report lists exactly the routes added without a class
+/// Why: `main` relies on it to surface endpoints that skipped classification
+/// Impact: unclassified routes are visible in logs before they take traffic
+#[test]
+fn report_lists_unclassified_routes() {
+    let (_, table) = app_routes().into_parts();
+
+    assert_eq!(table.report(), vec!["/legacy".to_string()]);
+    assert_eq!(table.len(), 6);
+    assert_eq!(
+        table.classify(&Method::DELETE, Some("/api/budgets/:id")),
+        RateLimitClass::Limited(TransactionType::BudgetModification)
+    );
+}
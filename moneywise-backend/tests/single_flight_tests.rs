@@ -0,0 +1,118 @@
+//! Tests for single-flight cache fills (stampede protection).
+
+use moneywise_backend::{
+    cache::core::single_flight::SingleFlight, error::AppError,
+    models::BudgetOverviewApi,
+};
+use rust_decimal::Decimal;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stand-in for `BudgetRepository::overview`: counts calls and takes a while
+async fn load_overview(
+    db_calls: Arc<AtomicUsize>,
+) -> Result<BudgetOverviewApi, AppError> {
+    db_calls.fetch_add(1, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    Ok(BudgetOverviewApi {
+        planned: Decimal::from(1000),
+        spent: Decimal::from(400),
+        remaining: Decimal::from(600),
+        currency: "USD".to_string(),
+    })
+}
+
+/// Test: 50 concurrent misses on one key run the loader exactly once
+/// Why: an expired overview key used to send every dashboard request to Postgres at once
+/// Impact: database load on expiry stays constant regardless of concurrency
+#[tokio::test]
+async fn concurrent_misses_share_one_database_call() {
+    let flight = Arc::new(SingleFlight::new());
+    let db_calls = Arc::new(AtomicUsize::new(0));
+
+    let tasks: Vec<_> = (0..50)
+        .map(|_| {
+            let flight = flight.clone();
+            let db_calls = db_calls.clone();
+            tokio::spawn(async move {
+                flight
+                    .run("moneywise:budget:overview:8:2025", || {
+                        load_overview(db_calls)
+                    })
+                    .await
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        let overview = task.await.unwrap().unwrap();
+        assert_eq!(overview.remaining, Decimal::from(600));
+    }
+    assert_eq!(db_calls.load(Ordering::SeqCst), 1);
+    assert_eq!(flight.in_flight(), 0);
+}
+
+/// Test: different keys are loaded independently
+/// Why: coalescing must be per key, not global
+/// Impact: one slow month does not block other months
+#[tokio::test]
+async fn different_keys_load_independently() {
+    let flight = Arc::new(SingleFlight::new());
+    let db_calls = Arc::new(AtomicUsize::new(0));
+
+    let (a, b) = tokio::join!(
+        flight.run("overview:7:2025", || load_overview(db_calls.clone())),
+        flight.run("overview:8:2025", || load_overview(db_calls.clone())),
+    );
+    assert!(a.is_ok() && b.is_ok());
+    assert_eq!(db_calls.load(Ordering::SeqCst), 2);
+}
+
+/// Test: a completed flight is not reused by later misses
+/// Why: after invalidation the next miss must reload fresh data, not a memoized value
+/// Impact: single-flight never serves stale data on its own
+#[tokio::test]
+async fn sequential_misses_reload() {
+    let flight = SingleFlight::new();
+    let db_calls = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..3 {
+        flight
+            .run("overview:8:2025", || load_overview(db_calls.clone()))
+            .await
+            .unwrap();
+    }
+    assert_eq!(db_calls.load(Ordering::SeqCst), 3);
+}
+
+/// Test: when the leader fails, it gets its own error and waiters get an error too
+/// Why: waiters must not hang or receive a bogus value when the database call fails
+/// Impact: failures surface as 500s instead of a second stampede
+#[tokio::test]
+async fn leader_error_is_shared() {
+    let flight = Arc::new(SingleFlight::new());
+    let db_calls = Arc::new(AtomicUsize::new(0));
+
+    let failing = |db_calls: Arc<AtomicUsize>| async move {
+        db_calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Err::<BudgetOverviewApi, _>(AppError::NotFound(
+            "no budgets".to_string(),
+        ))
+    };
+
+    let (leader, follower) = tokio::join!(
+        flight.run("overview:1:2020", || failing(db_calls.clone())),
+        async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            flight
+                .run("overview:1:2020", || failing(db_calls.clone()))
+                .await
+        },
+    );
+
+    assert!(matches!(leader, Err(AppError::NotFound(_))));
+    assert!(matches!(follower, Err(AppError::Internal(_))));
+    assert_eq!(db_calls.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,285 @@
+//! Tests for cache warm-up periods and stale-while-revalidate reads.
+
+mod common;
+
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate, Utc};
+use common::memory_budget_cache;
+use moneywise_backend::{
+    api::warmup::{warm_budget_cache, warmup_periods},
+    cache::{core::backend::CacheBackend, domains::budget::keys, CacheConfig},
+    error::{AppError, Result},
+    models::{Budget, BudgetOverviewApi, CategoryBudgetApi},
+    repository::{
+        BudgetRepository, InMemoryBudgetRepository, NewBudget,
+        SharedBudgetRepository,
+    },
+};
+use rust_decimal::Decimal;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn overview(planned: i64) -> BudgetOverviewApi {
+    BudgetOverviewApi {
+        planned: Decimal::from(planned),
+        spent: Decimal::ZERO,
+        remaining: Decimal::from(planned),
+        currency: "USD".to_string(),
+    }
+}
+
+/// An in-memory repository with one USD budget in the current month
+fn repo_with_usd_budget() -> SharedBudgetRepository {
+    let repo = InMemoryBudgetRepository::new();
+    let rent = repo.add_category("Rent", "#FF5733", None);
+    let today = Utc::now();
+    repo.insert_budget(Budget {
+        id: uuid::Uuid::new_v4(),
+        month: today.month() as i16,
+        year: today.year(),
+        category_id: rent,
+        planned: Decimal::from(900),
+        spent: Decimal::from(300),
+        carryover: Decimal::ZERO,
+        currency: "USD".to_string(),
+        created_at: today,
+        updated_at: today,
+    });
+    Arc::new(repo)
+}
+
+/// Repository whose USD category budgets fail to load
+struct FailingUsdCategories(SharedBudgetRepository);
+
+#[async_trait]
+impl BudgetRepository for FailingUsdCategories {
+    async fn overview(
+        &self,
+        month: i16,
+        year: i32,
+        currency: Option<&str>,
+    ) -> Result<BudgetOverviewApi> {
+        self.0.overview(month, year, currency).await
+    }
+
+    async fn category_budgets(
+        &self,
+        month: i16,
+        year: i32,
+        currency: Option<&str>,
+    ) -> Result<Vec<CategoryBudgetApi>> {
+        if currency == Some("USD") {
+            return Err(AppError::Internal("database unavailable".into()));
+        }
+        self.0.category_budgets(month, year, currency).await
+    }
+
+    async fn currencies(&self, periods: &[(i16, i32)]) -> Result<Vec<String>> {
+        self.0.currencies(periods).await
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Option<Budget>> {
+        self.0.find(id).await
+    }
+
+    async fn create(&self, budget: NewBudget) -> Result<Budget> {
+        self.0.create(budget).await
+    }
+
+    async fn update(
+        &self,
+        id: Uuid,
+        planned: Decimal,
+        carryover: Decimal,
+    ) -> Result<Option<Budget>> {
+        self.0.update(id, planned, carryover).await
+    }
+}
+
+/// Test: warm-up covers the current and previous month, across year ends
+/// Why: January's previous month is December of the year before
+/// Impact: the first dashboard loads of a new year are warm too
+#[test]
+fn warmup_periods_wrap_years() {
+    let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    assert_eq!(warmup_periods(day(2025, 8, 14)), [(8, 2025), (7, 2025)]);
+    assert_eq!(warmup_periods(day(2026, 1, 1)), [(1, 2026), (12, 2025)]);
+}
+
+/// Test: warm-up caches both months for every budgeted currency
+/// Why: the warm-up reads through `BudgetRepository`, so it runs without
+/// PostgreSQL
+/// Impact: the currency-less and per-currency dashboards start warm
+#[tokio::test]
+async fn warmup_fills_cache_from_repository() {
+    let repo = repo_with_usd_budget();
+    let today = Utc::now();
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
+
+    // 2 months x (no filter, USD) x (overview, categories)
+    assert_eq!(warm_budget_cache(&repo, &cache).await.unwrap(), 8);
+
+    let (month, year) = (today.month().to_string(), today.year().to_string());
+    let warmed = cache
+        .get_cached_budget_overview(&month, &year, Some("USD"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(warmed.remaining, Decimal::from(600));
+    let categories = cache
+        .get_cached_category_budgets(&month, &year, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(categories.len(), 1);
+}
+
+/// Test: warm-up keeps entries that are already cached
+/// Why: overwriting them with a snapshot read earlier could undo a newer write
+/// Impact: a budget write during a warm-up run is never reverted in the cache
+#[tokio::test]
+async fn warmup_keeps_cached_entries() {
+    let repo = repo_with_usd_budget();
+    let today = Utc::now();
+    let (month, year) = (today.month().to_string(), today.year().to_string());
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
+    cache
+        .cache_budget_overview(&month, &year, Some("USD"), &overview(1234))
+        .await
+        .unwrap();
+
+    assert_eq!(warm_budget_cache(&repo, &cache).await.unwrap(), 8);
+
+    let kept = cache
+        .get_cached_budget_overview(&month, &year, Some("USD"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(kept.planned, Decimal::from(1234));
+}
+
+/// Test: a failing entry does not stop the rest of the warm-up
+/// Why: one error used to abort the run and leave later months cold
+/// Impact: the other entries are warmed, and the run is reported as failed
+#[tokio::test]
+async fn warmup_continues_after_failed_entry() {
+    let repo: SharedBudgetRepository =
+        Arc::new(FailingUsdCategories(repo_with_usd_budget()));
+    let (cache, _) = memory_budget_cache(CacheConfig::default());
+
+    let err = warm_budget_cache(&repo, &cache).await.unwrap_err();
+    assert!(err.to_string().contains("2 of 8"), "{}", err);
+
+    let (month, year) = warmup_periods(Utc::now().date_naive())[1];
+    let (month, year) = (month.to_string(), year.to_string());
+    assert!(cache
+        .get_cached_budget_overview(&month, &year, Some("USD"))
+        .await
+        .unwrap()
+        .is_some());
+    assert!(cache
+        .get_cached_category_budgets(&month, &year, None)
+        .await
+        .unwrap()
+        .is_some());
+}
+
+/// Test: a hit close to expiry is served and refreshed in the background
+/// Why: stale-while-revalidate must not turn hot keys into misses
+/// Impact: dashboards keep hitting the cache across TTL boundaries
+#[tokio::test(start_paused = true)]
+async fn near_expiry_hit_is_refreshed_in_background() {
+    let config = CacheConfig {
+        overview_ttl: Duration::from_secs(100),
+        refresh_ahead: Duration::from_secs(30),
+        ..CacheConfig::default()
+    };
+    let (cache, backend) = memory_budget_cache(config);
+    let loads = Arc::new(AtomicUsize::new(0));
+    let load = |planned: i64| {
+        let loads = loads.clone();
+        move || async move {
+            loads.fetch_add(1, Ordering::SeqCst);
+            Ok(overview(planned))
+        }
+    };
+
+    let first = cache
+        .get_or_fill_budget_overview("8", "2025", None, load(1))
+        .await
+        .unwrap();
+    assert_eq!(first.planned, Decimal::from(1));
+
+    // Still fresh: no refresh
+    tokio::time::advance(Duration::from_secs(50)).await;
+    let fresh = cache
+        .get_or_fill_budget_overview("8", "2025", None, load(2))
+        .await
+        .unwrap();
+    assert_eq!(fresh.planned, Decimal::from(1));
+
+    // Within the refresh window: old value served, new one written behind
+    tokio::time::advance(Duration::from_secs(30)).await;
+    let stale = cache
+        .get_or_fill_budget_overview("8", "2025", None, load(3))
+        .await
+        .unwrap();
+    assert_eq!(stale.planned, Decimal::from(1));
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+    let key = keys::overview_key("8", "2025", None);
+    assert_eq!(
+        backend.ttl(&key),
+        Some(Duration::from_secs(100) - Duration::from_millis(10))
+    );
+    let refreshed = cache
+        .get_cached_budget_overview("8", "2025", None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(refreshed.planned, Decimal::from(3));
+}
+
+/// Test: only one background refresh runs per key
+/// Why: every read inside the refresh window would otherwise reload the key
+/// Impact: the database sees one refresh query per key, not one per request
+#[tokio::test(start_paused = true)]
+async fn concurrent_refreshes_are_deduplicated() {
+    let config = CacheConfig {
+        refresh_ahead: Duration::from_secs(30),
+        ..CacheConfig::default()
+    };
+    let (cache, backend) = memory_budget_cache(config);
+    let key = keys::overview_key("9", "2025", None);
+    cache
+        .cache_budget_overview("9", "2025", None, &overview(1))
+        .await
+        .unwrap();
+    // Leave the entry 10s to live
+    let value = backend.get(&key).await.unwrap().unwrap();
+    backend.set_ex(&key, &value, 10).await.unwrap();
+
+    let loads = Arc::new(AtomicUsize::new(0));
+    for _ in 0..5 {
+        let loads = loads.clone();
+        cache
+            .get_or_fill_budget_overview(
+                "9",
+                "2025",
+                None,
+                move || async move {
+                    loads.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    Ok(overview(2))
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+}